@@ -48,6 +48,10 @@ pub const PHANTOM_RANGE: i32 = 1000;
 
 pub const BASIS_SYMBOLS: &[char] = &['A', 'T', 'G', 'C', '*'];
 pub const NB_BASIS_SYMBOLS: usize = BASIS_SYMBOLS.len();
+/// The default colors of the A, T, G and C bases when nucleotides are colored by base
+pub const DEFAULT_BASE_COLORS: [u32; 4] =
+    [0xFF_2E_CC_40, 0xFF_FF_41_36, 0xFF_FF_DC_00, 0xFF_00_74_D9];
+pub const UNASSIGNED_BASE_COLOR: u32 = 0xFF_80_80_80;
 
 pub const BASE_SCROLL_SENSITIVITY: f32 = 0.12;
 
@@ -57,9 +61,13 @@ pub const HELIX_BORDER_COLOR: u32 = 0xFF_101010;
 
 pub const CANDIDATE_COLOR: u32 = 0xBF_00_FF_00;
 pub const SELECTED_COLOR: u32 = 0xBF_FF_00_00;
+pub const SECONDARY_SELECTION_COLOR: u32 = 0xBF_00_FF_FF;
 pub const SUGGESTION_COLOR: u32 = 0xBF_FF_00_FF;
 pub const PIVOT_SPHERE_COLOR: u32 = 0xBF_FF_FF_00;
 pub const FREE_XOVER_COLOR: u32 = 0xBF_00_00_FF;
+pub const FORCE_ARROW_COLOR: u32 = 0xFF_FF_80_00;
+/// The length of the arrow representing a force of magnitude 1 during simulations
+pub const FORCE_ARROW_SCALE: f32 = 0.5;
 
 pub const MAX_ZOOM_2D: f32 = 50.0;
 
@@ -69,6 +77,9 @@ pub const CIRCLE2D_RED: u32 = 0xFF_920303;
 pub const CIRCLE2D_GREEN: u32 = 0xFF_0C9203;
 
 pub const SCAFFOLD_COLOR: u32 = 0xFF_3498DB;
+/// The length of the domains of an automatically routed scaffold, when the routed helices are
+/// empty
+pub const DEFAULT_SCAFFOLD_ROUTE_LENGTH: isize = 32;
 
 pub const SELECTED_HELIX2D_COLOR: u32 = 0xFF_BF_1E_28;
 
@@ -108,3 +119,14 @@ pub const ROTCHAR: char = '\u{e915}';
 pub const LCLICK: char = '\u{e918}';
 pub const MCLICK: char = '\u{e91b}';
 pub const RCLICK: char = '\u{e91a}';
+
+/// Number of nucleotides above which a warning is emitted when loading a design
+pub const LARGE_DESIGN_NUCL_THRESHOLD: usize = 200_000;
+
+/// Maximal distance, in grid cells, at which an empty cell is looked for when building an helix
+/// on an occupied cell
+pub const MAX_FREE_CELL_DISTANCE: isize = 10;
+
+/// Advance of the characters that cannot be drawn in the 2D view, relative to the size of the
+/// characters
+pub const FALLBACK_CHAR_ADVANCE: f32 = 0.5;
@@ -18,8 +18,8 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! This modules defines the type [`Design`](Design) which offers an interface to a DNA nanostructure design.
 use crate::gui::SimulationRequest;
 use ahash::RandomState;
-use std::collections::{BTreeMap, HashMap, HashSet};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
 use ultraviolet::{Mat4, Vec3};
 
@@ -28,6 +28,8 @@ use mediator::{AppNotification, Selection, UndoableOp};
 
 mod controller;
 mod data;
+#[cfg(test)]
+pub mod fixtures;
 mod operation;
 pub mod utils;
 mod view;
@@ -46,33 +48,59 @@ pub struct Design {
     controller: Controller,
     data: Arc<Mutex<Data>>,
     id: usize,
+    /// A set of elements selected independently of the main selection, used by operations that
+    /// need two groups of elements.
+    secondary_selection: Vec<Selection>,
+    /// A callback invoked each time an operation modifies the design
+    change_listener: Mutex<Option<ChangeListener>>,
+    /// The strand states preceding the last operations applied with `apply_operation` that
+    /// modified the strands of the design. Only recorded if `undo_capacity` is positive.
+    undo_stack: VecDeque<StrandState>,
+    /// The strand states undone by `Design::undo`
+    redo_stack: Vec<StrandState>,
+    /// The maximum length of `undo_stack`
+    undo_capacity: usize,
 }
 
+pub type ChangeListener = Box<dyn FnMut(&DesignNotification) + Send>;
+
 impl Design {
     #[allow(dead_code)]
     pub fn new(id: usize) -> Self {
-        let view = Arc::new(Mutex::new(View::new()));
-        let data = Arc::new(Mutex::new(Data::new()));
-        let controller = Controller::new(view.clone(), data.clone());
-        Self {
-            view,
-            data,
-            controller,
-            id,
-        }
+        Self::new_with_data(id, Data::new())
     }
 
     /// Create a new design by reading a file. At the moment only codenano format is supported
     pub fn new_with_path(id: usize, path: &PathBuf) -> Option<Self> {
+        Some(Self::new_with_data(id, Data::new_with_path(path)?))
+    }
+
+    fn new_with_data(id: usize, data: Data) -> Self {
         let view = Arc::new(Mutex::new(View::new()));
-        let data = Arc::new(Mutex::new(Data::new_with_path(path)?));
+        let data = Arc::new(Mutex::new(data));
         let controller = Controller::new(view.clone(), data.clone());
-        Some(Self {
+        Self {
             view,
             data,
             controller,
             id,
-        })
+            secondary_selection: Vec::new(),
+            change_listener: Mutex::new(None),
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            undo_capacity: 0,
+        }
+    }
+
+    /// Load a design, returning a warning alongside it if the design has more than
+    /// `large_threshold` nucleotides.
+    pub fn new_with_path_and_threshold(
+        id: usize,
+        path: &PathBuf,
+        large_threshold: usize,
+    ) -> Option<(Self, Option<DesignLoadWarning>)> {
+        let (data, warning) = Data::new_with_path_and_threshold(path, large_threshold)?;
+        Some((Self::new_with_data(id, data), warning))
     }
 
     /// `true` if the view has been updated since the last time this function was called
@@ -102,16 +130,83 @@ impl Design {
                 design_id: self.id as usize,
             };
             Some(notification)
+        } else if let Some(strands) = self.data.lock().unwrap().strands_update() {
+            let notification = DesignNotification {
+                content: DesignNotificationContent::StrandsChanged(strands),
+                design_id: self.id as usize,
+            };
+            Some(notification)
         } else {
             None
         }
     }
 
+    /// Return all the notifications that must be sent to the observer since the last call to
+    /// this method.
+    ///
+    /// Pending changes are coalesced: the list contains at most one notification of each kind,
+    /// and no `StrandsChanged` notification is emitted if all the instances must be updated.
+    pub fn drain_notifications(&mut self) -> Vec<DesignNotification> {
+        let design_id = self.id as usize;
+        let mut contents = Vec::new();
+        if self.view.lock().unwrap().was_updated() {
+            contents.push(DesignNotificationContent::ModelChanged(
+                self.get_model_matrix(),
+            ));
+        }
+        let mut data = self.data.lock().unwrap();
+        if data.view_need_reset() {
+            contents.push(DesignNotificationContent::ViewNeedReset);
+        }
+        if data.was_updated() {
+            contents.push(DesignNotificationContent::InstanceChanged);
+        } else if let Some(strands) = data.strands_update() {
+            contents.push(DesignNotificationContent::StrandsChanged(strands));
+        }
+        contents
+            .into_iter()
+            .map(|content| DesignNotification { content, design_id })
+            .collect()
+    }
+
+    /// Apply `f` to self without notifying the intermediate modifications. The observers are
+    /// notified of all the modifications at once after the batch.
+    pub fn with_batched_view<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.data.lock().unwrap().begin_batch();
+        f(self);
+        self.data.lock().unwrap().end_batch();
+    }
+
+    /// Return the elements of the secondary selection that belong to the design
+    pub fn get_secondary_selection(&self) -> Vec<Selection> {
+        self.secondary_selection.clone()
+    }
+
+    pub fn set_secondary_selection(&mut self, selection: Vec<Selection>) {
+        self.secondary_selection = selection;
+    }
+
+    /// Return the position of each element of the running simulation and the force applied on
+    /// it, if the forces were requested in the simulation parameters
+    pub fn get_simulation_forces(&self) -> Vec<(Vec3, Vec3)> {
+        self.data.lock().unwrap().get_simulation_forces()
+    }
+
     /// Return the model matrix used to display the design
     pub fn get_model_matrix(&self) -> Mat4 {
         self.view.lock().unwrap().get_model_matrix()
     }
 
+    /// Return true if the model matrix used to display the design mirrors it
+    pub fn is_mirrored(&self) -> bool {
+        self.view.lock().unwrap().is_mirrored()
+    }
+
+    /// Correct the model matrix if it mirrors the design
+    pub fn fix_mirroring(&mut self) {
+        self.view.lock().unwrap().fix_mirroring()
+    }
+
     /// Translate the representation of self
     fn apply_translation(&mut self, translation: &DesignTranslation) -> bool {
         self.controller.translate(translation)
@@ -172,6 +267,75 @@ impl Design {
         }
     }
 
+    /// Return the axis of an helix in a given referential
+    pub fn get_helix_axis(&self, h_id: usize, referential: Referential) -> Option<Axis> {
+        let axis = self.data.lock().unwrap().get_helix_axis(h_id)?;
+        if referential.is_world() {
+            Some(axis.transformed(&self.view.lock().unwrap().model_matrix))
+        } else {
+            Some(axis)
+        }
+    }
+
+    fn get_nucl_position(&self, nucl: &Nucl, referential: Referential) -> Option<Vec3> {
+        let position = self.data.lock().unwrap().get_nucl_position(nucl)?;
+        if referential.is_world() {
+            Some(
+                self.view
+                    .lock()
+                    .unwrap()
+                    .model_matrix
+                    .transform_point3(position),
+            )
+        } else {
+            Some(position)
+        }
+    }
+
+    /// Return the distance, in nanometers, between the centers of two nucleotides.
+    ///
+    /// Return `None` if one of the nucleotides does not belong to a strand of the design.
+    pub fn measure_distance(&self, a: Nucl, b: Nucl, referential: Referential) -> Option<f32> {
+        let pos_a = self.get_nucl_position(&a, referential)?;
+        let pos_b = self.get_nucl_position(&b, referential)?;
+        Some((pos_a - pos_b).mag())
+    }
+
+    /// Return the angle, in radians, formed at `b` by the centers of the nucleotides `a`, `b`
+    /// and `c`.
+    pub fn measure_angle(
+        &self,
+        a: Nucl,
+        b: Nucl,
+        c: Nucl,
+        referential: Referential,
+    ) -> Option<f32> {
+        let pos_b = self.get_nucl_position(&b, referential)?;
+        let ba = self.get_nucl_position(&a, referential)? - pos_b;
+        let bc = self.get_nucl_position(&c, referential)? - pos_b;
+        if ba.mag() < 1e-6 || bc.mag() < 1e-6 {
+            return None;
+        }
+        let cos = ba.normalized().dot(bc.normalized());
+        Some(cos.max(-1.).min(1.).acos())
+    }
+
+    /// Return the identifiers of the strands that go through the same nucleotide several times
+    pub fn find_self_overlapping_strands(&self) -> Vec<usize> {
+        self.data.lock().unwrap().find_self_overlapping_strands()
+    }
+
+    /// Return the estimated width, height and depth, in nanometers, of the folded design.
+    pub fn estimate_folded_dimensions(&self) -> Option<(f32, f32, f32)> {
+        self.data.lock().unwrap().estimate_folded_dimensions()
+    }
+
+    /// Return the `count` helices closest to helix `h_id`, nearest first, with their
+    /// center-to-center distances.
+    pub fn helices_near(&self, h_id: usize, count: usize) -> Vec<(usize, f32)> {
+        self.data.lock().unwrap().helices_near(h_id, count)
+    }
+
     /// Return the `ObjectType` of an element
     pub fn get_object_type(&self, id: u32) -> Option<ObjectType> {
         self.data.lock().unwrap().get_object_type(id)
@@ -206,12 +370,110 @@ impl Design {
             .set_visibility_helix(h_id, visibility)
     }
 
+    pub fn get_visibility_strand(&self, s_id: usize) -> Option<bool> {
+        self.data.lock().unwrap().get_visibility_strand(s_id)
+    }
+
+    pub fn set_visibility_strand(&mut self, s_id: usize, visibility: bool) {
+        self.data
+            .lock()
+            .unwrap()
+            .set_visibility_strand(s_id, visibility)
+    }
+
     /// Return all identifer of bounds
     pub fn get_all_bound_ids(&self) -> Vec<u32> {
         self.data.lock().unwrap().get_all_bound_ids().collect()
     }
 
+    /// Register a callback that is invoked each time `apply_operation` modifies the design.
+    pub fn set_change_listener(&mut self, listener: ChangeListener) {
+        *self.change_listener.lock().unwrap() = Some(listener);
+    }
+
     pub fn apply_operation(&mut self, operation: UndoableOp) -> OperationResult {
+        let content = match &operation {
+            UndoableOp::Rotation(DesignRotation {
+                target: IsometryTarget::Design,
+                ..
+            })
+            | UndoableOp::Translation(DesignTranslation {
+                target: IsometryTarget::Design,
+                ..
+            }) => None,
+            _ => Some(DesignNotificationContent::InstanceChanged),
+        };
+        let result = self.apply_operation_no_notify(operation);
+        if let OperationResult::BigChange(init, _) = &result {
+            if self.undo_capacity > 0 {
+                if self.undo_stack.len() == self.undo_capacity {
+                    self.undo_stack.pop_front();
+                }
+                self.undo_stack.push_back(init.clone());
+                self.redo_stack.clear();
+            }
+        }
+        // The data mutex is released at this point, so the listener may query the design.
+        if !matches!(
+            result,
+            OperationResult::NoChange | OperationResult::GridPlacementFailure(_)
+        ) {
+            if let Some(listener) = self.change_listener.lock().unwrap().as_mut() {
+                let content = content.unwrap_or_else(|| {
+                    DesignNotificationContent::ModelChanged(self.get_model_matrix())
+                });
+                listener(&DesignNotification {
+                    design_id: self.id,
+                    content,
+                });
+            }
+        }
+        result
+    }
+
+    /// Keep the strand states preceding the last `capacity` operations that modified the strands,
+    /// so that they can be restored with `undo`. A capacity of 0, the default, disables this
+    /// history.
+    ///
+    /// This history is meant for editing a design without a mediator, which keeps its own undo
+    /// history. Only the operations that modify the strands alone are recorded.
+    pub fn record_undo_history(&mut self, capacity: usize) {
+        self.undo_capacity = capacity;
+        while self.undo_stack.len() > capacity {
+            self.undo_stack.pop_front();
+        }
+        if capacity == 0 {
+            self.redo_stack.clear();
+        }
+    }
+
+    /// Restore the strands as they were before the last operation that modified them. Return
+    /// false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        if let Some(state) = self.undo_stack.pop_back() {
+            let mut data = self.data.lock().unwrap();
+            self.redo_stack.push(data.get_strand_state());
+            data.new_strand_state(state);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Restore the strands as they were before the last call to `undo`. Return false if there was
+    /// nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        if let Some(state) = self.redo_stack.pop() {
+            let mut data = self.data.lock().unwrap();
+            self.undo_stack.push_back(data.get_strand_state());
+            data.new_strand_state(state);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn apply_operation_no_notify(&mut self, operation: UndoableOp) -> OperationResult {
         match operation {
             UndoableOp::Rotation(rotation) => self.apply_rotation(&rotation),
             UndoableOp::Translation(translation) => {
@@ -223,10 +485,14 @@ impl Design {
             }
             UndoableOp::MakeAllGrids => self.data.lock().unwrap().create_grids(),
             UndoableOp::AddGridHelix(GridHelixDescriptor { grid_id, x, y }, position, length) => {
-                self.data
+                if let Err(e) = self
+                    .data
                     .lock()
                     .unwrap()
                     .build_helix_grid(grid_id, x, y, position, length)
+                {
+                    return OperationResult::GridPlacementFailure(e);
+                }
             }
             UndoableOp::RmGridHelix(GridHelixDescriptor { grid_id, x, y }, position, length) => {
                 if length > 0 {
@@ -250,7 +516,10 @@ impl Design {
                 let after = self.data.lock().unwrap().get_strand_state();
                 return OperationResult::BigChange(init, after);
             }
-            UndoableOp::RmGrid => self.data.lock().unwrap().delete_last_grid(),
+            UndoableOp::RmGrid(g_id) => {
+                self.data.lock().unwrap().delete_grid(g_id);
+            }
+            UndoableOp::RestoreGrid(deleted) => self.data.lock().unwrap().restore_grid(deleted),
             UndoableOp::AddGrid(grid_descriptor) => {
                 self.data.lock().unwrap().add_grid(grid_descriptor);
             }
@@ -363,6 +632,9 @@ impl Design {
             }
             UndoableOp::ClearHyperboloid => self.data.lock().unwrap().clear_hyperboloid(),
             UndoableOp::NewStrandState(state) => self.data.lock().unwrap().new_strand_state(state),
+            UndoableOp::NewExtendedStrandState(state) => {
+                self.data.lock().unwrap().new_extended_strand_state(state)
+            }
             UndoableOp::ResetCopyPaste => self.data.lock().unwrap().reset_copy_paste(),
             UndoableOp::UndoGridSimulation(initial_state) => self
                 .data
@@ -374,6 +646,29 @@ impl Design {
                 .lock()
                 .unwrap()
                 .undo_helix_simulation(initial_state),
+            UndoableOp::NewLayout2D(layout) => {
+                self.data.lock().unwrap().apply_layout_2d(&layout);
+            }
+            UndoableOp::NewRolls(rolls) => self.data.lock().unwrap().set_rolls(&rolls),
+            UndoableOp::SetGridIsometry {
+                g_id,
+                position,
+                orientation,
+            } => self.set_grid_isometry(g_id, position, orientation),
+            UndoableOp::StraightenHelix { helix, h_id, undo } => {
+                if undo {
+                    self.data.lock().unwrap().set_helix(h_id, helix)
+                } else {
+                    self.straighten_helix(h_id)
+                }
+            }
+            UndoableOp::SnapHelixOrientation { helix, h_id, undo } => {
+                if undo {
+                    self.data.lock().unwrap().set_helix(h_id, helix)
+                } else {
+                    self.snap_helix_orientation(h_id);
+                }
+            }
         }
         OperationResult::UndoableChange
     }
@@ -414,20 +709,60 @@ impl Design {
         self.data.lock().unwrap().get_strand_length(strand_id)
     }
 
+    /// Return a map associating to each strand length the number of strands of that length. The
+    /// scaffold is counted only if `include_scaffold` is true.
+    pub fn strand_length_histogram(&self, include_scaffold: bool) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for s_id in self.get_all_strand_ids() {
+            if !include_scaffold && self.is_scaffold(s_id) {
+                continue;
+            }
+            if let Some(length) = self.get_strand_length(s_id) {
+                *histogram.entry(length).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
     /// Return all the identifier of the elements that lie on an helix
     pub fn get_helix_elements(&self, helix_id: usize) -> Vec<u32> {
         self.data.lock().unwrap().get_helix_elements(helix_id)
     }
 
-    /// Save the design in icednano format
-    pub fn save_to(&self, path: &PathBuf) {
-        let result = self.data.lock().unwrap().request_save(path);
-        if result.is_err() {
-            let text = format!("Could not save_file {:?}", result);
+    /// Save the design to `path`.
+    pub fn save_to(&self, path: &PathBuf) -> Result<(), SaveError> {
+        self.data.lock().unwrap().request_save(path)
+    }
+
+    /// Serialize the design in the `icednano` format.
+    pub fn serialize(&self) -> Result<String, serde_json::Error> {
+        self.data.lock().unwrap().to_json_string()
+    }
+
+    /// Create a new design from a string in the `icednano` format.
+    pub fn deserialize(id: usize, json_str: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self::new_with_data(id, Data::from_json_string(json_str)?))
+    }
+
+    /// Save the design to `path` and show an error message if it could not be saved.
+    pub fn save_to_with_dialog(&self, path: &PathBuf) {
+        if let Err(e) = self.save_to(path) {
+            let text = format!("Could not save file: {}", e);
             crate::utils::message(text.into(), rfd::MessageLevel::Error);
         }
     }
 
+    /// Return true if the design was modified since the last time it was saved
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.data.lock().unwrap().has_unsaved_changes()
+    }
+
+    /// Consider the current state of the design as saved. This is meant to be used when the
+    /// design is saved by other means than `save_to`.
+    pub fn mark_saved(&mut self) {
+        self.data.lock().unwrap().mark_saved()
+    }
+
     /// Change the collor of a strand
     pub fn change_strand_color(&mut self, strand_id: usize, color: u32) {
         self.data
@@ -452,6 +787,12 @@ impl Design {
         self.data.lock().unwrap().get_strand_sequence(strand_id)
     }
 
+    /// Return the identifiers of the strands whose sequence contains `query`, ignoring case. An
+    /// 'N' in `query` matches any base.
+    pub fn find_strands_by_sequence(&self, query: &str) -> Vec<usize> {
+        self.data.lock().unwrap().find_strands_by_sequence(query)
+    }
+
     /// Get the basis of the model in the world's coordinates
     pub fn get_basis(&self) -> ultraviolet::Rotor3 {
         let mat4 = self.view.lock().unwrap().get_model_matrix();
@@ -528,6 +869,11 @@ impl Design {
         self.data.lock().unwrap().get_identifier_nucl(nucl)
     }
 
+    /// Return the nucleotide that is paired with `nucl`, if any
+    pub fn get_paired_nucl(&self, nucl: Nucl) -> Option<Nucl> {
+        self.data.lock().unwrap().get_paired_nucl(&nucl)
+    }
+
     pub fn get_identifier_bound(&self, n1: &Nucl, n2: &Nucl) -> Option<u32> {
         self.data.lock().unwrap().get_identifier_bound(n1, n2)
     }
@@ -548,6 +894,47 @@ impl Design {
         self.data.lock().unwrap().prime5_of(&nucl)
     }
 
+    /// Return the nucleotide bound to the 3' side of `nucl`, following cross-overs
+    pub fn neighbor_3prime(&self, nucl: Nucl) -> Option<Nucl> {
+        self.data.lock().unwrap().neighbor_3prime(&nucl)
+    }
+
+    /// Return the nucleotide bound to the 5' side of `nucl`, following cross-overs
+    pub fn neighbor_5prime(&self, nucl: Nucl) -> Option<Nucl> {
+        self.data.lock().unwrap().neighbor_5prime(&nucl)
+    }
+
+    /// Reverse the polarity of a strand, so that its 5' end becomes the complement of its former
+    /// 3' end. Return the strand states before and after the operation.
+    pub fn reverse_strand(&mut self, s_id: usize) -> Option<(StrandState, StrandState)> {
+        self.data.lock().unwrap().reverse_strand(s_id)
+    }
+
+    /// Set the length of the insertion that follows `nucl` on its strand. An insertion of length
+    /// 0 is removed.
+    pub fn set_insertion_length(
+        &mut self,
+        nucl: Nucl,
+        length: usize,
+    ) -> Option<(StrandState, StrandState)> {
+        self.data.lock().unwrap().set_insertion_length(nucl, length)
+    }
+
+    /// Return the identifiers of the strands that are mechanically linked to strand `s_id`, so
+    /// that they can be selected together. Strands are linked when they pair with each other,
+    /// directly or through other strands.
+    pub fn select_connected_component(&self, s_id: usize) -> Vec<usize> {
+        let mut ret: Vec<usize> = self
+            .data
+            .lock()
+            .unwrap()
+            .connected_strands(s_id)
+            .into_iter()
+            .collect();
+        ret.sort_unstable();
+        ret
+    }
+
     pub fn split_strand(&self, nucl: Nucl) {
         self.data.lock().unwrap().split_strand(&nucl, None);
     }
@@ -560,6 +947,19 @@ impl Design {
         self.data.lock().unwrap().remove_helix(helix)
     }
 
+    /// Remove an helix. If strands lie on the helix, they are deleted or split when
+    /// `remove_strands` is true, and the helix is kept otherwise.
+    pub fn remove_helix_with_strands(
+        &mut self,
+        h_id: usize,
+        remove_strands: bool,
+    ) -> Result<(ExtendedStrandState, ExtendedStrandState), HelixRemovalError> {
+        self.data
+            .lock()
+            .unwrap()
+            .remove_helix_with_strands(h_id, remove_strands)
+    }
+
     pub fn get_grid_instance(&self) -> Vec<GridInstance> {
         self.data.lock().unwrap().get_grid_instances(self.id)
     }
@@ -588,10 +988,49 @@ impl Design {
         self.data.lock().unwrap().get_helix_grid(g_id, x, y)
     }
 
+    /// Return the cells of a rectangular region of a grid together with their occupation status.
+    pub fn get_grid_occupancy(
+        &self,
+        g_id: usize,
+        x_range: (isize, isize),
+        y_range: (isize, isize),
+    ) -> Vec<((isize, isize), bool)> {
+        self.data
+            .lock()
+            .unwrap()
+            .get_grid_occupancy(g_id, x_range, y_range)
+    }
+
     pub fn get_grid_position(&self, g_id: usize) -> Option<ultraviolet::Vec3> {
         self.data.lock().unwrap().get_grid_position(g_id)
     }
 
+    /// Move grid `g_b` so that its plane is parallel to the one of grid `g_a`, facing it at
+    /// distance `gap`.
+    ///
+    /// Return the initial position and orientation of grid `g_b`, that can be given to
+    /// `set_grid_isometry` to undo the alignment. Return `None` if the grids were already aligned.
+    pub fn align_grids(
+        &mut self,
+        g_a: usize,
+        g_b: usize,
+        gap: f32,
+    ) -> Option<(Vec3, ultraviolet::Rotor3)> {
+        self.data.lock().unwrap().align_grids(g_a, g_b, gap)
+    }
+
+    pub fn set_grid_isometry(
+        &mut self,
+        g_id: usize,
+        position: Vec3,
+        orientation: ultraviolet::Rotor3,
+    ) {
+        self.data
+            .lock()
+            .unwrap()
+            .set_grid_isometry(g_id, position, orientation)
+    }
+
     pub fn get_grid_latice_position(
         &self,
         g_id: usize,
@@ -615,7 +1054,7 @@ impl Design {
         y: isize,
         position: isize,
         length: usize,
-    ) {
+    ) -> Result<(), GridPlacementError> {
         self.data
             .lock()
             .unwrap()
@@ -679,6 +1118,36 @@ impl Design {
         self.data.lock().unwrap().set_isometry_2d(h_id, isometry)
     }
 
+    /// Write the 2d isometries of the helices in a json file
+    pub fn export_layout_2d(&self, path: &Path) -> std::io::Result<()> {
+        let layout = self.data.lock().unwrap().get_layout_2d();
+        let json_content = serde_json::to_string_pretty(&layout)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json_content)
+    }
+
+    /// Read a file written by `export_layout_2d` and set the 2d isometries of the helices of the
+    /// design accordingly. Helices that are not in the design are ignored.
+    ///
+    /// Return the initial and the new layout of the modified helices.
+    pub fn import_layout_2d(&mut self, path: &Path) -> std::io::Result<(Layout2D, Layout2D)> {
+        let json_str = std::fs::read_to_string(path)?;
+        let layout: Layout2D = serde_json::from_str(&json_str)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(self.data.lock().unwrap().apply_layout_2d(&layout))
+    }
+
+    /// Read a csv file of (strand identifier, sequence) rows and assign the sequences to the
+    /// corresponding strands. Return the number of assigned sequences and the initial and new
+    /// strand states, or an error message listing the rows that could not be applied.
+    pub fn import_sequences_csv(
+        &mut self,
+        path: &Path,
+    ) -> Result<(usize, StrandState, StrandState), String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        self.data.lock().unwrap().assign_sequences_csv(&content)
+    }
+
     pub fn is_xover_end(&self, nucl: &Nucl) -> Extremity {
         self.data.lock().unwrap().is_xover_end(nucl)
     }
@@ -719,6 +1188,20 @@ impl Design {
         self.data.lock().unwrap().set_scaffold_id(scaffold_id)
     }
 
+    /// Read the first record of a FASTA file and use it as the sequence of the scaffold. Return
+    /// the length of the sequence.
+    pub fn set_scaffold_sequence_from_fasta(
+        &mut self,
+        path: &PathBuf,
+        shift: usize,
+    ) -> Result<usize, FastaError> {
+        let content = std::fs::read_to_string(path)?;
+        let sequence = parse_fasta(&content)?;
+        let len = sequence.len();
+        self.set_scaffold_sequence(sequence, shift);
+        Ok(len)
+    }
+
     pub fn set_scaffold_sequence(&mut self, sequence: String, shift: usize) {
         self.data
             .lock()
@@ -754,10 +1237,106 @@ impl Design {
         self.data.lock().unwrap().get_stapples()
     }
 
+    /// Return an error message if the stapples of the design cannot be exported, i.e. if the
+    /// scaffold or its sequence is not set.
+    pub fn stapples_export_error(&self) -> Option<&'static str> {
+        if !self.scaffold_is_set() {
+            Some(
+                "No scaffold set. \n
+                    Chose a strand and set it as the scaffold by checking the scaffold checkbox\
+                    in the status bar",
+            )
+        } else if !self.scaffold_sequence_set() {
+            Some(
+                "No sequence uploaded for scaffold. \n
+                Upload a sequence for the scaffold by pressing the \"Load scaffold\" button",
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Write the stapples of the design, with their ends, color and plate/well assignment, to a
+    /// json file.
+    pub fn export_stapples_json(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(msg) = self.stapples_export_error() {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, msg));
+        }
+        let records: Vec<StappleRecord> =
+            self.get_stapples().iter().map(Stapple::to_record).collect();
+        let json_content = serde_json::to_string_pretty(&records)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        std::fs::write(path, json_content)
+    }
+
+    /// Write the stapples of the design to a csv file, one row per stapple. Stapples whose
+    /// sequence is not fully known have an empty sequence cell.
+    pub fn export_staples_csv(&self, path: &PathBuf) -> std::io::Result<()> {
+        let mut content = String::from(STAPLES_CSV_HEADER);
+        content.push('\n');
+        for stapple in self.get_stapples() {
+            let record = stapple.to_record();
+            let end = |end: Option<(usize, isize)>| {
+                end.map(|(h, nt)| (h.to_string(), nt.to_string()))
+                    .unwrap_or_default()
+            };
+            let (helix5, position5) = end(record.prime5);
+            let (helix3, position3) = end(record.prime3);
+            let sequence: String = if record.sequence.contains('?') {
+                String::new()
+            } else {
+                record
+                    .sequence
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect()
+            };
+            content.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                record.id, helix5, position5, helix3, position3, record.length, sequence
+            ));
+        }
+        std::fs::write(path, content)
+    }
+
+    /// Write the torsions induced by the cross-overs of the design to a csv file, one row per
+    /// entry of `get_torsions`.
+    pub fn export_torsions_csv(&self, path: &PathBuf) -> std::io::Result<()> {
+        let mut torsions: Vec<((Nucl, Nucl), Torsion)> = self.get_torsions().into_iter().collect();
+        torsions.sort_by_key(|(xover, _)| *xover);
+        let mut content = String::from(TORSIONS_CSV_HEADER);
+        content.push('\n');
+        for ((n1, n2), torsion) in torsions {
+            content.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                n1.helix,
+                n1.position,
+                n1.forward,
+                n2.helix,
+                n2.position,
+                n2.forward,
+                torsion.strength_prime5,
+                torsion.strength_prime3,
+                torsion.strength_prime5 - torsion.strength_prime3
+            ));
+        }
+        std::fs::write(path, content)
+    }
+
     pub fn optimize_shift(&self, channel: std::sync::mpsc::Sender<f32>) -> (usize, String) {
         self.data.lock().unwrap().optimize_shift(channel)
     }
 
+    /// Adjust the roll of the helices to minimize the tension of the cross-overs, sending the
+    /// progress on `channel`. Return the sum of the squared lengths of the cross-overs, and the
+    /// initial rolls of the modified helices.
+    pub fn optimize_rolls(
+        &self,
+        channel: std::sync::mpsc::Sender<f32>,
+    ) -> (f32, BTreeMap<usize, f32>) {
+        self.data.lock().unwrap().optimize_rolls(channel)
+    }
+
     /// Return the map whose keys are the id of strands that are in a group and the values are the
     /// corresponding group.
     pub fn get_groups(&self) -> Arc<RwLock<BTreeMap<usize, bool>>> {
@@ -770,7 +1349,13 @@ impl Design {
     }
 
     pub fn get_suggestions(&self) -> Vec<(Nucl, Nucl)> {
-        self.data.lock().unwrap().get_suggestions()
+        self.get_suggestions_within(data::DEFAULT_SUGGESTION_DISTANCE)
+    }
+
+    /// Return the cross-over suggestions between nucleotides that are less than `max_dist`
+    /// nanometers apart.
+    pub fn get_suggestions_within(&self, max_dist: f32) -> Vec<(Nucl, Nucl)> {
+        self.data.lock().unwrap().get_suggestions(max_dist)
     }
 
     /// Return a string describing the decomposition of the length of the strand `s_id` into the
@@ -784,12 +1369,40 @@ impl Design {
         self.data.lock().unwrap().recolor_stapples();
     }
 
+    /// Color the strands by cycling through the colors of `palette` in increasing strand
+    /// identifier order. If `skip_scaffold` is true, the scaffold keeps its color and does not
+    /// consume a color of the palette.
+    pub fn apply_color_palette(&mut self, palette: &[u32], skip_scaffold: bool) {
+        if palette.is_empty() {
+            return;
+        }
+        let mut s_ids = self.get_all_strand_ids();
+        s_ids.sort_unstable();
+        if skip_scaffold {
+            s_ids.retain(|s_id| !self.is_scaffold(*s_id));
+        }
+        for (s_id, color) in s_ids.into_iter().zip(palette.iter().cycle()) {
+            self.change_strand_color(s_id, *color);
+        }
+    }
+
     pub fn oxdna_export(&self) {
         self.data.lock().unwrap().oxdna_export();
     }
 
+    /// Write a PDB file in which each nucleotide is represented by the position of its
+    /// backbone, with one chain per strand.
+    pub fn export_pdb(&self, path: &PathBuf) -> std::io::Result<()> {
+        self.data.lock().unwrap().export_pdb(path)
+    }
+
+    /// Return the oxDNA topology and configuration of the design, without writing them to disk.
+    pub fn oxdna_strings(&self) -> (String, String) {
+        self.data.lock().unwrap().oxdna_strings()
+    }
+
     /// Merge all the consecutives domains in the design
-    pub fn clean_up_domains(&mut self) {
+    pub fn clean_up_domains(&mut self) -> CleanupReport {
         self.data.lock().unwrap().clean_up_domains()
     }
 
@@ -840,6 +1453,76 @@ impl Design {
         self.data.lock().unwrap().get_roll_helix(h_id)
     }
 
+    /// Reset the roll of an helix and align its axis with its grid or with the closest principal
+    /// axis.
+    pub fn straighten_helix(&mut self, h_id: usize) {
+        self.data.lock().unwrap().straighten_helix(h_id);
+    }
+
+    /// Align the axis of a free helix with the closest principal axis without changing its
+    /// roll. Return the helix as it was before the modification, or `None` if the helix is on a
+    /// grid.
+    pub fn snap_helix_orientation(&mut self, h_id: usize) -> Option<Helix> {
+        self.data.lock().unwrap().snap_helix_orientation(h_id)
+    }
+
+    /// Return the paired nucleotides whose bases are not complementary.
+    pub fn check_base_pairing(&self) -> Vec<(Nucl, Nucl)> {
+        self.data.lock().unwrap().check_base_pairing()
+    }
+
+    /// Delete a grid, the helices that were attached to it become free helices. Return what is
+    /// needed to undo the deletion.
+    pub fn delete_grid(&mut self, g_id: usize) -> Option<DeletedGrid> {
+        self.data.lock().unwrap().delete_grid(g_id)
+    }
+
+    pub fn restore_grid(&mut self, deleted: DeletedGrid) {
+        self.data.lock().unwrap().restore_grid(deleted)
+    }
+
+    /// Create an helix on every empty position of a grid. Return the number of created helices.
+    pub fn fill_grid(&mut self, g_id: usize, position: isize, length: usize) -> usize {
+        self.data.lock().unwrap().fill_grid(g_id, position, length)
+    }
+
+    /// Create an helix on every empty position of a grid. Return the states of the design before
+    /// and after the operation, or `None` if no helix was created.
+    pub fn undoable_fill_grid(
+        &mut self,
+        g_id: usize,
+        position: isize,
+        length: usize,
+    ) -> Option<(ExtendedStrandState, ExtendedStrandState)> {
+        self.data
+            .lock()
+            .unwrap()
+            .undoable_fill_grid(g_id, position, length)
+    }
+
+    /// Return the length in nanometers of the path going through the nucleotides of a strand.
+    pub fn strand_contour_length(&self, s_id: usize) -> Option<f32> {
+        self.data.lock().unwrap().strand_contour_length(s_id)
+    }
+
+    /// Return the average position of the selected nucleotides.
+    pub fn selection_center_of_mass(&self, selection: &[Selection]) -> Option<Vec3> {
+        self.data
+            .lock()
+            .unwrap()
+            .selection_center_of_mass(selection)
+    }
+
+    /// Return the extremal positions of the nucleotides on an helix, and their number.
+    pub fn helix_occupancy(&self, h_id: usize) -> Option<(isize, isize, usize)> {
+        self.data.lock().unwrap().helix_occupancy(h_id)
+    }
+
+    /// Return the axes of the helices `h_ids` projected on the plane perpendicular to `normal`.
+    pub fn project_to_plane(&self, h_ids: &[usize], normal: Vec3) -> Vec<(usize, Vec<Vec3>)> {
+        self.data.lock().unwrap().project_to_plane(h_ids, normal)
+    }
+
     pub fn request_copy(&mut self, nucl: Nucl) {
         if let Some(s_id) = self.get_strand_nucl(&nucl) {
             self.data.lock().unwrap().set_templates(vec![s_id])
@@ -862,6 +1545,14 @@ impl Design {
         self.data.lock().unwrap().paste_xovers(nucl, false);
     }
 
+    /// Create a scaffold strand going back and forth on all the helices of a grid
+    pub fn auto_route_scaffold(
+        &mut self,
+        g_id: usize,
+    ) -> Option<(ExtendedStrandState, ExtendedStrandState)> {
+        self.data.lock().unwrap().auto_route_scaffold(g_id)
+    }
+
     pub fn paste(&mut self, nucl: Nucl) -> Option<(StrandState, StrandState)> {
         self.data.lock().unwrap().set_copy(Some(nucl));
         self.data.lock().unwrap().apply_copy()
@@ -949,10 +1640,29 @@ impl Design {
         self.data.lock().unwrap().is_anchor(nucl)
     }
 
+    /// Return a channel on which frames of the trajectory of the running helices simulation are
+    /// sent, or `None` if no helices simulation is running.
+    pub fn subscribe_trajectory(&self) -> Option<std::sync::mpsc::Receiver<TrajectoryFrame>> {
+        self.data.lock().unwrap().subscribe_trajectory()
+    }
+
+    pub fn set_helix_pinned(&mut self, h_id: usize, pinned: bool) {
+        self.data.lock().unwrap().set_helix_pinned(h_id, pinned)
+    }
+
+    pub fn is_helix_pinned(&self, h_id: usize) -> bool {
+        self.data.lock().unwrap().is_helix_pinned(h_id)
+    }
+
     pub fn shake_nucl(&self, nucl: Nucl) {
         self.data.lock().unwrap().shake_nucl(nucl)
     }
 
+    /// Give a random impulse to all the elements of the running helix simulation, if any.
+    pub fn shake_all_during_simulation(&self) {
+        self.data.lock().unwrap().shake_all()
+    }
+
     pub fn set_new_shift(&mut self, g_id: usize, shift: f32) {
         self.data.lock().unwrap().set_new_shift(g_id, shift)
     }
@@ -972,6 +1682,7 @@ impl Design {
                 DnaAttribute::Visible(b) => match elt {
                     DnaElementKey::Helix(h) => data.set_visibility_helix(*h, b),
                     DnaElementKey::Grid(g) => data.set_visibility_grid(*g, b),
+                    DnaElementKey::Strand(s) => data.set_visibility_strand(*s, b),
                     _ => (),
                 },
                 DnaAttribute::XoverGroup(g) => match elt {
@@ -1022,10 +1733,116 @@ impl Design {
         }
     }
 
+    /// Add mirrored copies of the selected helices and strands, reflected across the plane going
+    /// through `plane_point` with normal `plane_normal`.
+    pub fn mirror_selection(
+        &mut self,
+        selection: Vec<Selection>,
+        plane_point: Vec3,
+        plane_normal: Vec3,
+    ) -> Option<(ExtendedStrandState, ExtendedStrandState)> {
+        self.data
+            .lock()
+            .unwrap()
+            .mirror_selection(selection, plane_point, plane_normal)
+    }
+
+    /// Add mirrored copies of the selected helices and strands, reflected across one of the
+    /// principal planes going through the origin of the design.
+    pub fn duplicate_mirrored(
+        &mut self,
+        selection: &[Selection],
+        plane: MirrorPlane,
+    ) -> Option<(ExtendedStrandState, ExtendedStrandState)> {
+        self.mirror_selection(selection.to_vec(), Vec3::zero(), plane.normal())
+    }
+
+    /// Return a new design made of the selected strands and the helices on which they lie,
+    /// together with the selected helices.
+    pub fn extract_selection(&self, selection: &[Selection]) -> Self {
+        let data = self.data.lock().unwrap().extract_selection(selection);
+        Self::new_with_data(self.id, data)
+    }
+
+    /// Delete all the strands except the scaffold.
+    ///
+    /// If no scaffold is set, this would delete all the strands of the design, so nothing is
+    /// done unless `even_without_scaffold` is true.
+    pub fn delete_all_staples(
+        &mut self,
+        even_without_scaffold: bool,
+    ) -> Option<(StrandState, StrandState)> {
+        let staples: Vec<Selection> = {
+            let data = self.data.lock().unwrap();
+            if !data.scaffold_is_set() && !even_without_scaffold {
+                return None;
+            }
+            data.get_all_strand_ids()
+                .into_iter()
+                .filter(|s_id| !data.is_scaffold(*s_id))
+                .map(|s_id| Selection::Strand(self.id as u32, s_id as u32))
+                .collect()
+        };
+        if staples.is_empty() {
+            None
+        } else {
+            self.delete_selection(staples)
+        }
+    }
+
+    /// Return the identifiers of the strands made of a single nucleotide. The scaffold is never
+    /// returned.
+    pub fn find_singleton_strands(&self) -> Vec<usize> {
+        let data = self.data.lock().unwrap();
+        data.get_all_strand_ids()
+            .into_iter()
+            .filter(|s_id| !data.is_scaffold(*s_id) && data.get_strand_length(*s_id) == Some(1))
+            .collect()
+    }
+
+    /// Delete all the strands made of a single nucleotide, except the scaffold.
+    pub fn remove_singleton_strands(&mut self) -> Option<(StrandState, StrandState)> {
+        let singletons: Vec<Selection> = self
+            .find_singleton_strands()
+            .into_iter()
+            .map(|s_id| Selection::Strand(self.id as u32, s_id as u32))
+            .collect();
+        if singletons.is_empty() {
+            None
+        } else {
+            self.delete_selection(singletons)
+        }
+    }
+
     pub fn get_scaffold_info(&self) -> Option<ScaffoldInfo> {
         self.data.lock().unwrap().get_scaffold_info()
     }
 
+    /// Return the nucleotides of the scaffold in 5' to 3' order, or `None` if no scaffold is
+    /// set. The nucleotides of insertions are not part of the route.
+    pub fn get_scaffold_route(&self) -> Option<Vec<Nucl>> {
+        let s_id = self.get_scaffold_info()?.id;
+        let points = self.get_strand_points(s_id)?;
+        let mut route = Vec::new();
+        // The points come by pairs of domain extremities. For cyclic strands, the last point is
+        // a copy of the first one and is ignored by `chunks_exact`.
+        for extremities in points.chunks_exact(2) {
+            let (first, last) = (extremities[0], extremities[1]);
+            if first.forward {
+                route.extend((first.position..=last.position).map(|p| Nucl {
+                    position: p,
+                    ..first
+                }));
+            } else {
+                route.extend((last.position..=first.position).rev().map(|p| Nucl {
+                    position: p,
+                    ..first
+                }));
+            }
+        }
+        Some(route)
+    }
+
     pub fn has_at_least_on_strand_with_insertions(&self) -> bool {
         self.data
             .lock()
@@ -1059,6 +1876,9 @@ pub enum DesignNotificationContent {
     ModelChanged(Mat4),
     /// The design was modified
     InstanceChanged,
+    /// Only the strands in the set were modified, and the identifiers of their elements did not
+    /// change
+    StrandsChanged(HashSet<usize>),
     ViewNeedReset,
 }
 
@@ -1091,6 +1911,8 @@ pub enum OperationResult {
     BigChange(StrandState, StrandState),
     UndoableChange,
     NoChange,
+    /// Nothing was changed because the helix could not be added to the grid
+    GridPlacementFailure(GridPlacementError),
 }
 
 #[derive(Clone, Debug)]
@@ -1100,3 +1922,617 @@ pub struct ScaffoldInfo {
     pub length: usize,
     pub starting_nucl: Option<Nucl>,
 }
+
+/// A principal plane going through the origin of a design
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorPlane {
+    XY,
+    YZ,
+    ZX,
+}
+
+impl MirrorPlane {
+    pub fn normal(&self) -> Vec3 {
+        match self {
+            Self::XY => Vec3::unit_z(),
+            Self::YZ => Vec3::unit_x(),
+            Self::ZX => Vec3::unit_y(),
+        }
+    }
+}
+
+/// The first line of the csv files written by `Design::export_staples_csv`
+const STAPLES_CSV_HEADER: &str =
+    "strand,start helix,start position,end helix,end position,length,sequence";
+
+/// The first line of the csv files written by `Design::export_torsions_csv`
+const TORSIONS_CSV_HEADER: &str = "helix 1,position 1,forward 1,helix 2,position 2,forward 2,\
+strength prime5,strength prime3,difference";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::design_two_strands;
+
+    #[test]
+    fn layout_2d_round_trip() {
+        let mut design = design_two_strands();
+        let path = std::env::temp_dir().join("ensnano_layout_2d_test.json");
+        let isometry_1 = design.get_isometry(1).unwrap();
+        let isometry_2 = design.get_isometry(2).unwrap();
+        design
+            .export_layout_2d(&path)
+            .expect("Could not export layout");
+
+        let moved = ultraviolet::Isometry2::new(
+            ultraviolet::Vec2::new(-3., 12.),
+            ultraviolet::Rotor2::from_angle(1.),
+        );
+        design.set_isometry(1, moved);
+        design.set_isometry(2, moved);
+
+        let (initial, applied) = design
+            .import_layout_2d(&path)
+            .expect("Could not import layout");
+        let _ = std::fs::remove_file(path);
+        assert_eq!(applied.isometries.len(), 2);
+        assert_eq!(initial.isometries.get(&1), Some(&Some(moved)));
+        assert_eq!(design.get_isometry(1), Some(isometry_1));
+        assert_eq!(design.get_isometry(2), Some(isometry_2));
+    }
+
+    #[test]
+    fn edits_between_drains_are_coalesced() {
+        let mut design = design_two_strands();
+        design.drain_notifications();
+        for i in 0..10 {
+            design.change_strand_sequence(i % 2, "ATGCATGCAT".repeat(i + 1));
+        }
+        let notifications = design.drain_notifications();
+        assert_eq!(notifications.len(), 1);
+        // Sequence and color changes only modify the instances of the edited strands
+        assert!(matches!(
+            &notifications[0].content,
+            DesignNotificationContent::StrandsChanged(strands) if strands.len() == 2
+        ));
+        assert!(design.drain_notifications().is_empty());
+    }
+
+    #[test]
+    fn mirrored_model_matrix_is_fixed() {
+        let mut design = design_two_strands();
+        assert!(!design.is_mirrored());
+        let mut mirrored =
+            Mat4::from_translation(Vec3::new(1., 2., 3.)) * Mat4::from_rotation_y(0.5);
+        mirrored.cols[2] = -mirrored.cols[2];
+        design.view.lock().unwrap().set_matrix(mirrored);
+        assert!(design.is_mirrored());
+
+        design.fix_mirroring();
+        assert!(!design.is_mirrored());
+        let fixed = design.get_model_matrix();
+        assert_eq!(fixed.cols[1], mirrored.cols[1]);
+        assert_eq!(fixed.cols[2], mirrored.cols[2]);
+        assert_eq!(fixed.cols[3], mirrored.cols[3]);
+    }
+
+    #[test]
+    fn sequences_imported_from_csv() {
+        let mut design = design_two_strands();
+        let path = std::env::temp_dir().join("ensnano_sequences_test.csv");
+        std::fs::write(
+            &path,
+            "strand,sequence\n0,AAAAA TTTTT\nStapple 0001; 5':h2:nt9>3':h2:nt0,GGGGGCCCCC\n",
+        )
+        .unwrap();
+        let (nb_assigned, init, _) = design.import_sequences_csv(&path).unwrap();
+        assert_eq!(nb_assigned, 2);
+        assert_eq!(design.get_strand_sequence(0).as_deref(), Some("AAAAATTTTT"));
+        assert_eq!(design.get_strand_sequence(1).as_deref(), Some("GGGGGCCCCC"));
+
+        std::fs::write(&path, "0,ATGC\n1,CCCCCGGGGG\n").unwrap();
+        let error = design.import_sequences_csv(&path).unwrap_err();
+        assert!(error.contains("line 1"));
+        assert!(!error.contains("line 2"));
+        assert_eq!(design.get_strand_sequence(1).as_deref(), Some("GGGGGCCCCC"));
+
+        design.apply_operation(UndoableOp::NewStrandState(init));
+        assert_eq!(design.get_strand_sequence(0).as_deref(), Some(""));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn stapples_json_export_round_trip() {
+        let mut design = design_two_strands();
+        let path = std::env::temp_dir().join("ensnano_stapples_test.json");
+        assert!(design.export_stapples_json(&path).is_err());
+        design.set_scaffold_id(Some(1));
+        assert!(design.export_stapples_json(&path).is_err());
+        design.set_scaffold_sequence("ATGC".repeat(5), 0);
+        design.export_stapples_json(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<StappleRecord> = serde_json::from_str(&content).unwrap();
+        let stapples = design.get_stapples();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records.len(), stapples.len());
+        for (record, stapple) in records.iter().zip(stapples.iter()) {
+            assert_eq!(record.sequence, stapple.sequence);
+        }
+        assert_eq!(records[0].id, 0);
+        assert_eq!(records[0].length, 10);
+        assert_eq!(records[0].prime5, Some((1, 0)));
+        assert_eq!(records[0].prime3, Some((1, 9)));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn mirrored_helix_is_reflected() {
+        let mut design = design_two_strands();
+        let original = design.get_raw_helix(1).unwrap();
+        assert!(design
+            .mirror_selection(
+                vec![Selection::Helix(0, 1), Selection::Strand(0, 0)],
+                Vec3::zero(),
+                Vec3::unit_z(),
+            )
+            .is_some());
+        let copy = design.get_raw_helix(3).unwrap();
+        let expected = Vec3::new(
+            original.position.x,
+            original.position.y,
+            -original.position.z,
+        );
+        assert!((copy.position - expected).mag() < 1e-5);
+
+        let axis = Vec3::unit_x().rotated_by(original.orientation);
+        let mirrored_axis = Vec3::unit_x().rotated_by(copy.orientation);
+        assert!((mirrored_axis - Vec3::new(axis.x, axis.y, -axis.z)).mag() < 1e-4);
+
+        assert_eq!(design.get_all_strand_ids(), vec![0, 1, 2]);
+        assert_eq!(design.get_strand_length(2), Some(10));
+    }
+
+    #[test]
+    fn helix_duplicated_across_yz_plane() {
+        let mut design = design_two_strands();
+        design.apply_operation(UndoableOp::RawHelixCreation {
+            helix: Helix::new(Vec3::new(3., 1., -2.), ultraviolet::Rotor3::identity()),
+            h_id: 10,
+            delete: false,
+        });
+        assert!(design
+            .duplicate_mirrored(&[Selection::Helix(0, 10)], MirrorPlane::YZ)
+            .is_some());
+        let copy = design.get_raw_helix(11).unwrap();
+        assert!((copy.position - Vec3::new(-3., 1., -2.)).mag() < 1e-5);
+        let mirrored_axis = Vec3::unit_x().rotated_by(copy.orientation);
+        assert!((mirrored_axis + Vec3::unit_x()).mag() < 1e-4);
+    }
+
+    #[test]
+    fn undo_mirroring_removes_mirrored_helices() {
+        let mut design = design_two_strands();
+        let (init, after) = design
+            .mirror_selection(
+                vec![Selection::Helix(0, 1), Selection::Strand(0, 0)],
+                Vec3::zero(),
+                Vec3::unit_z(),
+            )
+            .unwrap();
+        design.apply_operation(UndoableOp::NewExtendedStrandState(init));
+        assert!(design.get_raw_helix(3).is_none());
+        assert_eq!(design.get_all_strand_ids(), vec![0, 1]);
+        design.apply_operation(UndoableOp::NewExtendedStrandState(after));
+        assert!(design.get_raw_helix(3).is_some());
+        assert_eq!(design.get_all_strand_ids(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn listener_is_called_on_undoable_change() {
+        let mut design = design_two_strands();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = calls.clone();
+        design.set_change_listener(Box::new(move |notification| {
+            calls_clone.lock().unwrap().push(notification.clone())
+        }));
+        design.apply_operation(UndoableOp::RawHelixCreation {
+            helix: Helix::new(Vec3::zero(), ultraviolet::Rotor3::identity()),
+            h_id: 10,
+            delete: false,
+        });
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].design_id, 0);
+        assert!(matches!(
+            calls[0].content,
+            DesignNotificationContent::InstanceChanged
+        ));
+    }
+
+    #[test]
+    fn distance_between_consecutive_nucleotides() {
+        let design = design_two_strands();
+        let parameters = design.get_dna_parameters();
+        let nucl = |position| Nucl {
+            helix: 1,
+            position,
+            forward: true,
+        };
+        let beta = 2. * std::f32::consts::PI / parameters.bases_per_turn;
+        let chord = 2. * parameters.helix_radius * (beta / 2.).sin();
+        let expected = (parameters.z_step.powi(2) + chord.powi(2)).sqrt();
+        for i in 0..9 {
+            let d = design
+                .measure_distance(nucl(i), nucl(i + 1), Referential::Model)
+                .unwrap();
+            assert!((d - expected).abs() < 1e-4);
+        }
+        let world = design
+            .measure_distance(nucl(0), nucl(9), Referential::World)
+            .unwrap();
+        let model = design
+            .measure_distance(nucl(0), nucl(9), Referential::Model)
+            .unwrap();
+        assert!((world - model).abs() < 1e-4);
+        assert!(design
+            .measure_distance(nucl(0), nucl(20), Referential::Model)
+            .is_none());
+    }
+
+    #[test]
+    fn angle_along_a_helix() {
+        let design = design_two_strands();
+        let nucl = |position| Nucl {
+            helix: 1,
+            position,
+            forward: true,
+        };
+        let angle = design
+            .measure_angle(nucl(0), nucl(1), nucl(2), Referential::Model)
+            .unwrap();
+        assert!(angle > std::f32::consts::FRAC_PI_2 && angle < std::f32::consts::PI);
+        let flat = design
+            .measure_angle(nucl(0), nucl(1), nucl(0), Referential::Model)
+            .unwrap();
+        assert!(flat.abs() < 1e-3);
+        assert!(design
+            .measure_angle(nucl(0), nucl(1), nucl(30), Referential::Model)
+            .is_none());
+    }
+
+    #[test]
+    fn saving_to_unwritable_path_fails() {
+        let design = design_two_strands();
+        let path = std::env::temp_dir()
+            .join("ensnano_missing_directory")
+            .join("design.json");
+        let result = design.save_to(&path);
+        assert!(matches!(result, Err(SaveError::Io(_))));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn palette_is_applied_in_id_order() {
+        let mut design = design_two_strands();
+        design.split_strand(Nucl {
+            helix: 1,
+            position: 4,
+            forward: true,
+        });
+        assert_eq!(design.get_all_strand_ids(), vec![0, 1, 2]);
+        let palette = [0xFF_FF0000, 0xFF_00FF00];
+        design.apply_color_palette(&palette, false);
+        let colors: Vec<_> = (0..3)
+            .map(|s_id| design.get_strand_color(s_id).unwrap())
+            .collect();
+        assert_eq!(colors, vec![palette[0], palette[1], palette[0]]);
+
+        design.set_scaffold_id(Some(1));
+        design.change_strand_color(1, 0xFF_0000FF);
+        design.apply_color_palette(&palette[1..], true);
+        assert_eq!(design.get_strand_color(0), Some(palette[1]));
+        assert_eq!(design.get_strand_color(1), Some(0xFF_0000FF));
+        assert_eq!(design.get_strand_color(2), Some(palette[1]));
+    }
+
+    #[test]
+    fn scaffold_sequence_from_fasta() {
+        let mut design = design_two_strands();
+        design.set_scaffold_id(Some(0));
+        let path = std::env::temp_dir().join("ensnano_scaffold_test.fasta");
+        std::fs::write(
+            &path,
+            ">scaffold test\nATGCA\n  tgcau \n\n>second record\nGGGG\n",
+        )
+        .unwrap();
+        assert_eq!(
+            design.set_scaffold_sequence_from_fasta(&path, 0).unwrap(),
+            10
+        );
+        assert_eq!(design.get_scaffold_sequence_len(), Some(10));
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(parse_fasta(&content).unwrap(), "ATGCATGCAT");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn fasta_with_invalid_character_is_rejected() {
+        let mut design = design_two_strands();
+        let path = std::env::temp_dir().join("ensnano_invalid_scaffold_test.fasta");
+        std::fs::write(&path, ">scaffold\nATGC\nATXC\n").unwrap();
+        let result = design.set_scaffold_sequence_from_fasta(&path, 0);
+        assert!(matches!(
+            result,
+            Err(FastaError::InvalidCharacter {
+                character: 'X',
+                position: 6
+            })
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn torsions_csv_has_one_row_per_torsion() {
+        let design = design_two_strands();
+        let path = std::env::temp_dir().join("ensnano_torsions_test.csv");
+        design.export_torsions_csv(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content.lines().collect::<Vec<_>>(),
+            vec![TORSIONS_CSV_HEADER]
+        );
+
+        design
+            .data
+            .lock()
+            .unwrap()
+            .general_cross_over(Nucl::new(1, 9, true), Nucl::new(2, 9, false))
+            .unwrap();
+        let nb_torsions = design.get_torsions().len();
+        assert!(nb_torsions > 0);
+        design.export_torsions_csv(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], TORSIONS_CSV_HEADER);
+        assert_eq!(lines.len(), nb_torsions + 1);
+        assert!(lines[1..].iter().all(|l| l.split(',').count() == 9));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn staples_csv_has_one_row_per_staple() {
+        let mut design = design_two_strands();
+        let path = std::env::temp_dir().join("ensnano_staples_test.csv");
+        design.export_staples_csv(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], STAPLES_CSV_HEADER);
+        assert_eq!(lines.len(), 3);
+        // No sequence is known
+        assert!(lines[1..].iter().all(|l| l.ends_with(",10,")));
+
+        design.set_scaffold_id(Some(1));
+        design.export_staples_csv(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "0,1,0,1,9,10,");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn cut_is_undone_and_redone() {
+        let mut design = design_two_strands();
+        design.record_undo_history(10);
+        assert!(!design.undo());
+        let nucl = Nucl {
+            helix: 1,
+            position: 4,
+            forward: true,
+        };
+        let result = design.apply_operation(UndoableOp::Cut {
+            strand: design.get_raw_strand(0).unwrap(),
+            nucl,
+            undo: false,
+            s_id: 0,
+        });
+        assert!(matches!(result, OperationResult::BigChange(_, _)));
+        assert_eq!(design.get_all_strand_ids().len(), 3);
+
+        assert!(design.undo());
+        assert_eq!(design.get_all_strand_ids().len(), 2);
+        assert_eq!(design.get_strand_length(0), Some(10));
+        assert!(!design.undo());
+
+        assert!(design.redo());
+        assert_eq!(design.get_all_strand_ids().len(), 3);
+        assert!(!design.redo());
+
+        // Applying a new operation discards the undone states
+        assert!(design.undo());
+        design.apply_operation(UndoableOp::Cut {
+            strand: design.get_raw_strand(1).unwrap(),
+            nucl: Nucl {
+                helix: 2,
+                position: 4,
+                forward: false,
+            },
+            undo: false,
+            s_id: 1,
+        });
+        assert!(!design.redo());
+    }
+
+    #[test]
+    fn undo_history_is_opt_in_and_capped() {
+        let mut design = design_two_strands();
+        let cut = |design: &Design, s_id: usize, nucl: Nucl| UndoableOp::Cut {
+            strand: design.get_raw_strand(s_id).unwrap(),
+            nucl,
+            undo: false,
+            s_id,
+        };
+        let op = cut(&design, 0, Nucl::new(1, 4, true));
+        design.apply_operation(op);
+        assert!(!design.undo());
+
+        design.record_undo_history(1);
+        let op = cut(&design, 1, Nucl::new(2, 4, false));
+        design.apply_operation(op);
+        let op = cut(&design, 0, Nucl::new(1, 2, true));
+        design.apply_operation(op);
+        let nb_strands = design.get_all_strand_ids().len();
+        assert!(design.undo());
+        assert_eq!(design.get_all_strand_ids().len(), nb_strands - 1);
+        assert!(!design.undo());
+    }
+
+    #[test]
+    fn extracted_selection_keeps_selected_strand() {
+        let design = design_two_strands();
+        let extracted = design.extract_selection(&[Selection::Strand(0, 0)]);
+        assert_eq!(extracted.get_all_strand_ids(), vec![0]);
+        assert_eq!(extracted.get_strand_length(0), Some(10));
+        assert!(extracted.has_helix(0));
+        assert!(!extracted.has_helix(1));
+    }
+
+    #[test]
+    fn scaffold_route_follows_the_scaffold() {
+        let mut design = design_two_strands();
+        assert!(design.get_scaffold_route().is_none());
+        design.set_scaffold_id(Some(1));
+        let route = design.get_scaffold_route().unwrap();
+        assert_eq!(Some(route.len()), design.get_scaffold_len());
+        assert_eq!(route[0], Nucl::new(2, 9, false));
+        assert_eq!(route[9], Nucl::new(2, 0, false));
+    }
+
+    #[test]
+    fn strands_are_found_by_sequence() {
+        let mut design = design_two_strands();
+        design.change_strand_sequence(0, String::from("ACGTACGTAC"));
+        design.change_strand_sequence(1, String::from("TTTTGGGGCC"));
+        assert_eq!(design.find_strands_by_sequence("acgtacgtac"), vec![0]);
+        assert_eq!(design.find_strands_by_sequence("GGGG"), vec![1]);
+        assert_eq!(design.find_strands_by_sequence("tNNNg"), vec![1]);
+        assert_eq!(design.find_strands_by_sequence("ANG"), vec![0]);
+        assert!(design.find_strands_by_sequence("AAA").is_empty());
+    }
+
+    #[test]
+    fn strand_lengths_histogram() {
+        let mut design = Design::new(0);
+        design.apply_operation(UndoableOp::AddGrid(GridDescriptor {
+            position: Vec3::zero(),
+            orientation: ultraviolet::Rotor3::identity(),
+            grid_type: GridTypeDescr::Square,
+        }));
+        design.build_helix_grid(0, 0, 0, 0, 8).unwrap();
+        design.build_helix_grid(0, 1, 0, 0, 16).unwrap();
+        let scaffold = design
+            .get_all_strand_ids()
+            .into_iter()
+            .find(|s_id| design.get_strand_length(*s_id) == Some(16));
+        design.set_scaffold_id(scaffold);
+
+        let expected: BTreeMap<usize, usize> = vec![(8, 2), (16, 1)].into_iter().collect();
+        assert_eq!(design.strand_length_histogram(false), expected);
+        let expected: BTreeMap<usize, usize> = vec![(8, 2), (16, 2)].into_iter().collect();
+        assert_eq!(design.strand_length_histogram(true), expected);
+    }
+
+    #[test]
+    fn occupied_grid_position_is_reported() {
+        let mut design = Design::new(0);
+        design.apply_operation(UndoableOp::AddGrid(GridDescriptor {
+            position: Vec3::zero(),
+            orientation: ultraviolet::Rotor3::identity(),
+            grid_type: GridTypeDescr::Square,
+        }));
+        design.build_helix_grid(0, 0, 0, 0, 0).unwrap();
+        let result = design.apply_operation(UndoableOp::AddGridHelix(
+            GridHelixDescriptor {
+                grid_id: 0,
+                x: 0,
+                y: 0,
+            },
+            0,
+            0,
+        ));
+        assert!(matches!(
+            result,
+            OperationResult::GridPlacementFailure(GridPlacementError::Occupied(1))
+        ));
+    }
+
+    #[test]
+    fn grid_deletion_is_undone() {
+        let mut design = Design::new(0);
+        design.apply_operation(UndoableOp::AddGrid(GridDescriptor {
+            position: Vec3::zero(),
+            orientation: ultraviolet::Rotor3::identity(),
+            grid_type: GridTypeDescr::Square,
+        }));
+        design.build_helix_grid(0, 0, 0, 0, 8).unwrap();
+        let deleted = design.delete_grid(0).unwrap();
+        assert!(design.get_grid_pos_helix(1).is_none());
+        design.apply_operation(UndoableOp::RestoreGrid(deleted));
+        assert_eq!(design.get_grid_pos_helix(1).unwrap().grid, 0);
+        design.apply_operation(UndoableOp::RmGrid(0));
+        assert!(design.get_grid_pos_helix(1).is_none());
+    }
+
+    #[test]
+    fn singleton_strand_is_removed() {
+        let mut design = design_two_strands();
+        assert!(design.find_singleton_strands().is_empty());
+        assert!(design.remove_singleton_strands().is_none());
+
+        design.split_strand(Nucl {
+            helix: 1,
+            position: 8,
+            forward: true,
+        });
+        assert_eq!(design.find_singleton_strands(), vec![2]);
+        assert!(design.remove_singleton_strands().is_some());
+        assert_eq!(design.get_all_strand_ids(), vec![0, 1]);
+        assert_eq!(design.get_strand_length(0), Some(9));
+        assert!(design.find_singleton_strands().is_empty());
+    }
+
+    #[test]
+    fn only_scaffold_remains_after_deleting_staples() {
+        let mut design = design_two_strands();
+        assert!(design.delete_all_staples(false).is_none());
+        assert_eq!(design.get_all_strand_ids().len(), 2);
+
+        design.set_scaffold_id(Some(1));
+        assert!(design.delete_all_staples(false).is_some());
+        assert_eq!(design.get_all_strand_ids(), vec![1]);
+        assert!(design.is_scaffold(1));
+        assert!(design.delete_all_staples(false).is_none());
+    }
+
+    #[test]
+    fn batched_edits_are_notified_once() {
+        let mut design = design_two_strands();
+        design.drain_notifications();
+        design.with_batched_view(|design| {
+            for i in 0..10 {
+                design.change_strand_sequence(i % 2, "ATGCATGCAT".to_string());
+                design.change_strand_color(i % 2, 0xFF_00_00_FF + i as u32);
+                assert!(design.drain_notifications().is_empty());
+                assert!(design.data_was_updated().is_none());
+            }
+        });
+        let notifications = design.drain_notifications();
+        assert_eq!(notifications.len(), 1);
+        // Sequence and color changes only modify the instances of the edited strands
+        assert!(matches!(
+            &notifications[0].content,
+            DesignNotificationContent::StrandsChanged(strands) if strands.len() == 2
+        ));
+    }
+}
@@ -29,7 +29,7 @@ use crate::gui::SimulationRequest;
 use crate::utils::id_generator::IdGenerator;
 use ahash::RandomState;
 use cadnano_format::Cadnano;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use ultraviolet::Vec3;
@@ -45,6 +45,7 @@ mod grid;
 mod icednano;
 mod insertion_replacement;
 mod oxdna;
+mod pdb;
 mod rigid_body;
 mod roller;
 mod scadnano;
@@ -63,9 +64,12 @@ pub use grid::*;
 pub use icednano::Nucl;
 pub use icednano::{Axis, Design, Helix, Parameters, Strand};
 use icednano::{Domain, DomainJunction, HelixInterval};
-pub use rigid_body::{GridSystemState, RigidBodyConstants, RigidHelixState};
+pub use rigid_body::{GridSystemState, RigidBodyConstants, RigidHelixState, TrajectoryFrame};
 use roller::PhysicalSystem;
-use std::sync::{mpsc::Sender, Arc, Mutex, RwLock};
+use std::sync::{
+    mpsc::{Receiver, Sender},
+    Arc, Mutex, RwLock,
+};
 use strand_builder::NeighbourDescriptor;
 pub use strand_builder::{DomainIdentifier, StrandBuilder};
 use strand_template::{TemplateManager, XoverCopyManager};
@@ -83,6 +87,36 @@ impl std::fmt::Debug for StrandState {
     }
 }
 
+/// A `StrandState` extended with the scaffold designation and with some helices of the design.
+///
+/// Operations that create or delete helices together with strands return two such states, in
+/// which `helices` maps the identifiers of the created or deleted helices to the helices, or to
+/// `None` if the helix does not exist in that state.
+#[derive(Clone)]
+pub struct ExtendedStrandState {
+    strands: StrandState,
+    scaffold_id: Option<usize>,
+    helices: BTreeMap<usize, Option<Helix>>,
+}
+
+impl std::fmt::Debug for ExtendedStrandState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExtendedStrandState")
+            .field("scaffold_id", &self.scaffold_id)
+            .field("helices", &self.helices.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// The 2d isometries of the helices of a design. This is the content of the files produced by
+/// `Design::export_layout_2d`.
+///
+/// A `None` isometry means that the helix has no 2d isometry.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Layout2D {
+    pub isometries: BTreeMap<usize, Option<ultraviolet::Isometry2>>,
+}
+
 /// In addition to its `design` field, the `Data` struct has several hashmaps that are usefull to
 /// quickly access information about the design. These hasmaps must be updated when the design is
 /// modified.
@@ -113,6 +147,12 @@ pub struct Data {
     /// Must be set to true when the design is modified, so that its obeservers get notified of the
     /// modification
     update_status: bool,
+    /// True if the design was modified since the last time it was saved. Must be set by the
+    /// methods modifying the design, regardless of whether the modification was notified yet.
+    modified: bool,
+    /// The strands that were modified in a way that does not change the identifiers of their
+    /// elements, so that only the instances representing these strands need to be updated.
+    strands_to_update: HashSet<usize>,
     /// Must be set to true when a modification that requires an update of the hash maps is
     /// performed
     hash_maps_update: bool,
@@ -122,6 +162,9 @@ pub struct Data {
     grids: Vec<Arc<RwLock<Grid2D>>>,
     color_idx: usize,
     view_need_reset: bool,
+    /// The number of batches of modifications being applied. While it is positive, the
+    /// modifications of the design are not notified to the observers.
+    batch_depth: usize,
     groups: Arc<RwLock<BTreeMap<usize, bool>>>,
     red_cubes: HashMap<(isize, isize, isize), Vec<Nucl>, RandomState>,
     #[allow(dead_code)]
@@ -139,10 +182,14 @@ pub struct Data {
     template_manager: TemplateManager,
     xover_copy_manager: XoverCopyManager,
     anchors: HashSet<Nucl>,
+    /// The helices that do not move during helices simulations
+    pinned_helices: HashSet<usize>,
     rigid_helix_simulator: Option<rigid_body::RigidHelixSimulator>,
     elements_update: Option<Vec<DnaElement>>,
     visible: HashMap<Nucl, bool>,
     visibility_sieve: Option<VisibilitySieve>,
+    /// The identifiers of the strands that are hidden
+    invisible_strands: HashSet<usize>,
     xover_ids: IdGenerator<(Nucl, Nucl)>,
     prime3_set: Vec<(Vec3, Vec3, u32)>,
 }
@@ -174,12 +221,15 @@ impl Data {
             helix_map: HashMap::default(),
             color: HashMap::default(),
             update_status: false,
+            modified: false,
+            strands_to_update: HashSet::new(),
             hash_maps_update: false,
             basis_map: Arc::new(RwLock::new(HashMap::default())),
             grid_manager,
             grids: Vec::new(),
             color_idx: 0,
             view_need_reset: false,
+            batch_depth: 0,
             groups: Default::default(),
             red_cubes: HashMap::default(),
             blue_cubes: HashMap::default(),
@@ -192,10 +242,12 @@ impl Data {
             rigid_body_ptr: None,
             helix_simulation_ptr: None,
             anchors: HashSet::new(),
+            pinned_helices: HashSet::new(),
             rigid_helix_simulator: None,
             elements_update: None,
             visible: Default::default(),
             visibility_sieve: None,
+            invisible_strands: Default::default(),
             xover_ids: Default::default(),
             prime3_set: Default::default(),
         }
@@ -243,6 +295,7 @@ impl Data {
             });
             self.design.helices.remove(&h_id);
             self.update_status = true;
+            self.modified = true;
             self.hash_maps_update = true;
             self.view_need_reset = true;
         }
@@ -304,6 +357,7 @@ impl Data {
         }
 
         self.update_status = true;
+        self.modified = true;
         self.make_hash_maps();
     }
 
@@ -324,6 +378,7 @@ impl Data {
             });
             self.design.helices.remove(&h_id);
             self.update_status = true;
+            self.modified = true;
             self.hash_maps_update = true;
             self.view_need_reset = true;
         }
@@ -351,14 +406,37 @@ impl Data {
         self.hash_maps_update = true;
         self.view_need_reset = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     /// Create a new data by reading a file. At the moment, the supported format are
     /// * codenano
     /// * icednano
     pub fn new_with_path(json_path: &PathBuf) -> Option<Self> {
+        let design = read_file(json_path)?;
+        Some(Self::new_from_icednano(design, json_path))
+    }
+
+    /// Load a design like `new_with_path`, and return a warning alongside it if the design has
+    /// more than `large_threshold` nucleotides.
+    ///
+    /// The number of nucleotides is computed before the instances and hash maps are built.
+    pub fn new_with_path_and_threshold(
+        json_path: &PathBuf,
+        large_threshold: usize,
+    ) -> Option<(Self, Option<DesignLoadWarning>)> {
+        let design = read_file(json_path)?;
+        let nb_nucl: usize = design.strands.values().map(|s| s.length()).sum();
+        let warning = if nb_nucl > large_threshold {
+            Some(DesignLoadWarning::Large(nb_nucl))
+        } else {
+            None
+        };
+        Some((Self::new_from_icednano(design, json_path), warning))
+    }
+
+    fn new_from_icednano(mut design: icednano::Design, json_path: &PathBuf) -> Self {
         let mut xover_ids: IdGenerator<(Nucl, Nucl)> = Default::default();
-        let mut design = read_file(json_path)?;
         design.update_version();
         design.remove_empty_domains();
         for s in design.strands.values_mut() {
@@ -392,6 +470,8 @@ impl Data {
             helix_map: HashMap::default(),
             color: HashMap::default(),
             update_status: false,
+            modified: false,
+            strands_to_update: HashSet::new(),
             // false because we call make_hash_maps here
             hash_maps_update: false,
             basis_map: Default::default(),
@@ -399,6 +479,7 @@ impl Data {
             grids,
             color_idx,
             view_need_reset: false,
+            batch_depth: 0,
             groups: Arc::new(RwLock::new(groups)),
             red_cubes: HashMap::default(),
             blue_cubes: HashMap::default(),
@@ -412,15 +493,17 @@ impl Data {
             helix_simulation_ptr: None,
             rigid_helix_simulator: None,
             anchors,
+            pinned_helices: HashSet::new(),
             elements_update: None,
             visible: Default::default(),
             visibility_sieve: None,
+            invisible_strands: Default::default(),
             xover_ids,
             prime3_set: Default::default(),
         };
         ret.make_hash_maps();
         ret.terminate_movement();
-        Some(ret)
+        ret
     }
 
     /// Update all the hash maps
@@ -739,6 +822,7 @@ impl Data {
         }
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
         self.design.scaffold_shift = None;
     }
 
@@ -747,19 +831,33 @@ impl Data {
         self.design.scaffold_sequence = Some(sequence);
         self.design.scaffold_shift = Some(shift);
         self.hash_maps_update = true;
+        self.modified = true;
     }
 
     pub fn set_scaffold_shift(&mut self, shift: usize) {
         self.design.scaffold_shift = Some(shift);
         self.update_status = true;
+        self.modified = true;
         if self.design.scaffold_sequence.is_some() {
             self.hash_maps_update = true;
         }
     }
 
-    pub fn request_save(&mut self, path: &PathBuf) -> std::io::Result<()> {
+    pub fn request_save(&mut self, path: &PathBuf) -> Result<(), SaveError> {
         self.file_name = real_name(path);
-        self.save_file(path)
+        self.save_file(path)?;
+        self.mark_saved();
+        Ok(())
+    }
+
+    /// Return true if the design was modified since the last time it was saved
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.modified
+    }
+
+    /// Consider the current state of the design as saved
+    pub fn mark_saved(&mut self) {
+        self.modified = false;
     }
 
     fn backup_save(&mut self) {
@@ -777,20 +875,42 @@ impl Data {
     }
 
     /// Save the design to a file in the `icednano` format
-    pub fn save_file(&mut self, path: &PathBuf) -> std::io::Result<()> {
+    pub fn save_file(&mut self, path: &PathBuf) -> Result<(), SaveError> {
         self.design.anchors = self.anchors.clone();
         self.design.groups = self.groups.read().unwrap().clone();
         self.design.no_phantoms = self.grid_manager.no_phantoms.clone();
         self.design.small_spheres = self.grid_manager.small_spheres.clone();
-        let json_content = serde_json::to_string_pretty(&self.design);
+        let json_content = serde_json::to_string_pretty(&self.design)?;
         let mut f = std::fs::File::create(path)?;
-        f.write_all(json_content.expect("serde_json failed").as_bytes())
+        f.write_all(json_content.as_bytes())?;
+        Ok(())
+    }
+
+    /// Serialize the design in the `icednano` format, as it would be written by `save_file`.
+    pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
+        let mut design = self.design.clone();
+        design.anchors = self.anchors.clone();
+        design.groups = self.groups.read().unwrap().clone();
+        design.no_phantoms = self.grid_manager.no_phantoms.clone();
+        design.small_spheres = self.grid_manager.small_spheres.clone();
+        serde_json::to_string_pretty(&design)
+    }
+
+    /// Create a new data from a string in the `icednano` format.
+    pub fn from_json_string(json_str: &str) -> Result<Self, serde_json::Error> {
+        let design: icednano::Design = serde_json::from_str(json_str)?;
+        let mut file_name = std::env::current_exe().unwrap();
+        file_name.set_file_name("unamed_design.json");
+        Ok(Self::new_from_icednano(design, &file_name))
     }
 
     /// Return true if self was updated since the last time this function was called.
     /// This function is meant to be called by the mediator that will notify all the obeservers
     /// that a update took place.
     pub fn was_updated(&mut self) -> bool {
+        if self.batch_depth > 0 {
+            return false;
+        }
         if !self.read_rigid_helix_update() {
             self.check_rigid_body();
             self.check_rigid_helices();
@@ -807,6 +927,7 @@ impl Data {
                     *date = now;
                     self.hash_maps_update = true;
                     self.update_status = true;
+                    self.modified = true;
                     stop_rolling = true;
                 }
             }
@@ -819,9 +940,15 @@ impl Data {
             }
         } else {
             self.update_status = true;
+            self.modified = true;
         }
         let ret = self.update_status;
         self.update_status = false;
+        if ret {
+            // All the instances will be updated so there is no need to update the strands
+            // individually
+            self.strands_to_update.clear();
+        }
         if let Some(time) = self.last_backup_time {
             if (Instant::now() - time).as_secs() > 30 {
                 self.last_backup_time = None;
@@ -857,6 +984,44 @@ impl Data {
         self.xover_ids.get_all_elements()
     }
 
+    /// Return the identifiers of the strands that are mechanically linked to strand `s_id`,
+    /// including `s_id` itself.
+    ///
+    /// Two strands are linked if they are hybridized. The two ends of a cross-over always belong
+    /// to the same strand, so the cross-overs do not link strands by themselves, but since they
+    /// make a strand go from an helix to an other, its component contains the strands that it
+    /// pairs with on every helix that it visits.
+    pub fn connected_strands(&self, s_id: usize) -> HashSet<usize> {
+        let mut ret = HashSet::new();
+        if !self.design.strands.contains_key(&s_id) {
+            return ret;
+        }
+        let mut to_visit = VecDeque::new();
+        ret.insert(s_id);
+        to_visit.push_back(s_id);
+        while let Some(s_id) = to_visit.pop_front() {
+            let mut neighbours = Vec::new();
+            for domain in self.design.strands[&s_id].domains.iter() {
+                if let icednano::Domain::HelixDomain(dom) = domain {
+                    for position in dom.iter() {
+                        let compl = Nucl {
+                            position,
+                            forward: !dom.forward,
+                            helix: dom.helix,
+                        };
+                        neighbours.extend(self.get_strand_nucl(&compl));
+                    }
+                }
+            }
+            for neighbour in neighbours {
+                if ret.insert(neighbour) {
+                    to_visit.push_back(neighbour);
+                }
+            }
+        }
+        ret
+    }
+
     fn start_rolling(&mut self, request: SimulationRequest, computing: Arc<Mutex<bool>>) {
         let xovers = self.design.get_xovers();
         let helices: Vec<Helix> = self.design.helices.values().cloned().collect();
@@ -887,7 +1052,22 @@ impl Data {
     }
 
     pub fn view_need_reset(&mut self) -> bool {
-        std::mem::replace(&mut self.view_need_reset, false)
+        if self.batch_depth > 0 {
+            false
+        } else {
+            std::mem::replace(&mut self.view_need_reset, false)
+        }
+    }
+
+    /// Start a batch of modifications. The modifications are not notified until the end of the
+    /// batch.
+    pub fn begin_batch(&mut self) {
+        self.batch_depth += 1;
+    }
+
+    /// End a batch of modifications started with `begin_batch`.
+    pub fn end_batch(&mut self) {
+        self.batch_depth = self.batch_depth.saturating_sub(1);
     }
 
     /// Return the position of a nucleotide, this function is only used internally. The
@@ -977,6 +1157,62 @@ impl Data {
         })
     }
 
+    /// Return the position of a nucleotide that belongs to a strand of the design.
+    pub fn get_nucl_position(&self, nucl: &Nucl) -> Option<Vec3> {
+        self.get_space_pos(nucl).map(Vec3::from)
+    }
+
+    /// Return the smallest and the largest position of a nucleotide on helix `h_id`, and the
+    /// number of nucleotides on that helix. A position occupied on both strands of the helix
+    /// counts as two nucleotides.
+    ///
+    /// Return `None` if the helix does not exist or if there is no nucleotide on it.
+    pub fn helix_occupancy(&self, h_id: usize) -> Option<(isize, isize, usize)> {
+        if !self.design.helices.contains_key(&h_id) {
+            return None;
+        }
+        let mut ret: Option<(isize, isize, usize)> = None;
+        for nucl in self.identifier_nucl.keys().filter(|n| n.helix == h_id) {
+            ret = Some(match ret {
+                Some((min, max, nb)) => (min.min(nucl.position), max.max(nucl.position), nb + 1),
+                None => (nucl.position, nucl.position, 1),
+            });
+        }
+        ret
+    }
+
+    /// Return, for each helix of `h_ids`, the positions of its axis projected on the plane going
+    /// through the origin and perpendicular to `normal`.
+    ///
+    /// The axis is sampled at every position between the extremal nucleotides of the helix, or
+    /// only at position 0 if there is no nucleotide on the helix. If `normal` is zero, the plane
+    /// is perpendicular to the z axis. Helices that do not exist are ignored.
+    pub fn project_to_plane(&self, h_ids: &[usize], normal: Vec3) -> Vec<(usize, Vec<Vec3>)> {
+        let normal = if normal.mag_sq() > 0. {
+            normal.normalized()
+        } else {
+            Vec3::unit_z()
+        };
+        let parameters = self.design.parameters.unwrap_or_default();
+        let mut ret = Vec::with_capacity(h_ids.len());
+        for h_id in h_ids.iter() {
+            if let Some(helix) = self.design.helices.get(h_id) {
+                let (min, max) = self
+                    .helix_occupancy(*h_id)
+                    .map(|(min, max, _)| (min, max))
+                    .unwrap_or((0, 0));
+                let positions = (min..=max)
+                    .map(|n| {
+                        let p = helix.axis_position(&parameters, n);
+                        p - p.dot(normal) * normal
+                    })
+                    .collect();
+                ret.push((*h_id, positions));
+            }
+        }
+        ret
+    }
+
     /// Return the ObjectType associated to the identifier `id`
     pub fn get_object_type(&self, id: u32) -> Option<ObjectType> {
         self.object_type.get(&id).cloned()
@@ -998,7 +1234,7 @@ impl Data {
     pub fn get_all_visible_nucl_ids(&self) -> Vec<u32> {
         self.nucleotide
             .iter()
-            .filter(|(_, n)| self.is_visible(*n))
+            .filter(|(k, n)| self.is_visible(*n) && self.strand_of_element_is_visible(k))
             .map(|(k, _)| *k)
             .collect()
     }
@@ -1013,11 +1249,22 @@ impl Data {
     pub fn get_all_visible_bound_ids(&self) -> Vec<u32> {
         self.nucleotides_involved
             .iter()
-            .filter(|(_, b)| self.is_visible(&b.0) && self.is_visible(&b.1))
+            .filter(|(k, b)| {
+                self.is_visible(&b.0)
+                    && self.is_visible(&b.1)
+                    && self.strand_of_element_is_visible(k)
+            })
             .map(|(k, _)| *k)
             .collect()
     }
 
+    fn strand_of_element_is_visible(&self, id: &u32) -> bool {
+        self.strand_map
+            .get(id)
+            .map(|s_id| !self.invisible_strands.contains(s_id))
+            .unwrap_or(true)
+    }
+
     /// Return the identifier of the strand on which an element lies
     pub fn get_strand_of_element(&self, id: u32) -> Option<usize> {
         self.strand_map.get(&id).cloned()
@@ -1059,10 +1306,22 @@ impl Data {
         if let Some(strand) = self.design.strands.get_mut(&s_id) {
             self.color.insert(s_id as u32, color);
             strand.color = color;
+            self.strands_to_update.insert(s_id);
+            self.modified = true;
         } else {
             println!("Warning tried to change color of removed strand");
         }
-        self.update_status = true;
+    }
+
+    /// Return the set of strands whose instances must be updated, if the only modifications
+    /// performed since the last call to `was_updated` did not change the identifiers of the
+    /// elements of the design.
+    pub fn strands_update(&mut self) -> Option<HashSet<usize>> {
+        if self.strands_to_update.is_empty() || self.batch_depth > 0 {
+            None
+        } else {
+            Some(std::mem::take(&mut self.strands_to_update))
+        }
     }
 
     /// Change the color of a strand
@@ -1072,10 +1331,79 @@ impl Data {
             .get_mut(&s_id)
             .expect("wrong s_id in change_strand_color")
             .sequence = Some(std::borrow::Cow::Owned(sequence));
-        self.update_status = true;
+        // The identifiers of the elements of the strand are not modified, only their symbols and
+        // base colors must be updated.
+        self.strands_to_update.insert(s_id);
+        self.modified = true;
         self.hash_maps_update = true;
     }
 
+    /// Assign sequences to strands from the content of a csv file whose rows are made of a strand
+    /// identifier and a sequence.
+    ///
+    /// The strand identifier is either the identifier of the strand or the name given to it in
+    /// the stapples export. Whitespaces inside the sequences are ignored. The rows are all
+    /// validated before any sequence is assigned: if a row's strand does not exist or if its
+    /// sequence length differs from the strand's length, no sequence is assigned and the
+    /// faulty rows are reported in the error message.
+    ///
+    /// Return the number of sequences that were assigned, and the initial and new strand states.
+    pub fn assign_sequences_csv(
+        &mut self,
+        csv: &str,
+    ) -> Result<(usize, StrandState, StrandState), String> {
+        let mut assignments = Vec::new();
+        let mut errors = Vec::new();
+        for (line_idx, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.split(',').map(|f| f.trim().trim_matches('"'));
+            let identifier = fields.next().unwrap_or_default();
+            let sequence: String = fields
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            if line_idx == 0 && sequence.eq_ignore_ascii_case("sequence") {
+                // header
+                continue;
+            }
+            let strand = parse_strand_identifier(identifier)
+                .and_then(|s_id| self.design.strands.get(&s_id).map(|s| (s_id, s.length())));
+            match strand {
+                None => errors.push(format!(
+                    "line {}: no strand matches \"{}\"",
+                    line_idx + 1,
+                    identifier
+                )),
+                Some((_, length)) if length != sequence.len() => errors.push(format!(
+                    "line {}: strand \"{}\" has length {} but the sequence has length {}",
+                    line_idx + 1,
+                    identifier,
+                    length,
+                    sequence.len()
+                )),
+                Some((s_id, _)) => assignments.push((s_id, sequence)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(format!(
+                "No sequence assigned, {} invalid rows:\n{}",
+                errors.len(),
+                errors.join("\n")
+            ));
+        }
+        let init = self.get_strand_state();
+        let nb_assigned = assignments.len();
+        for (s_id, sequence) in assignments.into_iter() {
+            self.change_strand_sequence(s_id, sequence);
+        }
+        Ok((nb_assigned, init, self.get_strand_state()))
+    }
+
     pub fn get_strand_color(&self, s_id: usize) -> Option<u32> {
         self.design.strands.get(&s_id).map(|s| s.color)
     }
@@ -1089,11 +1417,69 @@ impl Data {
         })
     }
 
+    /// Return the sequence of a strand. If the strand has no sequence of its own, the sequence is
+    /// read from the bases assigned to its nucleotides, with '?' for the unknown ones.
+    fn get_assigned_sequence(&self, s_id: usize) -> Option<String> {
+        let own_sequence = self.get_strand_sequence(s_id)?;
+        if !own_sequence.is_empty() {
+            return Some(own_sequence);
+        }
+        let basis_map = self.basis_map.read().unwrap();
+        let mut sequence = String::new();
+        for domain in self.design.strands[&s_id].domains.iter() {
+            if let icednano::Domain::HelixDomain(dom) = domain {
+                for position in dom.iter() {
+                    let nucl = Nucl {
+                        position,
+                        forward: dom.forward,
+                        helix: dom.helix,
+                    };
+                    sequence.push(*basis_map.get(&nucl).unwrap_or(&'?'));
+                }
+            }
+        }
+        Some(sequence)
+    }
+
+    /// Return the identifiers of the strands whose sequence contains `query`. The comparison is
+    /// case-insensitive and the letter 'N' in `query` matches any base.
+    pub fn find_strands_by_sequence(&self, query: &str) -> Vec<usize> {
+        let query: Vec<char> = query
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        if query.is_empty() {
+            return vec![];
+        }
+        self.design
+            .strands
+            .keys()
+            .filter(|s_id| {
+                let sequence: Vec<char> = self
+                    .get_assigned_sequence(**s_id)
+                    .unwrap_or_default()
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect();
+                sequence.windows(query.len()).any(|window| {
+                    window
+                        .iter()
+                        .zip(query.iter())
+                        .all(|(base, q)| *q == 'N' && *base != '?' || base == q)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
     pub fn translate_grid(&mut self, g_id: usize, translation: Vec3) {
         self.grid_manager.translate_grid(g_id, translation);
         self.grid_manager.update(&mut self.design);
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     /// Apply `rotation` on helix `h_id` arround `origin`. `rotation` and `origin` must be
@@ -1114,6 +1500,7 @@ impl Data {
         self.grid_manager.update(&mut self.design);
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     /// Attempt to translate an helix, and return true iff the translation was effectively
@@ -1141,6 +1528,7 @@ impl Data {
         self.update_grids();
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
         ret
     }
 
@@ -1155,6 +1543,7 @@ impl Data {
         self.grid_manager.update(&mut self.design);
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     /// End current movement. This means that the old_matrices take the value of the current ones.
@@ -1165,6 +1554,124 @@ impl Data {
         self.grid_manager.terminate_movement();
     }
 
+    /// Return the axis of an helix in the model coordinates
+    pub fn get_helix_axis(&self, h_id: usize) -> Option<Axis> {
+        let parameters = self.design.parameters.unwrap_or_default();
+        self.design
+            .helices
+            .get(&h_id)
+            .map(|h| h.get_axis(&parameters))
+    }
+
+    /// Estimate the dimensions, in nanometers, of the folded design.
+    ///
+    /// The dimensions are the ones of a bounding box of the axis of all the helices that contain
+    /// at least one nucleotide, enlarged by one helix diameter. The first vector of the box is
+    /// the mean direction of the helices, the second one is the direction in which the helices
+    /// are the most spread in the orthogonal plane.
+    pub fn estimate_folded_dimensions(&self) -> Option<(f32, f32, f32)> {
+        use crate::scene::maths_3d::{Basis3D, UnalignedBoundaries};
+        let parameters = self.design.parameters.unwrap_or_default();
+        let intervals = self.design.get_intervals();
+        let mut segments = Vec::with_capacity(intervals.len());
+        for (h_id, (left, right)) in intervals.iter() {
+            if let Some(h) = self.design.helices.get(h_id) {
+                segments.push((
+                    h.axis_position(&parameters, *left),
+                    h.axis_position(&parameters, *right),
+                ));
+            }
+        }
+        let reference = Vec3::unit_x().rotated_by(
+            self.design
+                .helices
+                .get(intervals.keys().next()?)?
+                .orientation,
+        );
+        let mut direction = Vec3::zero();
+        for h_id in intervals.keys() {
+            if let Some(h) = self.design.helices.get(h_id) {
+                let axis = Vec3::unit_x().rotated_by(h.orientation);
+                if axis.dot(reference) < 0. {
+                    direction -= axis;
+                } else {
+                    direction += axis;
+                }
+            }
+        }
+        if direction.mag() < 1e-5 {
+            return None;
+        }
+        let unit_x = direction.normalized();
+
+        // Find the direction in which the helices are the most spread in the plane orthogonal
+        // to unit_x
+        let u = if unit_x.x.abs() < 0.9 {
+            Vec3::unit_x().cross(unit_x).normalized()
+        } else {
+            Vec3::unit_y().cross(unit_x).normalized()
+        };
+        let v = unit_x.cross(u);
+        let points: Vec<Vec3> = segments.iter().flat_map(|(a, b)| vec![*a, *b]).collect();
+        let center = points.iter().fold(Vec3::zero(), |acc, p| acc + *p) / points.len() as f32;
+        let (mut cov_uu, mut cov_uv, mut cov_vv) = (0f32, 0f32, 0f32);
+        for p in points.iter() {
+            let pu = (*p - center).dot(u);
+            let pv = (*p - center).dot(v);
+            cov_uu += pu * pu;
+            cov_uv += pu * pv;
+            cov_vv += pv * pv;
+        }
+        let theta = 0.5 * (2. * cov_uv).atan2(cov_uu - cov_vv);
+        let unit_y = theta.cos() * u + theta.sin() * v;
+        let unit_z = unit_x.cross(unit_y);
+
+        let mut boundaries =
+            UnalignedBoundaries::from_basis(Basis3D::from_vecs(unit_x, unit_y, unit_z));
+        for p in points.into_iter() {
+            boundaries.add_point(p);
+        }
+        let lengths = boundaries.lengths()?;
+        let diameter = 2. * parameters.helix_radius;
+        Some((
+            lengths.x + diameter,
+            lengths.y + diameter,
+            lengths.z + diameter,
+        ))
+    }
+
+    /// Return the `count` helices whose centers are the closest to the center of helix `h_id`,
+    /// sorted by increasing distance.
+    ///
+    /// The center of an helix is the middle of the portion of its axis that is covered by
+    /// strands, or the origin of the helix if it is empty.
+    pub fn helices_near(&self, h_id: usize, count: usize) -> Vec<(usize, f32)> {
+        let parameters = self.design.parameters.unwrap_or_default();
+        let intervals = self.design.get_intervals();
+        let center = |h_id: &usize, h: &Helix| {
+            if let Some((left, right)) = intervals.get(h_id) {
+                (h.axis_position(&parameters, *left) + h.axis_position(&parameters, *right)) / 2.
+            } else {
+                h.position
+            }
+        };
+        let reference = if let Some(h) = self.design.helices.get(&h_id) {
+            center(&h_id, h)
+        } else {
+            return vec![];
+        };
+        let mut ret: Vec<(usize, f32)> = self
+            .design
+            .helices
+            .iter()
+            .filter(|(id, _)| **id != h_id)
+            .map(|(id, h)| (*id, (center(id, h) - reference).mag()))
+            .collect();
+        ret.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        ret.truncate(count);
+        ret
+    }
+
     /// Return the orientation of an helix. (`None` if the helix id does not exists)
     pub fn get_helix_basis(&self, h_id: usize) -> Option<ultraviolet::Rotor3> {
         self.design.helices.get(&h_id).map(|h| {
@@ -1201,6 +1708,16 @@ impl Data {
         self.identifier_nucl.get(nucl).cloned()
     }
 
+    /// Return the nucleotide that is paired with `nucl`, or `None` if `nucl` is not paired.
+    pub fn get_paired_nucl(&self, nucl: &Nucl) -> Option<Nucl> {
+        let compl = nucl.compl();
+        if self.identifier_nucl.contains_key(nucl) && self.identifier_nucl.contains_key(&compl) {
+            Some(compl)
+        } else {
+            None
+        }
+    }
+
     pub fn get_identifier_bound(&self, n1: &Nucl, n2: &Nucl) -> Option<u32> {
         self.identifier_bound.get(&(*n1, *n2)).cloned()
     }
@@ -1240,6 +1757,7 @@ impl Data {
         }
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     /// Return a `StrandBuilder` with moving end `nucl` if possible. To create a
@@ -1344,6 +1862,7 @@ impl Data {
         );
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
         new_key
     }
 
@@ -1354,6 +1873,7 @@ impl Data {
         );
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     pub fn get_symbol(&self, e_id: u32) -> Option<char> {
@@ -1443,6 +1963,20 @@ impl Data {
         None
     }
 
+    /// Return the nucleotide bound to the 3' side of `nucl`, or `None` if `nucl` is the 3' end of
+    /// its strand.
+    pub fn neighbor_3prime(&self, nucl: &Nucl) -> Option<Nucl> {
+        let s_id = self.get_strand_nucl(nucl)?;
+        self.design.strands.get(&s_id)?.next_nucl(nucl)
+    }
+
+    /// Return the nucleotide bound to the 5' side of `nucl`, or `None` if `nucl` is the 5' end of
+    /// its strand.
+    pub fn neighbor_5prime(&self, nucl: &Nucl) -> Option<Nucl> {
+        let s_id = self.get_strand_nucl(nucl)?;
+        self.design.strands.get(&s_id)?.previous_nucl(nucl)
+    }
+
     /// Return the xover extremity status of nucl.
     pub fn is_xover_end(&self, nucl: &Nucl) -> Extremity {
         let strand_id = if let Some(id) = self.get_strand_nucl(nucl) {
@@ -1547,6 +2081,7 @@ impl Data {
             self.design.strands.insert(prime5, new_strand);
             self.hash_maps_update = true;
             self.update_status = true;
+            self.modified = true;
         } else {
             println!("cycling");
             /*
@@ -1558,6 +2093,7 @@ impl Data {
             .cyclic = true;
             self.hash_maps_update = true;
             self.update_status = true;
+            self.modified = true;
             */
         }
         self.view_need_reset = true;
@@ -1579,6 +2115,7 @@ impl Data {
         self.design.strands.insert(prime5, strand_5prime);
         self.design.strands.insert(prime3, strand_3prime);
         self.update_status = true;
+        self.modified = true;
         self.view_need_reset = true;
         self.hash_maps_update = true;
     }
@@ -1643,6 +2180,7 @@ impl Data {
         }
 
         self.update_status = true;
+        self.modified = true;
         self.view_need_reset = true;
         //self.make_hash_maps();
         self.hash_maps_update = true;
@@ -1654,6 +2192,7 @@ impl Data {
     /// undone.
     pub fn undo_split(&mut self, strand: Strand, s_id: usize) {
         self.update_status = true;
+        self.modified = true;
         self.view_need_reset = true;
         self.design
             .strands
@@ -1685,6 +2224,7 @@ impl Data {
     /// split.
     pub fn split_strand(&mut self, nucl: &Nucl, force_end: Option<bool>) -> Option<usize> {
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         self.view_need_reset = true;
         let id = self.get_strand_nucl(nucl);
@@ -1822,6 +2362,7 @@ impl Data {
             self.design.strands.insert(id_3prime, strand_3prime);
         }
         self.update_status = true;
+        self.modified = true;
         //self.make_hash_maps();
         self.hash_maps_update = true;
         self.view_need_reset = true;
@@ -1970,6 +2511,7 @@ impl Data {
 
     pub fn undoable_rm_strand(&mut self, strand: Strand, strand_id: usize, undo: bool) {
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         if undo {
             self.design.strands.insert(strand_id, strand);
@@ -2001,6 +2543,7 @@ impl Data {
 
     pub(super) fn rm_strand_containing_nucl(&mut self, nucl: &Nucl) {
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         let id = self
             .identifier_nucl
@@ -2033,6 +2576,7 @@ impl Data {
         self.grid_manager.update(&mut self.design);
         self.update_grids();
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
     }
 
@@ -2042,6 +2586,7 @@ impl Data {
         self.grid_manager.update(&mut self.design);
         self.update_grids();
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
     }
 
@@ -2092,6 +2637,23 @@ impl Data {
             .and_then(|g| g.read().unwrap().helices().get(&(x, y)).map(|x| *x as u32))
     }
 
+    /// Return, for each cell of grid `g_id` in the rectangle `x_range` × `y_range` (bounds
+    /// included), whether an helix lies on that cell.
+    pub fn get_grid_occupancy(
+        &self,
+        g_id: usize,
+        x_range: (isize, isize),
+        y_range: (isize, isize),
+    ) -> Vec<((isize, isize), bool)> {
+        let mut ret = Vec::new();
+        for x in x_range.0..=x_range.1 {
+            for y in y_range.0..=y_range.1 {
+                ret.push(((x, y), self.get_helix_grid(g_id, x, y).is_some()));
+            }
+        }
+        ret
+    }
+
     pub fn get_grid_basis(&self, g_id: usize) -> Option<ultraviolet::Rotor3> {
         self.grid_manager
             .grids
@@ -2103,6 +2665,61 @@ impl Data {
         self.grid_manager.grids.get(g_id).map(|g| g.position)
     }
 
+    /// Move grid `g_b` so that its plane is parallel to the plane of grid `g_a`, facing it, at
+    /// distance `gap`.
+    ///
+    /// Return the position and orientation of grid `g_b` before the movement, or `None` if the
+    /// grids were already aligned.
+    pub fn align_grids(
+        &mut self,
+        g_a: usize,
+        g_b: usize,
+        gap: f32,
+    ) -> Option<(Vec3, ultraviolet::Rotor3)> {
+        let position_a = self.get_grid_position(g_a)?;
+        let orientation_a = self.get_grid_basis(g_a)?;
+        let position_b = self.get_grid_position(g_b)?;
+        let orientation_b = self.get_grid_basis(g_b)?;
+        if g_a == g_b {
+            return None;
+        }
+        let normal_a = Vec3::unit_x().rotated_by(orientation_a);
+        let target_position = position_a + gap * normal_a;
+        // Flip the grid arround its own z axis so that its normal is opposed to the one of g_a
+        let target_orientation =
+            orientation_a * ultraviolet::Rotor3::from_rotation_xy(std::f32::consts::PI);
+        let same_orientation = [Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z()]
+            .iter()
+            .all(|v| (v.rotated_by(target_orientation) - v.rotated_by(orientation_b)).mag() < 1e-3);
+        if same_orientation && (target_position - position_b).mag() < 1e-3 {
+            return None;
+        }
+        let rotation = (target_orientation * orientation_b.reversed()).normalized();
+        self.rotate_grid_arround(g_b, rotation, position_b);
+        self.terminate_movement();
+        self.translate_grid(g_b, target_position - position_b);
+        self.terminate_movement();
+        Some((position_b, orientation_b))
+    }
+
+    /// Set the position and orientation of a grid. This is used to undo the movement of grids.
+    pub fn set_grid_isometry(
+        &mut self,
+        g_id: usize,
+        position: Vec3,
+        orientation: ultraviolet::Rotor3,
+    ) {
+        if let Some(grid) = self.grid_manager.grids.get_mut(g_id) {
+            grid.position = position;
+            grid.orientation = orientation;
+            grid.end_movement();
+            self.grid_manager.update(&mut self.design);
+            self.hash_maps_update = true;
+            self.update_status = true;
+            self.modified = true;
+        }
+    }
+
     pub fn get_grid_latice_position(&self, g_id: usize, x: isize, y: isize) -> Option<Vec3> {
         self.grid_manager
             .grids
@@ -2117,80 +2734,322 @@ impl Data {
         y: isize,
         position: isize,
         length: usize,
-    ) {
-        if let Some(grid) = self.grid_manager.grids.get(g_id) {
-            if !self.grids[g_id]
-                .read()
-                .unwrap()
-                .helices()
-                .contains_key(&(x, y))
-            {
-                let helix = icednano::Helix::new_on_grid(grid, x, y, g_id);
-                let helix_id = self.design.helices.keys().last().unwrap_or(&0) + 1;
-                self.design.helices.insert(helix_id, helix);
-                if length > 0 {
-                    for b in [false, true].iter() {
-                        let new_key = self.add_strand(helix_id, position, *b);
-                        if let icednano::Domain::HelixDomain(ref mut dom) =
-                            self.design.strands.get_mut(&new_key).unwrap().domains[0]
-                        {
-                            dom.end = dom.start + length as isize;
-                        }
-                    }
+    ) -> Result<(), GridPlacementError> {
+        let grid = self
+            .grid_manager
+            .grids
+            .get(g_id)
+            .ok_or(GridPlacementError::GridDoesNotExist(g_id))?;
+        if !grid.grid_type.is_valid_position(x, y) {
+            return Err(GridPlacementError::InvalidPosition { x, y });
+        }
+        if let Some(h_id) = self.get_helix_grid(g_id, x, y) {
+            return Err(GridPlacementError::Occupied(h_id as usize));
+        }
+        let helix = icednano::Helix::new_on_grid(grid, x, y, g_id);
+        let helix_id = self.design.helices.keys().last().unwrap_or(&0) + 1;
+        self.design.helices.insert(helix_id, helix);
+        if length > 0 {
+            for b in [false, true].iter() {
+                let new_key = self.add_strand(helix_id, position, *b);
+                if let icednano::Domain::HelixDomain(ref mut dom) =
+                    self.design.strands.get_mut(&new_key).unwrap().domains[0]
+                {
+                    dom.end = dom.start + length as isize;
                 }
-                self.update_status = true;
-                self.hash_maps_update = true;
-                self.grid_manager.update(&mut self.design);
-                self.update_grids();
             }
         }
-    }
-
-    /// Add an helix to the design.
-    pub fn add_helix(&mut self, helix: &Helix, h_id: usize) {
-        if self.design.helices.contains_key(&h_id) {
-            println!("WARNING: helix key already exists {}", h_id);
-        }
-        self.design.helices.insert(h_id, helix.clone());
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         self.grid_manager.update(&mut self.design);
         self.update_grids();
+        Ok(())
     }
 
-    pub fn get_helix(&self, h_id: usize) -> Option<Helix> {
-        self.design.helices.get(&h_id).cloned()
+    /// Create an helix on every empty position of grid `g_id` and return the number of helices
+    /// that were created.
+    ///
+    /// Hyperboloid grids are filled entirely. Square and honeycomb grids are unbounded, so only
+    /// the positions inside the bounding rectangle of the helices that already lie on them are
+    /// filled, and no helix is created on such a grid if it is empty.
+    pub fn fill_grid(&mut self, g_id: usize, position: isize, length: usize) -> usize {
+        let mut nb_created = 0;
+        for (x, y) in self.fill_grid_candidates(g_id) {
+            if self.build_helix_grid(g_id, x, y, position, length).is_ok() {
+                nb_created += 1;
+            }
+        }
+        nb_created
     }
 
-    pub fn get_strand(&self, s_id: usize) -> Option<Strand> {
-        self.design.strands.get(&s_id).cloned()
+    /// Fill grid `g_id` as `fill_grid` does. Return the states of the design before and after the
+    /// operation, or `None` if no helix was created.
+    pub fn undoable_fill_grid(
+        &mut self,
+        g_id: usize,
+        position: isize,
+        length: usize,
+    ) -> Option<(ExtendedStrandState, ExtendedStrandState)> {
+        let helices_before: HashSet<usize> = self.design.helices.keys().cloned().collect();
+        let mut initial_state = self.get_extended_strand_state(&[]);
+        if self.fill_grid(g_id, position, length) == 0 {
+            return None;
+        }
+        let new_helices: Vec<usize> = self
+            .design
+            .helices
+            .keys()
+            .filter(|h_id| !helices_before.contains(h_id))
+            .cloned()
+            .collect();
+        initial_state.helices = new_helices.iter().map(|h_id| (*h_id, None)).collect();
+        Some((initial_state, self.get_extended_strand_state(&new_helices)))
+    }
+
+    /// The positions of grid `g_id` that are candidates for `fill_grid`.
+    fn fill_grid_candidates(&self, g_id: usize) -> Vec<(isize, isize)> {
+        match self.grid_manager.grids.get(g_id).map(|g| &g.grid_type) {
+            Some(GridType::Hyperboloid(h)) => (0..h.radius as isize).map(|x| (x, 0)).collect(),
+            Some(_) => {
+                let coords = self.get_helices_grid_coord(g_id).unwrap_or_default();
+                let x_min = coords.iter().map(|c| c.0).min();
+                let x_max = coords.iter().map(|c| c.0).max();
+                let y_min = coords.iter().map(|c| c.1).min();
+                let y_max = coords.iter().map(|c| c.1).max();
+                let mut ret = Vec::new();
+                if let (Some(x_min), Some(x_max), Some(y_min), Some(y_max)) =
+                    (x_min, x_max, y_min, y_max)
+                {
+                    for x in x_min..=x_max {
+                        for y in y_min..=y_max {
+                            ret.push((x, y));
+                        }
+                    }
+                }
+                ret
+            }
+            None => Vec::new(),
+        }
     }
 
-    /// Remove an helix containing only two filling strands.
-    pub fn rm_full_helix_grid(&mut self, g_id: usize, x: isize, y: isize, position: isize) {
-        let h = self.grids[g_id]
-            .read()
-            .unwrap()
-            .helices()
-            .get(&(x, y))
-            .cloned();
-        if let Some(h_id) = h {
-            self.rm_strand_containing_nucl(&Nucl {
-                helix: h_id,
-                position,
-                forward: true,
-            });
-            self.rm_strand_containing_nucl(&Nucl {
-                helix: h_id,
-                position,
-                forward: false,
-            });
-            self.design.helices.remove(&h_id);
-            self.grid_manager.remove_helix(h_id);
-            self.update_status = true;
-            self.hash_maps_update = true;
-            self.grid_manager.update(&mut self.design);
-            self.update_grids();
+    /// Build a new design made of the selected strands and helices.
+    ///
+    /// The selected strands are copied whole, together with the helices on which they lie. Only
+    /// the selected strands are copied, the other strands lying on the selected helices are not.
+    /// Helices, grids and strands are renumbered contiguously in the new design.
+    pub fn extract_selection(&self, selection: &[Selection]) -> Self {
+        let mut helices = BTreeSet::new();
+        let mut whole_strands = BTreeSet::new();
+        for s in selection.iter() {
+            match s {
+                Selection::Helix(_, h_id) => {
+                    helices.insert(*h_id as usize);
+                }
+                Selection::Strand(_, s_id) => {
+                    if let Some(strand) = self.design.strands.get(&(*s_id as usize)) {
+                        helices.extend(strand.domains.iter().filter_map(|d| d.helix()));
+                        whole_strands.insert(*s_id as usize);
+                    }
+                }
+                _ => (),
+            }
+        }
+        helices.retain(|h_id| self.design.helices.contains_key(h_id));
+
+        let mut grid_map = HashMap::new();
+        let mut grids = Vec::new();
+        for h_id in helices.iter() {
+            let grid = self.design.helices[h_id].grid_position.map(|gp| gp.grid);
+            if let Some(g_id) = grid {
+                if !grid_map.contains_key(&g_id) {
+                    if let Some(descriptor) = self.design.grids.get(g_id) {
+                        grid_map.insert(g_id, grids.len());
+                        grids.push(*descriptor);
+                    }
+                }
+            }
+        }
+
+        let helix_map: HashMap<usize, usize> = helices
+            .iter()
+            .enumerate()
+            .map(|(new_id, h_id)| (*h_id, new_id))
+            .collect();
+        let mut design = icednano::Design::new();
+        design.parameters = self.design.parameters;
+        design.grids = grids;
+        for (h_id, new_id) in helix_map.iter() {
+            let mut helix = self.design.helices[h_id].clone();
+            helix.grid_position = helix.grid_position.and_then(|gp| {
+                grid_map
+                    .get(&gp.grid)
+                    .map(|g| GridPosition { grid: *g, ..gp })
+            });
+            design.helices.insert(*new_id, helix);
+            if let Some(group) = self.design.groups.get(h_id) {
+                design.groups.insert(*new_id, *group);
+            }
+        }
+
+        let strands = self
+            .design
+            .strands
+            .iter()
+            .filter(|(s_id, _)| whole_strands.contains(s_id))
+            .map(|(s_id, strand)| (*s_id, strand.clone()));
+        for (new_id, (old_id, mut strand)) in strands.enumerate() {
+            strand.junctions.clear();
+            for d in strand.domains.iter_mut() {
+                if let Domain::HelixDomain(interval) = d {
+                    interval.helix = helix_map[&interval.helix];
+                }
+            }
+            if Some(old_id) == self.design.scaffold_id {
+                design.scaffold_id = Some(new_id);
+                design.scaffold_sequence = self.design.scaffold_sequence.clone();
+                design.scaffold_shift = self.design.scaffold_shift;
+            }
+            design.strands.insert(new_id, strand);
+        }
+        design.anchors = self
+            .design
+            .anchors
+            .iter()
+            .filter_map(|nucl| {
+                helix_map
+                    .get(&nucl.helix)
+                    .map(|h| Nucl { helix: *h, ..*nucl })
+            })
+            .collect();
+
+        let mut path = self.file_name.clone();
+        let stem = self
+            .file_name
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unamed_design")
+            .to_owned();
+        path.set_file_name(format!("{}_extract.json", stem));
+        Self::new_from_icednano(design, &path)
+    }
+
+    /// Add the images of the selected helices and strands by the reflection across the plane
+    /// going through `plane_point` with normal `plane_normal`.
+    ///
+    /// The helices on which the selected strands lie are mirrored too, and the copies of the
+    /// strands are placed on the mirrored helices.
+    pub fn mirror_selection(
+        &mut self,
+        selection: Vec<Selection>,
+        plane_point: Vec3,
+        plane_normal: Vec3,
+    ) -> Option<(ExtendedStrandState, ExtendedStrandState)> {
+        if plane_normal.mag() < 1e-5 {
+            return None;
+        }
+        let mut helices = BTreeSet::new();
+        let mut strands = Vec::new();
+        for s in selection.iter() {
+            match s {
+                Selection::Helix(_, h_id) => {
+                    helices.insert(*h_id as usize);
+                }
+                Selection::Strand(_, s_id) => {
+                    if let Some(strand) = self.design.strands.get(&(*s_id as usize)) {
+                        helices.extend(strand.domains.iter().filter_map(|d| d.helix()));
+                        strands.push(strand.clone());
+                    }
+                }
+                _ => (),
+            }
+        }
+        helices.retain(|h_id| self.design.helices.contains_key(h_id));
+        if helices.is_empty() {
+            return None;
+        }
+
+        let first_id = self.design.helices.keys().max().map(|k| k + 1).unwrap_or(0);
+        let new_helices: BTreeMap<usize, usize> = helices
+            .iter()
+            .enumerate()
+            .map(|(i, h_id)| (*h_id, first_id + i))
+            .collect();
+        let new_ids: Vec<usize> = new_helices.values().cloned().collect();
+        let init = self.get_extended_strand_state(&new_ids);
+        for (h_id, new_id) in new_helices.iter() {
+            let mirrored = self.design.helices[h_id].mirrored(plane_point, plane_normal);
+            self.design.helices.insert(*new_id, mirrored);
+        }
+        for strand in strands {
+            let mut copy = strand.clone();
+            for domain in copy.domains.iter_mut() {
+                if let Domain::HelixDomain(dom) = domain {
+                    dom.helix = new_helices[&dom.helix];
+                }
+            }
+            copy.junctions.clear();
+            copy.read_junctions(&mut self.xover_ids, true);
+            copy.read_junctions(&mut self.xover_ids, false);
+            let new_key = self.design.strands.keys().max().map(|k| k + 1).unwrap_or(0);
+            self.design.strands.insert(new_key, copy);
+        }
+        self.update_status = true;
+        self.modified = true;
+        self.hash_maps_update = true;
+        self.view_need_reset = true;
+        self.grid_manager.update(&mut self.design);
+        self.update_grids();
+        Some((init, self.get_extended_strand_state(&new_ids)))
+    }
+
+    /// Add an helix to the design.
+    pub fn add_helix(&mut self, helix: &Helix, h_id: usize) {
+        if self.design.helices.contains_key(&h_id) {
+            println!("WARNING: helix key already exists {}", h_id);
+        }
+        self.design.helices.insert(h_id, helix.clone());
+        self.update_status = true;
+        self.modified = true;
+        self.hash_maps_update = true;
+        self.grid_manager.update(&mut self.design);
+        self.update_grids();
+    }
+
+    pub fn get_helix(&self, h_id: usize) -> Option<Helix> {
+        self.design.helices.get(&h_id).cloned()
+    }
+
+    pub fn get_strand(&self, s_id: usize) -> Option<Strand> {
+        self.design.strands.get(&s_id).cloned()
+    }
+
+    /// Remove an helix containing only two filling strands.
+    pub fn rm_full_helix_grid(&mut self, g_id: usize, x: isize, y: isize, position: isize) {
+        let h = self.grids[g_id]
+            .read()
+            .unwrap()
+            .helices()
+            .get(&(x, y))
+            .cloned();
+        if let Some(h_id) = h {
+            self.rm_strand_containing_nucl(&Nucl {
+                helix: h_id,
+                position,
+                forward: true,
+            });
+            self.rm_strand_containing_nucl(&Nucl {
+                helix: h_id,
+                position,
+                forward: false,
+            });
+            self.design.helices.remove(&h_id);
+            self.grid_manager.remove_helix(h_id);
+            self.update_status = true;
+            self.modified = true;
+            self.hash_maps_update = true;
+            self.grid_manager.update(&mut self.design);
+            self.update_grids();
             self.view_need_reset = true;
         }
     }
@@ -2206,6 +3065,7 @@ impl Data {
             self.design.helices.remove(&h_id);
             self.grid_manager.remove_helix(h_id);
             self.update_status = true;
+            self.modified = true;
             self.hash_maps_update = true;
             self.grid_manager.update(&mut self.design);
             self.update_grids();
@@ -2215,6 +3075,7 @@ impl Data {
 
     pub fn remove_helix(&mut self, h_id: usize) {
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         if !self.helix_is_empty(h_id) {
             println!("WARNING REMOVING HELIX THAT IS NOT EMPTY");
@@ -2229,6 +3090,49 @@ impl Data {
         self.view_need_reset = true;
     }
 
+    /// Remove helix `h_id`.
+    ///
+    /// If strands lie on the helix, nothing is done unless `remove_strands` is true, in which
+    /// case the domains of these strands that are on the helix are deleted, splitting the strands
+    /// if needed.
+    pub fn remove_helix_with_strands(
+        &mut self,
+        h_id: usize,
+        remove_strands: bool,
+    ) -> Result<(ExtendedStrandState, ExtendedStrandState), HelixRemovalError> {
+        if !self.design.helices.contains_key(&h_id) {
+            return Err(HelixRemovalError::NoSuchHelix(h_id));
+        }
+        let on_helix: Vec<usize> = self
+            .design
+            .strands
+            .iter()
+            .filter(|(_, s)| s.domains.iter().any(|d| d.helix() == Some(h_id)))
+            .map(|(s_id, _)| *s_id)
+            .collect();
+        if !on_helix.is_empty() && !remove_strands {
+            return Err(HelixRemovalError::NotEmpty {
+                h_id,
+                strands: on_helix,
+            });
+        }
+        let init = self.get_extended_strand_state(&[h_id]);
+        for s_id in on_helix {
+            let strand = self.design.strands.get(&s_id).cloned().unwrap();
+            self.rm_strand(s_id);
+            let mut new_id = s_id;
+            for mut piece in strand_pieces_off_helix(&strand, h_id) {
+                piece.read_junctions(&mut self.xover_ids, true);
+                piece.read_junctions(&mut self.xover_ids, false);
+                self.design.strands.insert(new_id, piece);
+                new_id = self.design.strands.keys().max().map(|k| k + 1).unwrap_or(0);
+            }
+        }
+        self.remove_helix(h_id);
+        self.view_need_reset = true;
+        Ok((init, self.get_extended_strand_state(&[h_id])))
+    }
+
     /// Return false if there exists at least one strand with a domain on helix `h_id`, and false
     /// otherwise.
     pub fn helix_is_empty(&self, h_id: usize) -> bool {
@@ -2244,13 +3148,71 @@ impl Data {
         self.design.helices.contains_key(&h_id)
     }
 
-    /// Delete the last grid that was added to the grid manager. This can only be done
-    /// if the last grid is empty.
+    /// Delete grid `g_id`. The helices that were attached to it keep their position and
+    /// orientation but become free helices, and the identifiers of the following grids are
+    /// decreased by one.
     ///
-    /// At the moment this method is only called when the user undo the creation of a grid.
-    pub fn delete_last_grid(&mut self) {
-        self.grid_manager.delete_last_grid();
+    /// Return what is needed to undo the deletion, or `None` if there is no grid `g_id`.
+    pub fn delete_grid(&mut self, g_id: usize) -> Option<DeletedGrid> {
+        let grid = self.grid_manager.grids.get(g_id)?.clone();
+        let no_phantoms = self.grid_manager.no_phantoms.contains(&g_id);
+        let small_spheres = self.grid_manager.small_spheres.contains(&g_id);
+        let visibility = self.grid_manager.visibility.get(&g_id).cloned();
+        let mut helices = Vec::new();
+        for (h_id, h) in self.design.helices.iter_mut() {
+            if let Some(grid_position) = h.grid_position.as_mut() {
+                if grid_position.grid == g_id {
+                    helices.push((*h_id, *grid_position));
+                    h.grid_position = None;
+                    h.end_movement();
+                } else if grid_position.grid > g_id {
+                    grid_position.grid -= 1;
+                }
+            }
+        }
+        self.grid_manager.remove_grid(g_id);
+        self.update_status = true;
+        self.modified = true;
+        self.hash_maps_update = true;
+        self.grid_manager.update(&mut self.design);
+        self.update_grids();
+        Some(DeletedGrid {
+            g_id,
+            grid,
+            helices,
+            no_phantoms,
+            small_spheres,
+            visibility,
+        })
+    }
+
+    /// Undo the deletion of a grid, re-attaching the helices that were on it.
+    pub fn restore_grid(&mut self, deleted: DeletedGrid) {
+        for h in self.design.helices.values_mut() {
+            if let Some(grid_position) = h.grid_position.as_mut() {
+                if grid_position.grid >= deleted.g_id {
+                    grid_position.grid += 1;
+                }
+            }
+        }
+        for (h_id, grid_position) in deleted.helices.iter() {
+            if let Some(h) = self.design.helices.get_mut(h_id) {
+                h.grid_position = Some(*grid_position);
+            }
+        }
+        let g_id = deleted.g_id;
+        self.grid_manager.insert_grid(g_id, deleted.grid);
+        if deleted.no_phantoms {
+            self.grid_manager.no_phantoms.insert(g_id);
+        }
+        if deleted.small_spheres {
+            self.grid_manager.small_spheres.insert(g_id);
+        }
+        if let Some(visibility) = deleted.visibility {
+            self.grid_manager.set_visibility(g_id, visibility);
+        }
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         self.grid_manager.update(&mut self.design);
         self.update_grids();
@@ -2259,6 +3221,7 @@ impl Data {
     pub fn add_grid(&mut self, desc: GridDescriptor) -> usize {
         let n = self.grid_manager.add_grid(desc);
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         self.grid_manager.update(&mut self.design);
         self.update_grids();
@@ -2289,6 +3252,7 @@ impl Data {
         }
         self.update_grids();
         self.update_status = true;
+        self.modified = true;
     }
 
     pub fn helix_has_small_spheres(&mut self, h_id: &usize) -> bool {
@@ -2313,6 +3277,7 @@ impl Data {
         self.grids[*g_id as usize].write().unwrap().small_spheres = small;
         self.update_grids();
         self.update_status = true;
+        self.modified = true;
     }
 
     pub fn get_grid_pos_helix(&self, h_id: u32) -> Option<GridPosition> {
@@ -2333,10 +3298,53 @@ impl Data {
             .map(|h| h.isometry2d = Some(isometry2d));
     }
 
+    /// Return the 2d isometries of all the helices that have one
+    pub fn get_layout_2d(&self) -> Layout2D {
+        let isometries = self
+            .design
+            .helices
+            .iter()
+            .filter_map(|(h_id, h)| h.isometry2d.map(|isometry| (*h_id, Some(isometry))))
+            .collect();
+        Layout2D { isometries }
+    }
+
+    /// Set the 2d isometries of the helices of `layout` that exist in the design.
+    ///
+    /// Return a pair `(initial, applied)` where `initial` contains the previous isometries of the
+    /// modified helices, `None` for the helices that had no isometry, and `applied` the
+    /// isometries that were actually set.
+    pub fn apply_layout_2d(&mut self, layout: &Layout2D) -> (Layout2D, Layout2D) {
+        let mut initial = Layout2D::default();
+        let mut applied = Layout2D::default();
+        for (h_id, isometry) in layout.isometries.iter() {
+            if let Some(h) = self.design.helices.get_mut(h_id) {
+                initial.isometries.insert(*h_id, h.isometry2d);
+                h.isometry2d = *isometry;
+                applied.isometries.insert(*h_id, *isometry);
+            }
+        }
+        if !applied.isometries.is_empty() {
+            self.update_status = true;
+            self.modified = true;
+        }
+        (initial, applied)
+    }
+
     pub fn get_strand_nucl(&self, nucl: &Nucl) -> Option<usize> {
         self.design.get_strand_nucl(nucl)
     }
 
+    /// Return the identifiers of the strands that go through the same nucleotide several times
+    pub fn find_self_overlapping_strands(&self) -> Vec<usize> {
+        self.design
+            .strands
+            .iter()
+            .filter(|(_, s)| s.has_repeated_nucl())
+            .map(|(s_id, _)| *s_id)
+            .collect()
+    }
+
     pub fn get_visibility_helix(&self, h_id: usize) -> Option<bool> {
         self.design.helices.get(&h_id).map(|h| h.visible)
     }
@@ -2351,6 +3359,25 @@ impl Data {
         self.hash_maps_update = update;
     }
 
+    pub fn get_visibility_strand(&self, s_id: usize) -> Option<bool> {
+        if self.design.strands.contains_key(&s_id) {
+            Some(!self.invisible_strands.contains(&s_id))
+        } else {
+            None
+        }
+    }
+
+    pub fn set_visibility_strand(&mut self, s_id: usize, visibility: bool) {
+        let update = self.get_visibility_strand(s_id) == Some(!visibility);
+        if visibility {
+            self.invisible_strands.remove(&s_id);
+        } else if update {
+            self.invisible_strands.insert(s_id);
+        }
+        self.update_status = update;
+        self.hash_maps_update = update;
+    }
+
     pub fn set_visibility_grid(&mut self, g_id: usize, visibility: bool) {
         let update = self.grid_manager.get_visibility(g_id) != visibility;
         self.grid_manager.set_visibility(g_id, visibility);
@@ -2399,6 +3426,27 @@ impl Data {
         None
     }
 
+    /// Return the pairs of paired nucleotides whose bases are not complementary. Nucleotides
+    /// whose basis, or whose complement's basis, is not known are ignored.
+    pub fn check_base_pairing(&self) -> Vec<(Nucl, Nucl)> {
+        let basis_map = self.basis_map.read().unwrap();
+        let mut ret: Vec<(Nucl, Nucl)> = basis_map
+            .iter()
+            .filter(|(nucl, _)| nucl.forward)
+            .filter_map(|(nucl, basis)| {
+                let basis_compl = basis_map.get(&nucl.compl())?;
+                let expected = compl(Some(basis.to_ascii_uppercase()));
+                if expected != Some(basis_compl.to_ascii_uppercase()) {
+                    Some((*nucl, nucl.compl()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        ret.sort();
+        ret
+    }
+
     pub fn get_scaffold_sequence_len(&self) -> Option<usize> {
         self.design.scaffold_sequence.as_ref().map(|s| s.len())
     }
@@ -2415,8 +3463,10 @@ impl Data {
     /// This function will panic if all the sapples are not matched.
     pub fn get_stapples(&self) -> Vec<Stapple> {
         let mut ret = Vec::new();
-        let mut sequences: BTreeMap<(usize, isize, usize, isize), (usize, String)> =
-            Default::default();
+        let mut sequences: BTreeMap<
+            (usize, isize, usize, isize),
+            (usize, String, Option<(Nucl, Nucl)>, u32),
+        > = Default::default();
         let basis_map = self.basis_map.read().unwrap();
         for (s_id, strand) in self.design.strands.iter() {
             if strand.length() == 0 || self.design.scaffold_id == Some(*s_id) {
@@ -2440,15 +3490,17 @@ impl Data {
                     }
                 }
             }
-            let key = if let Some((prim5, prim3)) = strand.get_5prime().zip(strand.get_3prime()) {
+            let ends = strand.get_5prime().zip(strand.get_3prime());
+            let key = if let Some((prim5, prim3)) = ends {
                 (prim5.helix, prim5.position, prim3.helix, prim5.position)
             } else {
                 println!("WARNING, STAPPLE WITH NO KEY !!!");
                 (0, 0, 0, 0)
             };
-            sequences.insert(key, (*s_id, sequence));
+            sequences.insert(key, (*s_id, sequence, ends, strand.color));
         }
-        for (n, ((h5, nt5, h3, nt3), (s_id, sequence))) in sequences.iter().enumerate() {
+        for (n, ((h5, nt5, h3, nt3), (s_id, sequence, ends, color))) in sequences.iter().enumerate()
+        {
             let plate = n / 96 + 1;
             let row = (n % 96) / 8 + 1;
             let column = match (n % 96) % 8 {
@@ -2470,6 +3522,9 @@ impl Data {
                     "Stapple {:04}; 5':h{}:nt{}>3':h{}:nt{}",
                     s_id, *h5, *nt5, *h3, *nt3
                 ),
+                s_id: *s_id,
+                ends: *ends,
+                color: *color,
             });
         }
         ret
@@ -2505,6 +3560,7 @@ impl Data {
         self.design.scaffold_shift = Some(best_shfit);
         self.read_scaffold_seq(best_shfit);
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         (best_shfit, best_result)
     }
@@ -2609,6 +3665,7 @@ impl Data {
         }
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     pub fn set_group(&mut self, h_id: usize, new_group: Option<bool>) {
@@ -2619,12 +3676,16 @@ impl Data {
         }
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
-    pub fn get_suggestions(&self) -> Vec<(Nucl, Nucl)> {
+    /// Return pairs of nucleotides that could be linked by a cross-over, and whose centers are
+    /// less than `max_dist` nanometers apart. Each nucleotide appears in at most one pair, the
+    /// closest pairs being chosen first.
+    pub fn get_suggestions(&self, max_dist: f32) -> Vec<(Nucl, Nucl)> {
         let mut ret = vec![];
         for blue_nucl in self.blue_nucl.iter() {
-            let neighbour = self.get_possible_cross_over(blue_nucl);
+            let neighbour = self.get_possible_cross_over(blue_nucl, max_dist);
             for (red_nucl, dist) in neighbour {
                 ret.push((*blue_nucl, red_nucl, dist))
             }
@@ -2646,15 +3707,16 @@ impl Data {
         ret
     }
 
-    pub fn get_possible_cross_over(&self, nucl: &Nucl) -> Vec<(Nucl, f32)> {
+    pub fn get_possible_cross_over(&self, nucl: &Nucl, max_dist: f32) -> Vec<(Nucl, f32)> {
         let mut ret = Vec::new();
         let positions = self.get_space_pos(nucl).unwrap();
         let cube0 = space_to_cube(positions[0], positions[1], positions[2]);
 
-        let len_crit = 1.2;
-        for i in vec![-1, 0, 1].iter() {
-            for j in vec![-1, 0, 1].iter() {
-                for k in vec![-1, 0, 1].iter() {
+        let len_crit = max_dist;
+        let nb_cubes = (max_dist / CUBE_LEN).ceil().max(1.) as isize;
+        for i in -nb_cubes..=nb_cubes {
+            for j in -nb_cubes..=nb_cubes {
+                for k in -nb_cubes..=nb_cubes {
                     let cube = (cube0.0 + i, cube0.1 + j, cube0.2 + k);
                     if let Some(v) = self.red_cubes.get(&cube) {
                         for red_nucl in v {
@@ -2697,9 +3759,38 @@ impl Data {
         ret
     }
 
+    /// Return the length, in nanometers, of the path that goes through the nucleotides of strand
+    /// `s_id` from its 5' end to its 3' end.
+    ///
+    /// The distances between the two ends of each cross-over are included, as well as the
+    /// distance between the 3' and 5' ends if the strand is cyclic. Return `None` if the strand
+    /// does not exist or if the position of one of its nucleotides is not known.
+    pub fn strand_contour_length(&self, s_id: usize) -> Option<f32> {
+        let strand = self.design.strands.get(&s_id)?;
+        let mut positions = Vec::with_capacity(strand.length());
+        for d in strand.domains.iter() {
+            if let icednano::Domain::HelixDomain(dom) = d {
+                for position in dom.iter() {
+                    let nucl = Nucl {
+                        helix: dom.helix,
+                        position,
+                        forward: dom.forward,
+                    };
+                    positions.push(self.get_nucl_position(&nucl)?);
+                }
+            }
+        }
+        let mut ret: f32 = positions.windows(2).map(|w| (w[1] - w[0]).mag()).sum();
+        if strand.cyclic && positions.len() > 1 {
+            ret += (positions[0] - positions[positions.len() - 1]).mag();
+        }
+        Some(ret)
+    }
+
     pub fn recolor_stapples(&mut self) {
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
         for (s_id, strand) in self.design.strands.iter_mut() {
             if Some(*s_id) != self.design.scaffold_id {
                 let color = {
@@ -2718,27 +3809,72 @@ impl Data {
         }
     }
 
-    pub fn clean_up_domains(&mut self) {
+    /// Merge the consecutive domains of all strands, and return a report of the changes.
+    pub fn clean_up_domains(&mut self) -> CleanupReport {
         let ids: Vec<usize> = self.design.strands.keys().cloned().collect();
+        let mut report = CleanupReport::default();
         for s_id in ids {
-            self.clean_domains_one_strand(s_id)
+            let nb_merged = self.clean_domains_one_strand(s_id);
+            if nb_merged > 0 {
+                report.merged_domains += nb_merged;
+                report.strands_affected += 1;
+            }
+        }
+        report
+    }
+
+    /// Reverse the polarity of strand `s_id`, keeping its cross-overs. Nothing is done if a
+    /// nucleotide of the reversed strand is already used by an other strand.
+    pub fn reverse_strand(&mut self, s_id: usize) -> Option<(StrandState, StrandState)> {
+        let mut strand = self.design.strands.get(&s_id).cloned()?;
+        strand.reverse();
+        for domain in strand.domains.iter() {
+            if let icednano::Domain::HelixDomain(dom) = domain {
+                for position in dom.iter() {
+                    let nucl = Nucl {
+                        position,
+                        forward: dom.forward,
+                        helix: dom.helix,
+                    };
+                    if self
+                        .get_strand_nucl(&nucl)
+                        .map_or(false, |other| other != s_id)
+                    {
+                        return None;
+                    }
+                }
+            }
         }
+        let init = self.get_strand_state();
+        self.rm_strand(s_id);
+        strand.read_junctions(&mut self.xover_ids, true);
+        strand.read_junctions(&mut self.xover_ids, false);
+        self.design.strands.insert(s_id, strand);
+        // The reversed strand has as many nucleotides and bounds as before, so its elements keep
+        // the same identifiers once the hash maps are rebuilt.
+        self.strands_to_update.insert(s_id);
+        self.modified = true;
+        self.hash_maps_update = true;
+        Some((init, self.get_strand_state()))
     }
 
-    fn clean_domains_one_strand(&mut self, s_id: usize) {
+    /// Merge the consecutive domains of strand `s_id` and return the number of merges.
+    fn clean_domains_one_strand(&mut self, s_id: usize) -> usize {
         if !self.design.strands.contains_key(&s_id) {
-            return;
+            return 0;
         }
         let mut strand = self.design.strands.get(&s_id).cloned().unwrap();
         self.rm_strand(s_id);
-        strand.merge_consecutive_domains();
+        let nb_merged = strand.merge_consecutive_domains();
         strand.junctions.clear();
         strand.read_junctions(&mut self.xover_ids, true);
         strand.read_junctions(&mut self.xover_ids, false);
         self.design.strands.insert(s_id, strand);
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         self.view_need_reset = true;
+        nb_merged
     }
 
     /// Return the infomation necessary to make a crossover from source_nucl to target_nucl
@@ -2779,51 +3915,274 @@ impl Data {
         self.design.helices.get_mut(&h_id).map(|h| h.set_roll(roll));
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     pub fn get_roll_helix(&self, h_id: usize) -> Option<f32> {
         self.design.helices.get(&h_id).map(|h| h.roll)
     }
 
-    pub fn has_template(&self) -> bool {
-        self.template_manager.templates.len() > 0
+    /// Set the roll of each helix of `rolls`.
+    pub fn set_rolls(&mut self, rolls: &BTreeMap<usize, f32>) {
+        for (h_id, roll) in rolls.iter() {
+            if let Some(helix) = self.design.helices.get_mut(h_id) {
+                helix.set_roll(*roll);
+            }
+        }
+        self.hash_maps_update = true;
+        self.update_status = true;
+        self.modified = true;
     }
 
-    fn can_add_domains(&self, domains: &[icednano::Domain]) -> bool {
-        for s in self.design.strands.values() {
-            if s.intersect_domains(domains) {
-                return false;
+    /// Adjust the roll of the helices involved in cross-overs to minimize the sum of the squared
+    /// distances between the nucleotides linked by a cross-over.
+    ///
+    /// The rolls are optimized by coordinate descent: each helix is rolled by plus or minus
+    /// `step` as long as this decreases the sum, and `step` is halved when no helix can be
+    /// improved anymore. The progress is sent on `channel`. Return the final sum, and the initial
+    /// rolls of the helices involved in cross-overs.
+    pub fn optimize_rolls(&mut self, channel: Sender<f32>) -> (f32, BTreeMap<usize, f32>) {
+        const NB_STEP_HALVINGS: usize = 16;
+        let xovers: Vec<(Nucl, Nucl)> = self
+            .get_xovers_list()
+            .into_iter()
+            .map(|(_, xover)| xover)
+            .filter(|(n1, n2)| {
+                self.design.helices.contains_key(&n1.helix)
+                    && self.design.helices.contains_key(&n2.helix)
+            })
+            .collect();
+        let helices: BTreeSet<usize> = xovers
+            .iter()
+            .flat_map(|(n1, n2)| vec![n1.helix, n2.helix])
+            .collect();
+        let initial_rolls = helices
+            .iter()
+            .map(|h_id| (*h_id, self.design.helices[h_id].roll))
+            .collect();
+        let parameters = self.design.parameters.unwrap_or_default();
+
+        let mut energy = self.xovers_tension(&xovers, &parameters);
+        let mut step = std::f32::consts::FRAC_PI_4;
+        for i in 0..NB_STEP_HALVINGS {
+            let _ = channel.send(i as f32 / NB_STEP_HALVINGS as f32);
+            let mut improved = true;
+            while improved {
+                improved = false;
+                for h_id in helices.iter() {
+                    for delta in [step, -step].iter() {
+                        self.design.helices.get_mut(h_id).unwrap().roll(*delta);
+                        let new_energy = self.xovers_tension(&xovers, &parameters);
+                        if new_energy < energy {
+                            energy = new_energy;
+                            improved = true;
+                            break;
+                        } else {
+                            self.design.helices.get_mut(h_id).unwrap().roll(-*delta);
+                        }
+                    }
+                }
             }
+            step /= 2.;
         }
-        true
+        let _ = channel.send(1.);
+        self.hash_maps_update = true;
+        self.update_status = true;
+        self.modified = true;
+        (energy, initial_rolls)
     }
 
-    pub(super) fn get_strand_state(&self) -> StrandState {
-        StrandState {
-            strands: self.design.strands.clone(),
-            xover_ids: self.xover_ids.clone(),
-        }
+    /// The sum of the squared distances between the nucleotides linked by `xovers`.
+    fn xovers_tension(&self, xovers: &[(Nucl, Nucl)], parameters: &Parameters) -> f32 {
+        xovers
+            .iter()
+            .map(|(n1, n2)| {
+                let pos1 =
+                    self.design.helices[&n1.helix].space_pos(parameters, n1.position, n1.forward);
+                let pos2 =
+                    self.design.helices[&n2.helix].space_pos(parameters, n2.position, n2.forward);
+                (pos1 - pos2).mag_sq()
+            })
+            .sum()
     }
 
-    pub fn general_cross_over(
-        &mut self,
-        source_nucl: Nucl,
-        target_nucl: Nucl,
-    ) -> Option<(StrandState, StrandState)> {
-        if source_nucl.helix == target_nucl.helix {
-            return None;
+    /// Reset the roll of an helix to zero and realign its axis.
+    ///
+    /// If the helix is on a grid, its axis is aligned with the grid's normal. Otherwise, the
+    /// helix keeps its position and its axis is snapped to the closest principal axis.
+    /// Return the helix as it was before the modification.
+    pub fn straighten_helix(&mut self, h_id: usize) -> Option<Helix> {
+        let helix = self.design.helices.get_mut(&h_id)?;
+        let initial = helix.clone();
+        helix.set_roll(0.);
+        if let Some(grid_position) = helix.grid_position.as_mut() {
+            grid_position.roll = 0.;
+        } else {
+            snap_to_principal_axis(helix);
         }
-        let init = self.get_strand_state();
-        println!("cross over between {:?} and {:?}", source_nucl, target_nucl);
-        let source_id = self.get_strand_nucl(&source_nucl);
-        let target_id = self.get_strand_nucl(&target_nucl);
+        helix.end_movement();
+        self.hash_maps_update = true;
+        self.update_status = true;
+        self.modified = true;
+        self.grid_manager.update(&mut self.design);
+        Some(initial)
+    }
 
-        let source = source_id
-            .as_ref()
-            .and_then(|source_id| self.design.strands.get(source_id).cloned());
-        let target = target_id
-            .as_ref()
-            .and_then(|target_id| self.design.strands.get(target_id).cloned());
+    /// Snap the axis of helix `h_id` to the closest of the principal axes, keeping its position
+    /// and its roll. Return the helix as it was before the modification.
+    ///
+    /// Return `None` if the helix does not exist or if it is on a grid, since the orientation of
+    /// such helices is given by their grid.
+    pub fn snap_helix_orientation(&mut self, h_id: usize) -> Option<Helix> {
+        let helix = self
+            .design
+            .helices
+            .get_mut(&h_id)
+            .filter(|h| h.grid_position.is_none())?;
+        let initial = helix.clone();
+        snap_to_principal_axis(helix);
+        helix.end_movement();
+        self.hash_maps_update = true;
+        self.update_status = true;
+        self.modified = true;
+        self.view_need_reset = true;
+        self.grid_manager.update(&mut self.design);
+        Some(initial)
+    }
+
+    /// Replace helix `h_id` by `helix`.
+    pub fn set_helix(&mut self, h_id: usize, helix: Helix) {
+        if let Some(h) = self.design.helices.get_mut(&h_id) {
+            *h = helix;
+            self.hash_maps_update = true;
+            self.update_status = true;
+            self.modified = true;
+            self.grid_manager.update(&mut self.design);
+        }
+    }
+
+    pub fn has_template(&self) -> bool {
+        self.template_manager.templates.len() > 0
+    }
+
+    /// Create a scaffold strand that goes back and forth on all the helices of grid `g_id`.
+    ///
+    /// The helices are visited column by column, and the direction of the route is reversed
+    /// in every other column. The strand goes forward on the first helix, backward on the next
+    /// one and so on, so that the cross-overs are always at the ends of the helices. All the
+    /// domains span the union of the intervals of the routed helices, or
+    /// `DEFAULT_SCAFFOLD_ROUTE_LENGTH` nucleotides if these helices are empty.
+    ///
+    /// Return `None` if the grid has no helix or if the route intersects an existing strand.
+    pub fn auto_route_scaffold(
+        &mut self,
+        g_id: usize,
+    ) -> Option<(ExtendedStrandState, ExtendedStrandState)> {
+        use crate::consts::DEFAULT_SCAFFOLD_ROUTE_LENGTH;
+        let mut helices = self.get_helices_grid_key_coord(g_id)?;
+        if helices.is_empty() {
+            return None;
+        }
+        helices.sort();
+        let mut route = Vec::with_capacity(helices.len());
+        let mut column_start = 0;
+        let mut nb_columns = 0;
+        while column_start < helices.len() {
+            let x = (helices[column_start].0).0;
+            let mut column: Vec<usize> = helices[column_start..]
+                .iter()
+                .take_while(|((x_column, _), _)| *x_column == x)
+                .map(|(_, h_id)| *h_id)
+                .collect();
+            column_start += column.len();
+            if nb_columns % 2 == 1 {
+                column.reverse();
+            }
+            route.extend(column);
+            nb_columns += 1;
+        }
+
+        let intervals = self.design.get_intervals();
+        let (start, end) = route
+            .iter()
+            .filter_map(|h_id| intervals.get(h_id))
+            .fold(None, |acc: Option<(isize, isize)>, (left, right)| {
+                Some(acc.map_or((*left, *right), |(l, r)| (l.min(*left), r.max(*right))))
+            })
+            .map(|(left, right)| (left, right + 1))
+            .unwrap_or((0, DEFAULT_SCAFFOLD_ROUTE_LENGTH));
+        let domains: Vec<Domain> = route
+            .iter()
+            .enumerate()
+            .map(|(i, h_id)| {
+                Domain::HelixDomain(HelixInterval {
+                    helix: *h_id,
+                    start,
+                    end,
+                    forward: i % 2 == 0,
+                    sequence: None,
+                })
+            })
+            .collect();
+        if !self.can_add_domains(&domains) {
+            return None;
+        }
+
+        let init = self.get_extended_strand_state(&[]);
+        let junctions = icednano::read_junctions(&domains, false);
+        let new_key = self.design.strands.keys().max().map(|k| k + 1).unwrap_or(0);
+        self.design.strands.insert(
+            new_key,
+            Strand {
+                domains,
+                junctions,
+                sequence: None,
+                cyclic: false,
+                color: crate::consts::SCAFFOLD_COLOR,
+            },
+        );
+        self.set_scaffold_id(Some(new_key));
+        self.update_status = true;
+        self.modified = true;
+        self.hash_maps_update = true;
+        Some((init, self.get_extended_strand_state(&[])))
+    }
+
+    fn can_add_domains(&self, domains: &[icednano::Domain]) -> bool {
+        for s in self.design.strands.values() {
+            if s.intersect_domains(domains) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub(super) fn get_strand_state(&self) -> StrandState {
+        StrandState {
+            strands: self.design.strands.clone(),
+            xover_ids: self.xover_ids.clone(),
+        }
+    }
+
+    pub fn general_cross_over(
+        &mut self,
+        source_nucl: Nucl,
+        target_nucl: Nucl,
+    ) -> Option<(StrandState, StrandState)> {
+        if source_nucl.helix == target_nucl.helix {
+            return None;
+        }
+        let init = self.get_strand_state();
+        println!("cross over between {:?} and {:?}", source_nucl, target_nucl);
+        let source_id = self.get_strand_nucl(&source_nucl);
+        let target_id = self.get_strand_nucl(&target_nucl);
+
+        let source = source_id
+            .as_ref()
+            .and_then(|source_id| self.design.strands.get(source_id).cloned());
+        let target = target_id
+            .as_ref()
+            .and_then(|target_id| self.design.strands.get(target_id).cloned());
 
         let source_strand_end = self.is_strand_end(&source_nucl);
         let target_strand_end = self.is_strand_end(&target_nucl);
@@ -2928,10 +4287,66 @@ impl Data {
         self.design.strands = state.strands;
         self.xover_ids = state.xover_ids;
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
         self.view_need_reset = true;
     }
 
+    /// Return the current strand state, together with the scaffold designation and the helices
+    /// `h_ids`.
+    pub(super) fn get_extended_strand_state(&self, h_ids: &[usize]) -> ExtendedStrandState {
+        ExtendedStrandState {
+            strands: self.get_strand_state(),
+            scaffold_id: self.design.scaffold_id,
+            helices: h_ids
+                .iter()
+                .map(|h_id| (*h_id, self.design.helices.get(h_id).cloned()))
+                .collect(),
+        }
+    }
+
+    pub fn new_extended_strand_state(&mut self, state: ExtendedStrandState) {
+        for (h_id, helix) in state.helices.into_iter() {
+            if let Some(helix) = helix {
+                self.design.helices.insert(h_id, helix);
+            } else {
+                self.design.helices.remove(&h_id);
+            }
+        }
+        self.grid_manager.update(&mut self.design);
+        self.update_grids();
+        self.design.scaffold_id = state.scaffold_id;
+        self.new_strand_state(state.strands);
+    }
+
+    /// Set the length of the insertion that follows `nucl` on its strand. An insertion of length
+    /// 0 is removed. Return the strand states before and after the operation.
+    pub fn set_insertion_length(
+        &mut self,
+        nucl: Nucl,
+        length: usize,
+    ) -> Option<(StrandState, StrandState)> {
+        let s_id = self.get_strand_nucl(&nucl)?;
+        let mut strand = self.design.strands.get(&s_id).cloned()?;
+        if !strand.set_insertion_length(&nucl, length) {
+            return None;
+        }
+        if length == 0 {
+            strand.merge_consecutive_domains();
+        }
+        let init = self.get_strand_state();
+        self.rm_strand(s_id);
+        strand.junctions.clear();
+        strand.read_junctions(&mut self.xover_ids, true);
+        strand.read_junctions(&mut self.xover_ids, false);
+        self.design.strands.insert(s_id, strand);
+        self.update_status = true;
+        self.modified = true;
+        self.hash_maps_update = true;
+        self.view_need_reset = true;
+        Some((init, self.get_strand_state()))
+    }
+
     pub fn get_insertions(&mut self, s_id: usize) -> Option<Vec<Nucl>> {
         self.design.strands.get(&s_id).map(|s| s.get_insertions())
     }
@@ -2948,6 +4363,27 @@ impl Data {
         self.anchors.contains(&anchor)
     }
 
+    /// Set whether the helix `h_id` must stay fixed during helices simulations.
+    pub fn set_helix_pinned(&mut self, h_id: usize, pinned: bool) {
+        if pinned {
+            self.pinned_helices.insert(h_id);
+        } else {
+            self.pinned_helices.remove(&h_id);
+        }
+    }
+
+    pub fn is_helix_pinned(&self, h_id: usize) -> bool {
+        self.pinned_helices.contains(&h_id)
+    }
+
+    /// Return a channel on which frames of the trajectory of the running helices simulation are
+    /// sent, or `None` if no helices simulation is running.
+    pub fn subscribe_trajectory(&self) -> Option<Receiver<TrajectoryFrame>> {
+        self.rigid_helix_simulator
+            .as_ref()
+            .map(|simulator| simulator.subscribe_trajectory())
+    }
+
     pub fn rigid_parameters_update(&mut self, parameters: RigidBodyConstants) {
         if let Some(simulator) = self.rigid_helix_simulator.as_mut() {
             simulator.update_parameters(parameters)
@@ -2960,6 +4396,14 @@ impl Data {
         }
     }
 
+    /// Shake all the helices and free nucleotides of the running helix simulation. Does nothing
+    /// if no helix simulation is running.
+    pub fn shake_all(&mut self) {
+        if let Some(simulator) = self.rigid_helix_simulator.as_mut() {
+            simulator.shake_all()
+        }
+    }
+
     /// Set the shift a the hyperboloid grid g_id.
     pub fn set_new_shift(&mut self, g_id: usize, shift: f32) {
         let parameters = self.design.parameters.unwrap_or_default();
@@ -2971,6 +4415,7 @@ impl Data {
         self.hash_maps_update = true;
         self.view_need_reset = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     /// Return the shift a the hyperboloid grid g_id.
@@ -3030,6 +4475,26 @@ impl Data {
         false
     }
 
+    /// Return the average position of the nucleotides in `selection`, or `None` if the selection
+    /// contains no nucleotide.
+    pub fn selection_center_of_mass(&self, selection: &[Selection]) -> Option<Vec3> {
+        let mut sum = Vec3::zero();
+        let mut nb_nucl = 0;
+        for nucl in self.nucleotide.values() {
+            if self.is_in_selection(nucl, selection) {
+                if let Some(position) = self.get_nucl_position(nucl) {
+                    sum += position;
+                    nb_nucl += 1;
+                }
+            }
+        }
+        if nb_nucl > 0 {
+            Some(sum / nb_nucl as f32)
+        } else {
+            None
+        }
+    }
+
     fn update_visibility(&mut self) {
         if let Some(VisibilitySieve {
             selection,
@@ -3141,8 +4606,19 @@ fn read_file<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Option<icednano::Desi
         std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("File not found {:?}", path));
 
     let design: Result<icednano::Design, _> = serde_json::from_str(&json_str);
-    // First try to read icednano format
-    if let Ok(design) = design {
+    if is_cadnano_json(&json_str) {
+        // Cadnano designs are recognized by their top-level key
+        if let Ok(cadnano) = Cadnano::from_file(path) {
+            Some(icednano::Design::from_cadnano(cadnano))
+        } else {
+            message(
+                "Could not read cadnano file".into(),
+                rfd::MessageLevel::Error,
+            );
+            None
+        }
+    } else if let Ok(design) = design {
+        // Otherwise, first try to read icednano format
         println!("ok icednano");
         Some(design)
     } else {
@@ -3169,6 +4645,14 @@ fn read_file<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Option<icednano::Desi
     }
 }
 
+/// Return true if `json_str` is a cadnano design, i.e. if it has a top-level "vstrands" key.
+fn is_cadnano_json(json_str: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(json_str)
+        .ok()
+        .and_then(|value| value.get("vstrands").map(|v| v.is_array()))
+        .unwrap_or(false)
+}
+
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum ObjectType {
     /// A nucleotide identified by its identifier
@@ -3197,23 +4681,314 @@ impl ObjectType {
     }
 }
 
+/// The changes made by `Data::clean_up_domains`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// The number of domains that were merged with the previous domain of their strand
+    pub merged_domains: usize,
+    /// The number of strands that had at least one domain merged
+    pub strands_affected: usize,
+}
+
+/// A grid that was deleted by `Data::delete_grid`, with the helices that were attached to it
+#[derive(Debug, Clone)]
+pub struct DeletedGrid {
+    g_id: usize,
+    grid: Grid,
+    /// The helices that were attached to the grid, with their position on it
+    helices: Vec<(usize, GridPosition)>,
+    no_phantoms: bool,
+    small_spheres: bool,
+    visibility: Option<bool>,
+}
+
+impl DeletedGrid {
+    /// The identifier that the grid had before its deletion
+    pub fn g_id(&self) -> usize {
+        self.g_id
+    }
+}
+
+/// A warning emitted when loading a design
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesignLoadWarning {
+    /// The design is very large, its number of nucleotides is given
+    Large(usize),
+}
+
+/// The reasons why an helix cannot be added on a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridPlacementError {
+    GridDoesNotExist(usize),
+    /// The coordinates are not the ones of a vertex of the grid
+    InvalidPosition {
+        x: isize,
+        y: isize,
+    },
+    /// An helix, whose identifier is given, already lies on the vertex
+    Occupied(usize),
+}
+
+impl fmt::Display for GridPlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::GridDoesNotExist(g_id) => write!(f, "Grid {} does not exist", g_id),
+            Self::InvalidPosition { x, y } => {
+                write!(f, "({}, {}) is not a position of the grid", x, y)
+            }
+            Self::Occupied(h_id) => write!(f, "Helix {} already lies at this position", h_id),
+        }
+    }
+}
+
+/// The reasons why an helix cannot be removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HelixRemovalError {
+    NoSuchHelix(usize),
+    /// Strands, whose identifiers are given, lie on the helix and the removal of strands was not
+    /// requested
+    NotEmpty {
+        h_id: usize,
+        strands: Vec<usize>,
+    },
+}
+
+impl fmt::Display for HelixRemovalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoSuchHelix(h_id) => write!(f, "Helix {} does not exist", h_id),
+            Self::NotEmpty { h_id, strands } => {
+                write!(f, "Strands {:?} lie on helix {}", strands, h_id)
+            }
+        }
+    }
+}
+
+/// An error that occurred while saving a design
+#[derive(Debug)]
+pub enum SaveError {
+    /// The file could not be written
+    Io(std::io::Error),
+    /// The design could not be serialized
+    Serialization(serde_json::Error),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not write file: {}", e),
+            Self::Serialization(e) => write!(f, "Could not serialize design: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialization(e)
+    }
+}
+
 #[derive(Debug)]
 pub struct Stapple {
     pub well: String,
     pub name: String,
     pub sequence: String,
     pub plate: usize,
+    pub s_id: usize,
+    /// The 5' and 3' ends of the stapple
+    pub ends: Option<(Nucl, Nucl)>,
+    pub color: u32,
+}
+
+impl Stapple {
+    pub fn to_record(&self) -> StappleRecord {
+        StappleRecord {
+            id: self.s_id,
+            length: self.sequence.chars().filter(|c| !c.is_whitespace()).count(),
+            sequence: self.sequence.clone(),
+            prime5: self.ends.map(|(n, _)| (n.helix, n.position)),
+            prime3: self.ends.map(|(_, n)| (n.helix, n.position)),
+            color: self.color,
+            plate: self.plate,
+            well: self.well.clone(),
+        }
+    }
+}
+
+/// The description of a stapple written in json stapples exports. The 5' and 3' ends are given
+/// as (helix, position) pairs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StappleRecord {
+    pub id: usize,
+    pub sequence: String,
+    pub length: usize,
+    pub prime5: Option<(usize, isize)>,
+    pub prime3: Option<(usize, isize)>,
+    pub color: u32,
+    pub plate: usize,
+    pub well: String,
+}
+
+/// Return the pieces of `strand` that remain once its domains on helix `h_id` are removed.
+/// Insertions that follow a removed domain are removed too. The junctions of the returned strands
+/// are left empty.
+fn strand_pieces_off_helix(strand: &Strand, h_id: usize) -> Vec<Strand> {
+    strand_pieces(strand, |h| h != h_id)
 }
 
+/// Return the pieces of `strand` made of its domains on the helices for which `keep` returns
+/// `true`. The insertions that follow a removed domain are removed with it.
+///
+/// If no domain is removed the strand is returned unchanged, otherwise the returned pieces are
+/// not cyclic and their junctions are left empty.
+fn strand_pieces<F: Fn(usize) -> bool>(strand: &Strand, keep: F) -> Vec<Strand> {
+    if strand.domains.iter().all(|d| d.helix().map_or(true, &keep)) {
+        return vec![strand.clone()];
+    }
+    let mut chars = strand.sequence.as_ref().map(|s| s.chars());
+    let mut domains: Vec<(Domain, Option<String>)> = strand
+        .domains
+        .iter()
+        .map(|d| {
+            let seq = chars
+                .as_mut()
+                .map(|chars| chars.by_ref().take(d.length()).collect());
+            (d.clone(), seq)
+        })
+        .collect();
+    if strand.cyclic {
+        // Start the path right after a removed domain so that no piece wraps around
+        if let Some(n) = domains
+            .iter()
+            .position(|(d, _)| d.helix().map_or(false, |h| !keep(h)))
+        {
+            domains.rotate_left(n + 1);
+        }
+    }
+
+    let mut pieces = Vec::new();
+    let mut current: Vec<(Domain, Option<String>)> = Vec::new();
+    let mut dropping = false;
+    for (domain, seq) in domains {
+        match domain.helix() {
+            Some(h) if !keep(h) => {
+                dropping = true;
+                pieces.push(std::mem::take(&mut current));
+            }
+            None if dropping => (),
+            _ => {
+                dropping = false;
+                current.push((domain, seq));
+            }
+        }
+    }
+    pieces.push(current);
+
+    pieces
+        .into_iter()
+        .filter(|p| p.iter().any(|(d, _)| d.helix().is_some()))
+        .map(|p| {
+            let sequence = if strand.sequence.is_some() {
+                Some(Cow::Owned(
+                    p.iter().filter_map(|(_, s)| s.clone()).collect::<String>(),
+                ))
+            } else {
+                None
+            };
+            Strand {
+                domains: p.into_iter().map(|(d, _)| d).collect(),
+                junctions: vec![],
+                sequence,
+                cyclic: false,
+                color: strand.color,
+            }
+        })
+        .collect()
+}
+
+/// Read a strand identifier, given either as a number or as a name of the form
+/// "Stapple 0042; ..." like in the stapples export.
+fn parse_strand_identifier(identifier: &str) -> Option<usize> {
+    let identifier = identifier.trim();
+    let number = identifier.strip_prefix("Stapple ").unwrap_or(identifier);
+    let number: String = number.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if number.is_empty() {
+        None
+    } else {
+        number.parse().ok()
+    }
+}
+
+/// The length of the side of the cubes used to look for cross-over suggestions
+const CUBE_LEN: f32 = 1.2;
+
+/// The maximal distance between two nucleotides suggested for a cross-over when no other distance
+/// is given
+pub const DEFAULT_SUGGESTION_DISTANCE: f32 = 1.2;
+
 fn space_to_cube(x: f32, y: f32, z: f32) -> (isize, isize, isize) {
-    let cube_len = 1.2;
     (
-        x.div_euclid(cube_len) as isize,
-        y.div_euclid(cube_len) as isize,
-        z.div_euclid(cube_len) as isize,
+        x.div_euclid(CUBE_LEN) as isize,
+        y.div_euclid(CUBE_LEN) as isize,
+        z.div_euclid(CUBE_LEN) as isize,
     )
 }
 
+/// Align the axis of `helix` with the principal axis that is the closest to it. Only the axis is
+/// rotated, so the twist of the helix around its axis is preserved.
+fn snap_to_principal_axis(helix: &mut Helix) {
+    let axis = Vec3::unit_x().rotated_by(helix.orientation);
+    let target = closest_principal_axis(axis);
+    helix.orientation = (rotation_between(axis, target) * helix.orientation).normalized();
+}
+
+/// Return the principal axis that is the closest to `axis`.
+fn closest_principal_axis(axis: Vec3) -> Vec3 {
+    let candidates = [
+        Vec3::unit_x(),
+        -Vec3::unit_x(),
+        Vec3::unit_y(),
+        -Vec3::unit_y(),
+        Vec3::unit_z(),
+        -Vec3::unit_z(),
+    ];
+    let mut target = candidates[0];
+    for c in candidates.iter() {
+        if c.dot(axis) > target.dot(axis) {
+            target = *c;
+        }
+    }
+    target
+}
+
+/// Return the smallest rotation that maps the unit vector `from` on the unit vector `to`.
+fn rotation_between(from: Vec3, to: Vec3) -> ultraviolet::Rotor3 {
+    if from.dot(to) < -1. + 1e-5 {
+        // The rotation between two opposite vectors is not well defined, we make a half turn
+        // around an axis perpendicular to `from`
+        let other = if from.x.abs() < 0.9 {
+            Vec3::unit_x()
+        } else {
+            Vec3::unit_y()
+        };
+        let perpendicular = from.cross(other).normalized();
+        ultraviolet::Rotor3::from_angle_plane(
+            std::f32::consts::PI,
+            ultraviolet::Bivec3::from_normalized_axis(perpendicular),
+        )
+    } else {
+        ultraviolet::Rotor3::from_rotation_between(from, to)
+    }
+}
+
 fn real_name(path: &PathBuf) -> PathBuf {
     let mut file_name = path.clone();
     let stem = path
@@ -3317,3 +5092,929 @@ impl Default for SimulationState {
         Self::None
     }
 }
+
+#[cfg(test)]
+mod unit_tests {
+    use super::super::fixtures::{data_two_strands, test_design_path};
+    use super::*;
+
+    /// A design with one strand containing 3 domains: H1: -1 -> 4 ; Insertion 5 ; H1: 5 -> 10 ;
+    /// H2: 0 <- 10
+    fn design_one_strand() -> Data {
+        let path_str = format!(
+            "{}/src/design/data/test_designs/one_strand_with_insertion.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let path = Path::new(path_str.as_str());
+        Data::new_with_path(&path.into()).expect("Could parse file")
+    }
+
+    #[test]
+    fn edit_sets_unsaved_changes() {
+        let mut data = design_one_strand();
+        assert!(!data.has_unsaved_changes());
+        let s_id = data.get_all_strand_ids()[0];
+        data.change_strand_color(s_id, 0xFF0000);
+        assert!(data.has_unsaved_changes());
+        data.was_updated();
+        data.strands_update();
+        assert!(data.has_unsaved_changes());
+    }
+
+    #[test]
+    fn saving_clears_unsaved_changes() {
+        let mut data = design_one_strand();
+        let s_id = data.get_all_strand_ids()[0];
+        data.change_strand_color(s_id, 0xFF0000);
+        data.was_updated();
+        data.strands_update();
+        let path = std::env::temp_dir().join("ensnano_unsaved_changes_test.json");
+        data.request_save(&path).expect("Could not save file");
+        assert!(!data.has_unsaved_changes());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn saving_before_polling_clears_unsaved_changes() {
+        let mut data = design_one_strand();
+        let s_id = data.get_all_strand_ids()[0];
+        data.change_strand_color(s_id, 0xFF0000);
+        data.change_strand_sequence(s_id, String::from("ATGC"));
+        let path = std::env::temp_dir().join("ensnano_save_before_polling_test.json");
+        data.request_save(&path).expect("Could not save file");
+        assert!(!data.has_unsaved_changes());
+        data.was_updated();
+        assert!(data.strands_update().is_some());
+        assert!(!data.has_unsaved_changes());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn color_change_only_updates_one_strand() {
+        let mut data = data_two_strands();
+        data.was_updated();
+        data.change_strand_color(1, 0xFF00FF00);
+        assert!(!data.was_updated());
+        let strands = data.strands_update().expect("expected a localized update");
+        assert_eq!(strands.len(), 1);
+        assert!(strands.contains(&1));
+        assert!(data.strands_update().is_none());
+    }
+
+    #[test]
+    fn sequence_change_and_reversal_only_update_one_strand() {
+        let mut data = data_two_strands();
+        data.was_updated();
+        let mut elements = data.get_strand_elements(1);
+        elements.sort();
+
+        data.change_strand_sequence(1, "ACGTACGTAC".to_owned());
+        assert!(!data.was_updated());
+        assert_eq!(data.strands_update(), Some(vec![1].into_iter().collect()));
+        assert!(data.get_symbol(elements[0]).is_some());
+
+        assert!(data.reverse_strand(1).is_some());
+        assert!(!data.was_updated());
+        assert!(!data.view_need_reset());
+        assert_eq!(data.strands_update(), Some(vec![1].into_iter().collect()));
+        let mut reversed_elements = data.get_strand_elements(1);
+        reversed_elements.sort();
+        assert_eq!(reversed_elements, elements);
+        assert_eq!(
+            data.get_strand(1).unwrap().get_5prime(),
+            Some(Nucl::new(2, 0, true))
+        );
+    }
+
+    #[test]
+    fn full_update_overrides_strands_update() {
+        let mut data = data_two_strands();
+        data.was_updated();
+        data.change_strand_color(1, 0xFF00FF00);
+        data.set_visibility_helix(1, false);
+        assert!(data.was_updated());
+        assert!(data.strands_update().is_none());
+    }
+
+    fn two_grids_design() -> Data {
+        let mut data = Data::new();
+        data.add_grid(GridDescriptor {
+            position: Vec3::zero(),
+            orientation: ultraviolet::Rotor3::identity(),
+            grid_type: GridTypeDescr::Square,
+        });
+        data.add_grid(GridDescriptor {
+            position: Vec3::new(3., -2., 5.),
+            orientation: ultraviolet::Rotor3::from_euler_angles(0.3, 1.2, -0.7),
+            grid_type: GridTypeDescr::Honeycomb,
+        });
+        data
+    }
+
+    #[test]
+    fn grid_occupancy_matches_helices() {
+        let mut data = two_grids_design();
+        data.build_helix_grid(0, 0, 0, 0, 0).unwrap();
+        data.build_helix_grid(0, 1, 1, 0, 0).unwrap();
+        let occupancy = data.get_grid_occupancy(0, (0, 1), (0, 1));
+        assert_eq!(
+            occupancy,
+            vec![
+                ((0, 0), true),
+                ((0, 1), false),
+                ((1, 0), false),
+                ((1, 1), true)
+            ]
+        );
+        assert!(data
+            .get_grid_occupancy(1, (0, 1), (0, 1))
+            .iter()
+            .all(|(_, occupied)| !occupied));
+    }
+
+    #[test]
+    fn aligned_grids_face_each_other() {
+        let mut data = two_grids_design();
+        let gap = 4.5;
+        assert!(data.align_grids(0, 1, gap).is_some());
+        let normal_a = Vec3::unit_x().rotated_by(data.get_grid_basis(0).unwrap());
+        let normal_b = Vec3::unit_x().rotated_by(data.get_grid_basis(1).unwrap());
+        assert!((normal_a.dot(normal_b) + 1.).abs() < 1e-4);
+        let delta = data.get_grid_position(1).unwrap() - data.get_grid_position(0).unwrap();
+        assert!((delta.dot(normal_a) - gap).abs() < 1e-4);
+        assert!((delta.mag() - gap).abs() < 1e-4);
+    }
+
+    #[test]
+    fn aligning_aligned_grids_is_a_no_op() {
+        let mut data = two_grids_design();
+        data.align_grids(0, 1, 2.).unwrap();
+        let position = data.get_grid_position(1).unwrap();
+        assert!(data.align_grids(0, 1, 2.).is_none());
+        assert!((data.get_grid_position(1).unwrap() - position).mag() < 1e-6);
+    }
+
+    #[test]
+    fn grid_rotated_in_its_plane_is_realigned() {
+        let mut data = two_grids_design();
+        data.align_grids(0, 1, 2.).unwrap();
+        let position = data.get_grid_position(1).unwrap();
+        let orientation = data.get_grid_basis(1).unwrap();
+        // Rotating the grid arround its normal keeps its plane parallel to the one of grid 0
+        let in_plane_rotation = ultraviolet::Rotor3::from_rotation_yz(0.4);
+        data.set_grid_isometry(1, position, orientation * in_plane_rotation);
+        assert!(data.align_grids(0, 1, 2.).is_some());
+        let y_axis = Vec3::unit_y().rotated_by(data.get_grid_basis(1).unwrap());
+        assert!((y_axis - Vec3::unit_y().rotated_by(orientation)).mag() < 1e-4);
+    }
+
+    #[test]
+    fn undo_grid_alignment() {
+        let mut data = two_grids_design();
+        let (position, orientation) = data.align_grids(0, 1, 2.).unwrap();
+        data.set_grid_isometry(1, position, orientation);
+        assert!((data.get_grid_position(1).unwrap() - Vec3::new(3., -2., 5.)).mag() < 1e-5);
+    }
+
+    #[test]
+    fn nearest_helices_first() {
+        let mut data = Data::new();
+        let positions = [0., 7., -3., 12., 5.];
+        for (h_id, y) in positions.iter().enumerate() {
+            let helix = Helix::new(Vec3::new(0., *y, 0.), ultraviolet::Rotor3::identity());
+            data.add_helix(&helix, h_id);
+        }
+        let near = data.helices_near(0, 3);
+        let ids: Vec<usize> = near.iter().map(|(h_id, _)| *h_id).collect();
+        assert_eq!(ids, vec![2, 4, 1]);
+        assert!((near[0].1 - 3.).abs() < 1e-5);
+        assert!((near[1].1 - 5.).abs() < 1e-5);
+        assert!((near[2].1 - 7.).abs() < 1e-5);
+        assert_eq!(data.helices_near(0, 10).len(), 4);
+    }
+
+    #[test]
+    fn straighten_grid_helix() {
+        let mut data = two_grids_design();
+        data.build_helix_grid(1, 0, 0, 0, 0).unwrap();
+        let h_id = 1;
+        data.roll_helix(h_id, 0.8);
+        data.design
+            .helices
+            .get_mut(&h_id)
+            .unwrap()
+            .grid_position
+            .as_mut()
+            .unwrap()
+            .roll = 0.4;
+        assert!(data.straighten_helix(h_id).is_some());
+        assert_eq!(data.get_roll_helix(h_id), Some(0.));
+        let axis = data.get_helix_axis(h_id).unwrap().direction.normalized();
+        let normal = Vec3::unit_x().rotated_by(data.get_grid_basis(1).unwrap());
+        assert!((axis - normal).mag() < 1e-4);
+    }
+
+    #[test]
+    fn straighten_free_helix() {
+        let mut data = Data::new();
+        let position = Vec3::new(1., 2., 3.);
+        let orientation = ultraviolet::Rotor3::from_euler_angles(0.1, -0.2, 1.4);
+        data.add_helix(&Helix::new(position, orientation), 0);
+        data.roll_helix(0, -1.2);
+        let initial = data.straighten_helix(0).unwrap();
+        assert_eq!(initial.roll, -1.2);
+        assert_eq!(data.get_roll_helix(0), Some(0.));
+        let axis = data.get_helix_axis(0).unwrap();
+        assert!((axis.origin - position).mag() < 1e-5);
+        let direction = axis.direction.normalized();
+        let principal = [direction.x.abs(), direction.y.abs(), direction.z.abs()];
+        assert!(principal.iter().any(|x| (x - 1.).abs() < 1e-4));
+        data.set_helix(0, initial);
+        assert_eq!(data.get_roll_helix(0), Some(-1.2));
+    }
+
+    #[test]
+    fn snapping_removes_small_perturbation() {
+        let mut data = Data::new();
+        let perturbation = ultraviolet::Rotor3::from_euler_angles(0.02, -0.03, 0.01);
+        data.add_helix(&Helix::new(Vec3::zero(), perturbation), 0);
+        data.roll_helix(0, 0.7);
+        let initial = data.snap_helix_orientation(0).unwrap();
+        assert_eq!(initial.orientation, perturbation);
+        let direction = data.get_helix_axis(0).unwrap().direction.normalized();
+        assert!((direction - Vec3::unit_x()).mag() < 1e-5);
+        assert_eq!(data.get_roll_helix(0), Some(0.7));
+    }
+
+    #[test]
+    fn snapping_preserves_nucleotides_phase() {
+        let mut data = Data::new();
+        let twist = ultraviolet::Rotor3::from_rotation_yz(0.9);
+        let tilt = ultraviolet::Rotor3::from_rotation_xy(0.05);
+        data.add_helix(&Helix::new(Vec3::zero(), tilt * twist), 0);
+        data.snap_helix_orientation(0).unwrap();
+        let parameters = data.design.parameters.unwrap_or_default();
+        let expected = Helix::new(Vec3::zero(), twist);
+        for n in 0..10 {
+            for forward in [true, false].iter() {
+                let snapped_position = data.design.helices[&0].space_pos(&parameters, n, *forward);
+                let expected_position = expected.space_pos(&parameters, n, *forward);
+                assert!((snapped_position - expected_position).mag() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn opposite_vectors_are_mapped_by_a_half_turn() {
+        let rotation = rotation_between(Vec3::unit_x(), -Vec3::unit_x());
+        assert!((Vec3::unit_x().rotated_by(rotation) + Vec3::unit_x()).mag() < 1e-5);
+    }
+
+    #[test]
+    fn grid_helices_are_not_snapped() {
+        let mut data = two_grids_design();
+        data.build_helix_grid(1, 0, 0, 0, 0).unwrap();
+        let orientation = data.design.helices[&1].orientation;
+        assert!(data.snap_helix_orientation(1).is_none());
+        assert_eq!(data.design.helices[&1].orientation, orientation);
+    }
+
+    #[test]
+    fn folded_dimensions_of_planar_design() {
+        let data = data_two_strands();
+        let parameters = data.design.parameters.unwrap_or_default();
+        let (length, width, thickness) = data.estimate_folded_dimensions().unwrap();
+        let diameter = 2. * parameters.helix_radius;
+        assert!((thickness - diameter).abs() < 1e-3);
+        assert!(width > thickness);
+        assert!((length - (9. * parameters.z_step + diameter)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn self_overlapping_strand_is_detected() {
+        let mut data = data_two_strands();
+        assert!(data.find_self_overlapping_strands().is_empty());
+        let interval = |start, end| {
+            icednano::Domain::HelixDomain(HelixInterval {
+                helix: 1,
+                start,
+                end,
+                forward: true,
+                sequence: None,
+            })
+        };
+        let overlapping = Strand {
+            domains: vec![interval(12, 17), interval(15, 20)],
+            sequence: None,
+            cyclic: false,
+            junctions: vec![],
+            color: 0,
+        };
+        data.design.strands.insert(2, overlapping);
+        assert_eq!(data.find_self_overlapping_strands(), vec![2]);
+    }
+
+    #[test]
+    fn auto_routed_scaffold_is_connected() {
+        let mut data = two_grids_design();
+        // The second column of the grid is empty
+        data.build_helix_grid(0, 0, 0, 0, 0).unwrap();
+        data.build_helix_grid(0, 0, 1, 0, 0).unwrap();
+        data.build_helix_grid(0, 2, 0, 0, 0).unwrap();
+        let (init, after) = data.auto_route_scaffold(0).unwrap();
+        data.new_extended_strand_state(init);
+        assert!(data.design.strands.is_empty());
+        assert_eq!(data.design.scaffold_id, None);
+        data.new_extended_strand_state(after);
+
+        assert_eq!(data.design.strands.len(), 1);
+        let s_id = *data.design.strands.keys().next().unwrap();
+        assert_eq!(data.design.scaffold_id, Some(s_id));
+        let strand = &data.design.strands[&s_id];
+        assert!(!strand.cyclic);
+        let helices: Vec<usize> = strand
+            .domains
+            .iter()
+            .filter_map(|d| match d {
+                Domain::HelixDomain(dom) => Some(dom.helix),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(helices, vec![1, 2, 3]);
+        for h_id in 1..=3 {
+            for forward in [false, true].iter() {
+                let nucl = Nucl::new(h_id, 0, *forward);
+                let expected = if (h_id == 2) ^ *forward {
+                    Some(s_id)
+                } else {
+                    None
+                };
+                assert_eq!(data.get_strand_nucl(&nucl), expected);
+            }
+        }
+        // A second route would go through the scaffold
+        assert!(data.auto_route_scaffold(0).is_none());
+    }
+
+    #[test]
+    fn neighbors_across_xover() {
+        let mut data = data_two_strands();
+        let prime3 = Nucl::new(1, 9, true);
+        let prime5 = Nucl::new(2, 9, false);
+        assert!(data.neighbor_3prime(&prime3).is_none());
+        assert!(data.general_cross_over(prime3, prime5).is_some());
+
+        assert_eq!(data.neighbor_3prime(&prime3), Some(prime5));
+        assert_eq!(data.neighbor_5prime(&prime5), Some(prime3));
+        assert_eq!(
+            data.neighbor_3prime(&Nucl::new(1, 4, true)),
+            Some(Nucl::new(1, 5, true))
+        );
+        assert_eq!(
+            data.neighbor_3prime(&Nucl::new(2, 4, false)),
+            Some(Nucl::new(2, 3, false))
+        );
+        assert!(data.neighbor_5prime(&Nucl::new(1, 0, true)).is_none());
+        assert!(data.neighbor_3prime(&Nucl::new(2, 0, false)).is_none());
+    }
+
+    #[test]
+    fn large_design_is_loaded_with_warning() {
+        let path = test_design_path("two_strands.json");
+        let (data, warning) = Data::new_with_path_and_threshold(&path, 15).unwrap();
+        assert_eq!(warning, Some(DesignLoadWarning::Large(20)));
+        assert_eq!(data.get_all_strand_ids(), vec![0, 1]);
+
+        let (_, warning) = Data::new_with_path_and_threshold(&path, 20).unwrap();
+        assert!(warning.is_none());
+    }
+
+    #[test]
+    fn helix_removal_requires_opt_in_for_strands() {
+        let mut data = data_two_strands();
+        assert_eq!(
+            data.remove_helix_with_strands(1, false).unwrap_err(),
+            HelixRemovalError::NotEmpty {
+                h_id: 1,
+                strands: vec![0]
+            }
+        );
+        assert!(data.design.helices.contains_key(&1));
+        assert_eq!(data.get_all_strand_ids(), vec![0, 1]);
+
+        assert!(data.remove_helix_with_strands(1, true).is_ok());
+        assert!(!data.design.helices.contains_key(&1));
+        assert_eq!(data.get_all_strand_ids(), vec![1]);
+        assert!(data.remove_helix_with_strands(2, false).is_err());
+        assert_eq!(
+            data.remove_helix_with_strands(1, true).unwrap_err(),
+            HelixRemovalError::NoSuchHelix(1)
+        );
+    }
+
+    #[test]
+    fn undo_helix_removal_restores_helix() {
+        let mut data = data_two_strands();
+        let helix = data.design.helices.get(&1).cloned().unwrap();
+        let (init, after) = data.remove_helix_with_strands(1, true).unwrap();
+        data.new_extended_strand_state(init);
+        let restored = data.design.helices.get(&1).unwrap();
+        assert!((restored.position - helix.position).mag() < 1e-5);
+        assert_eq!(data.get_all_strand_ids(), vec![0, 1]);
+        data.new_extended_strand_state(after);
+        assert!(!data.design.helices.contains_key(&1));
+        assert_eq!(data.get_all_strand_ids(), vec![1]);
+    }
+
+    #[test]
+    fn helix_removal_splits_crossing_strands() {
+        let mut data = data_two_strands();
+        assert!(data
+            .general_cross_over(Nucl::new(1, 9, true), Nucl::new(2, 9, false))
+            .is_some());
+        assert_eq!(data.get_all_strand_ids().len(), 1);
+
+        assert!(data.remove_helix_with_strands(1, true).is_ok());
+        let ids = data.get_all_strand_ids();
+        assert_eq!(ids.len(), 1);
+        let strand = data.get_strand(ids[0]).unwrap();
+        assert_eq!(strand.length(), 10);
+        assert!(strand.domains.iter().all(|d| d.helix() == Some(2)));
+    }
+
+    #[test]
+    fn paired_nucl_of_duplex() {
+        let mut data = two_grids_design();
+        data.build_helix_grid(0, 0, 0, 0, 10).unwrap();
+        data.make_hash_maps();
+        let nucl = Nucl::new(1, 3, true);
+        assert_eq!(data.get_paired_nucl(&nucl), Some(Nucl::new(1, 3, false)));
+        assert_eq!(data.get_paired_nucl(&nucl.compl()), Some(nucl));
+        assert!(data.get_paired_nucl(&Nucl::new(1, 10, true)).is_none());
+
+        let data = data_two_strands();
+        assert!(data.get_paired_nucl(&Nucl::new(1, 3, true)).is_none());
+    }
+
+    #[test]
+    fn oxdna_topology_header() {
+        let data = data_two_strands();
+        let (topology, configuration) = data.oxdna_strings();
+        assert_eq!(topology.lines().next(), Some("20 2"));
+        assert_eq!(topology.lines().count(), 21);
+        // 3 header lines and one line per nucleotide
+        assert_eq!(configuration.lines().count(), 23);
+        let first_bound: Vec<&str> = topology.lines().nth(1).unwrap().split(' ').collect();
+        assert_eq!(first_bound[0], "0");
+        assert_eq!(first_bound[2], "-1");
+        assert_eq!(first_bound[3], "1");
+    }
+
+    #[test]
+    fn pdb_export_of_a_duplex() {
+        let mut data = two_grids_design();
+        data.build_helix_grid(0, 0, 0, 0, 10).unwrap();
+        let path = std::env::temp_dir().join("ensnano_pdb_export_test.pdb");
+        data.export_pdb(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let atoms: Vec<&str> = content.lines().filter(|l| l.starts_with("ATOM")).collect();
+        assert_eq!(atoms.len(), 20);
+        assert_eq!(content.lines().filter(|l| l.starts_with("TER")).count(), 2);
+        assert_eq!(content.lines().last(), Some("END"));
+        // The two strands of the duplex are on different chains
+        assert_eq!(&atoms[0][21..22], "A");
+        assert_eq!(&atoms[19][21..22], "B");
+        assert_eq!(atoms[0][22..26].trim(), "1");
+    }
+
+    #[test]
+    fn pdb_residue_numbers_wrap() {
+        assert_eq!(pdb::residue_number(0), 1);
+        assert_eq!(pdb::residue_number(9998), 9999);
+        assert_eq!(pdb::residue_number(9999), 1);
+    }
+
+    #[test]
+    fn hidden_strand_is_not_visible() {
+        let mut data = data_two_strands();
+        let strand_of = |data: &Data, id: &u32| data.get_strand_of_element(*id);
+        assert_eq!(data.get_all_visible_nucl_ids().len(), 20);
+
+        data.set_visibility_strand(0, false);
+        assert_eq!(data.get_visibility_strand(0), Some(false));
+        let nucls = data.get_all_visible_nucl_ids();
+        assert_eq!(nucls.len(), 10);
+        assert!(nucls.iter().all(|id| strand_of(&data, id) == Some(1)));
+        let bounds = data.get_all_visible_bound_ids();
+        assert_eq!(bounds.len(), 9);
+        assert!(bounds.iter().all(|id| strand_of(&data, id) == Some(1)));
+
+        data.set_visibility_strand(0, true);
+        assert_eq!(data.get_all_visible_nucl_ids().len(), 20);
+    }
+
+    #[test]
+    fn suggestions_within_distance() {
+        let mut data = data_two_strands();
+        data.set_group(1, Some(true));
+        data.set_group(2, Some(false));
+        data.make_hash_maps();
+        // The axis of the two helices are 5.3nm apart
+        assert!(data.get_suggestions(DEFAULT_SUGGESTION_DISTANCE).is_empty());
+        assert!(data.get_suggestions(3.).is_empty());
+        let max_dist = 4.;
+        let suggestions = data.get_suggestions(max_dist);
+        assert!(!suggestions.is_empty());
+        for (a, b) in suggestions.iter() {
+            let distance =
+                (data.get_nucl_position(a).unwrap() - data.get_nucl_position(b).unwrap()).mag();
+            assert!(distance < max_dist);
+        }
+        assert!(data.get_suggestions(8.).len() > suggestions.len());
+    }
+
+    #[test]
+    fn helix_placement_on_honeycomb_grid() {
+        let mut data = two_grids_design();
+        assert_eq!(data.build_helix_grid(1, 2, -3, 0, 0), Ok(()));
+        let h_id = data.get_helix_grid(1, 2, -3).unwrap() as usize;
+        assert_eq!(
+            data.build_helix_grid(1, 2, -3, 0, 10),
+            Err(GridPlacementError::Occupied(h_id))
+        );
+        assert_eq!(
+            data.build_helix_grid(5, 0, 0, 0, 0),
+            Err(GridPlacementError::GridDoesNotExist(5))
+        );
+        assert_eq!(data.design.helices.len(), 1);
+    }
+
+    #[test]
+    fn helix_placement_out_of_hyperboloid() {
+        let mut data = Data::new();
+        data.add_grid(GridDescriptor {
+            position: Vec3::zero(),
+            orientation: ultraviolet::Rotor3::identity(),
+            grid_type: GridTypeDescr::Hyperboloid {
+                radius: 6,
+                shift: 0.,
+                length: 10.,
+                radius_shift: 0.,
+                forced_radius: None,
+            },
+        });
+        assert_eq!(data.build_helix_grid(0, 5, 0, 0, 0), Ok(()));
+        assert_eq!(
+            data.build_helix_grid(0, 6, 0, 0, 0),
+            Err(GridPlacementError::InvalidPosition { x: 6, y: 0 })
+        );
+        assert_eq!(
+            data.build_helix_grid(0, 1, 1, 0, 0),
+            Err(GridPlacementError::InvalidPosition { x: 1, y: 1 })
+        );
+    }
+
+    #[test]
+    fn extraction_copies_only_the_selected_strands() {
+        let mut data = data_two_strands();
+        let mut strand = data.design.strands[&0].clone();
+        strand.domains.push(Domain::HelixDomain(HelixInterval {
+            helix: 2,
+            start: 10,
+            end: 15,
+            forward: false,
+            sequence: None,
+        }));
+        strand.junctions.clear();
+        data.design.strands.insert(0, strand);
+
+        let extracted = data.extract_selection(&[Selection::Helix(0, 2)]);
+        assert_eq!(extracted.design.helices.len(), 1);
+        assert!(extracted.design.strands.is_empty());
+
+        let extracted = data.extract_selection(&[Selection::Strand(0, 0)]);
+        assert_eq!(extracted.design.helices.len(), 2);
+        assert_eq!(extracted.design.strands.len(), 1);
+        let strand = &extracted.design.strands[&0];
+        assert_eq!(strand.length(), 15);
+        assert_eq!(strand.domains[1].helix(), Some(1));
+    }
+
+    #[test]
+    fn optimized_rolls_do_not_stretch_crossovers() {
+        let mut data = data_two_strands();
+        assert!(data
+            .general_cross_over(Nucl::new(1, 9, true), Nucl::new(2, 9, false))
+            .is_some());
+        data.make_hash_maps();
+        data.roll_helix(1, 2.);
+        let xovers: Vec<(Nucl, Nucl)> = data.get_xovers_list().into_iter().map(|x| x.1).collect();
+        assert_eq!(xovers.len(), 1);
+        let parameters = data.design.parameters.unwrap_or_default();
+        let before = data.xovers_tension(&xovers, &parameters);
+
+        let (snd, rcv) = std::sync::mpsc::channel();
+        let (after, initial_rolls) = data.optimize_rolls(snd);
+        assert!(after <= before);
+        assert_eq!(after, data.xovers_tension(&xovers, &parameters));
+        assert_eq!(rcv.try_iter().last(), Some(1.));
+
+        assert_eq!(initial_rolls.len(), 2);
+        data.set_rolls(&initial_rolls);
+        assert_eq!(data.get_roll_helix(1), Some(2.));
+        assert_eq!(data.xovers_tension(&xovers, &parameters), before);
+    }
+
+    #[test]
+    fn reversed_strand_has_swapped_ends() {
+        let mut data = data_two_strands();
+        assert!(data
+            .general_cross_over(Nucl::new(1, 9, true), Nucl::new(2, 9, false))
+            .is_some());
+        let s_id = data.get_all_strand_ids()[0];
+        let strand = data.get_strand(s_id).unwrap();
+        assert_eq!(strand.get_5prime(), Some(Nucl::new(1, 0, true)));
+        assert_eq!(strand.get_3prime(), Some(Nucl::new(2, 0, false)));
+
+        assert!(data.reverse_strand(s_id).is_some());
+        data.make_hash_maps();
+        let strand = data.get_strand(s_id).unwrap();
+        assert_eq!(strand.domains.len(), 2);
+        assert_eq!(strand.get_5prime(), Some(Nucl::new(2, 0, true)));
+        assert_eq!(strand.get_3prime(), Some(Nucl::new(1, 0, false)));
+        let xovers: Vec<(Nucl, Nucl)> = data.get_xovers_list().into_iter().map(|x| x.1).collect();
+        assert_eq!(
+            xovers,
+            vec![(Nucl::new(2, 9, true), Nucl::new(1, 9, false))]
+        );
+    }
+
+    #[test]
+    fn json_round_trip_keeps_strands_and_helices() {
+        let data = data_two_strands();
+        let json = data.to_json_string().unwrap();
+        let copy = Data::from_json_string(&json).unwrap();
+        assert_eq!(copy.design.strands.len(), data.design.strands.len());
+        assert_eq!(copy.design.helices.len(), data.design.helices.len());
+        assert_eq!(copy.get_all_strand_ids(), data.get_all_strand_ids());
+    }
+
+    #[test]
+    fn cleanup_merges_colinear_domains() {
+        let mut data = data_two_strands();
+        let s_id = data.get_strand_nucl(&Nucl::new(1, 0, true)).unwrap();
+        let split = |start, end| {
+            icednano::Domain::HelixDomain(icednano::HelixInterval {
+                helix: 1,
+                start,
+                end,
+                forward: true,
+                sequence: None,
+            })
+        };
+        data.design.strands.get_mut(&s_id).unwrap().domains = vec![split(0, 5), split(5, 10)];
+        let report = data.clean_up_domains();
+        assert_eq!(report.merged_domains, 1);
+        assert_eq!(report.strands_affected, 1);
+        assert_eq!(data.design.strands[&s_id].domains.len(), 1);
+        assert_eq!(data.clean_up_domains(), CleanupReport::default());
+    }
+
+    #[test]
+    fn insertion_length_is_set() {
+        let mut data = data_two_strands();
+        let nucl = Nucl::new(1, 4, true);
+        let s_id = data.get_strand_nucl(&nucl).unwrap();
+        assert!(data.set_insertion_length(nucl, 3).is_some());
+        assert_eq!(data.get_insertions(s_id), Some(vec![nucl]));
+        assert_eq!(data.design.strands[&s_id].length(), 13);
+
+        data.make_hash_maps();
+        assert!(data.set_insertion_length(nucl, 5).is_some());
+        assert_eq!(data.get_insertions(s_id), Some(vec![nucl]));
+        assert_eq!(data.design.strands[&s_id].length(), 15);
+
+        data.make_hash_maps();
+        assert!(data.set_insertion_length(nucl, 0).is_some());
+        assert_eq!(data.get_insertions(s_id), Some(vec![]));
+        assert_eq!(data.design.strands[&s_id].domains.len(), 1);
+    }
+
+    #[test]
+    fn connected_strands_are_linked_by_pairing_and_crossovers() {
+        let mut data = data_two_strands();
+        let domain = |helix, start, end, forward| {
+            icednano::Domain::HelixDomain(icednano::HelixInterval {
+                helix,
+                start,
+                end,
+                forward,
+                sequence: None,
+            })
+        };
+        // A strand that pairs with strand 0 on helix 1, and crosses over to a part of helix 2
+        // that is not used by strand 1.
+        let mut staple = icednano::Strand {
+            domains: vec![domain(1, 0, 5, false), domain(2, 20, 25, true)],
+            junctions: vec![],
+            sequence: None,
+            cyclic: false,
+            color: 0,
+        };
+        staple.read_junctions(&mut data.xover_ids, true);
+        staple.read_junctions(&mut data.xover_ids, false);
+        data.design.strands.insert(2, staple);
+        data.make_hash_maps();
+
+        let expected: HashSet<usize> = vec![0, 2].into_iter().collect();
+        assert_eq!(data.connected_strands(0), expected);
+        assert_eq!(data.connected_strands(2), expected);
+        let isolated: HashSet<usize> = vec![1].into_iter().collect();
+        assert_eq!(data.connected_strands(1), isolated);
+    }
+
+    #[test]
+    fn one_mismatch_is_reported() {
+        let mut data = data_two_strands();
+        data.design.strands.get_mut(&0).unwrap().sequence = Some("AAAAAAAAAA".into());
+        let mut staple = icednano::Strand {
+            domains: vec![icednano::Domain::HelixDomain(icednano::HelixInterval {
+                helix: 1,
+                start: 0,
+                end: 10,
+                forward: false,
+                sequence: None,
+            })],
+            junctions: vec![],
+            sequence: Some("TTTTTTGTTT".into()),
+            cyclic: false,
+            color: 0,
+        };
+        staple.read_junctions(&mut data.xover_ids, true);
+        staple.read_junctions(&mut data.xover_ids, false);
+        data.design.strands.insert(2, staple);
+        data.make_hash_maps();
+
+        let mismatches = data.check_base_pairing();
+        assert_eq!(mismatches.len(), 1);
+        let (nucl, compl) = mismatches[0];
+        assert_eq!(nucl, Nucl::new(1, 3, true));
+        assert_eq!(compl, nucl.compl());
+    }
+
+    #[test]
+    fn deleted_grid_detaches_its_helices() {
+        let mut data = two_grids_design();
+        data.build_helix_grid(0, 0, 0, 0, 0).unwrap();
+        data.build_helix_grid(1, 0, 0, 0, 0).unwrap();
+        let axis = data.get_helix_axis(1).unwrap();
+        let deleted = data.delete_grid(0).unwrap();
+        assert_eq!(deleted.helices.len(), 1);
+        assert!(data.design.helices[&1].grid_position.is_none());
+        assert_eq!(data.design.helices[&2].grid_position.unwrap().grid, 0);
+        assert_eq!(data.grid_manager.grids.len(), 1);
+        let new_axis = data.get_helix_axis(1).unwrap();
+        assert!((new_axis.origin - axis.origin).mag() < 1e-5);
+        assert!((new_axis.direction - axis.direction).mag() < 1e-5);
+
+        data.restore_grid(deleted);
+        assert_eq!(data.grid_manager.grids.len(), 2);
+        assert_eq!(data.design.helices[&1].grid_position.unwrap().grid, 0);
+        assert_eq!(data.design.helices[&2].grid_position.unwrap().grid, 1);
+    }
+
+    #[test]
+    fn cadnano_design_is_imported() {
+        let path_str = format!(
+            "{}/src/design/data/test_designs/minimal_cadnano.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let path = Path::new(path_str.as_str());
+        let data = Data::new_with_path(&path.into()).expect("Could parse file");
+        assert_eq!(data.design.helices.len(), 2);
+        assert_eq!(data.design.grids.len(), 1);
+        assert_eq!(data.design.strands.len(), 3);
+        let nb_nucls: usize = data.design.strands.values().map(|s| s.length()).sum();
+        assert_eq!(nb_nucls, 32);
+    }
+
+    #[test]
+    fn fill_grid_fills_bounding_rectangle() {
+        let mut data = two_grids_design();
+        data.build_helix_grid(0, 0, 0, 0, 0).unwrap();
+        data.build_helix_grid(0, 2, 1, 0, 0).unwrap();
+        data.build_helix_grid(0, 1, 0, 0, 0).unwrap();
+        assert_eq!(data.fill_grid(0, 0, 10), 3);
+        assert_eq!(data.design.helices.len(), 6);
+        assert!(data
+            .get_grid_occupancy(0, (0, 2), (0, 1))
+            .iter()
+            .all(|(_, occupied)| *occupied));
+        assert_eq!(data.fill_grid(0, 0, 10), 0);
+        assert_eq!(data.fill_grid(1, 0, 10), 0);
+        assert_eq!(data.fill_grid(2, 0, 10), 0);
+    }
+
+    #[test]
+    fn undoing_fill_grid_removes_all_created_helices() {
+        let mut data = two_grids_design();
+        data.build_helix_grid(0, 0, 0, 0, 0).unwrap();
+        data.build_helix_grid(0, 1, 1, 0, 0).unwrap();
+        let nb_strands = data.design.strands.len();
+        let (initial, _) = data.undoable_fill_grid(0, 0, 10).unwrap();
+        assert_eq!(data.design.helices.len(), 4);
+        data.new_extended_strand_state(initial);
+        assert_eq!(data.design.helices.len(), 2);
+        assert_eq!(data.design.strands.len(), nb_strands);
+        assert!(data.undoable_fill_grid(1, 0, 10).is_none());
+    }
+
+    #[test]
+    fn contour_length_of_straight_strand() {
+        let data = data_two_strands();
+        let parameters = data.design.parameters.unwrap_or_default();
+        // Consecutive nucleotides are separated by z_step along the axis, and by a chord of
+        // the helix's circle around it.
+        let beta = 2. * std::f32::consts::PI / parameters.bases_per_turn;
+        let chord = 2. * parameters.helix_radius * (beta / 2.).sin();
+        let step = (parameters.z_step * parameters.z_step + chord * chord).sqrt();
+        let length = data.strand_contour_length(0).unwrap();
+        assert!((length - 9. * step).abs() < 1e-3);
+        assert!(length > 9. * parameters.z_step);
+        assert!(data.strand_contour_length(42).is_none());
+    }
+
+    #[test]
+    fn center_of_mass_of_two_nucleotides() {
+        let data = data_two_strands();
+        let n1 = Nucl {
+            helix: 1,
+            position: 0,
+            forward: true,
+        };
+        let n2 = Nucl {
+            helix: 2,
+            position: 9,
+            forward: false,
+        };
+        let selection = vec![Selection::Nucleotide(0, n1), Selection::Nucleotide(0, n2)];
+        let expected =
+            (data.get_nucl_position(&n1).unwrap() + data.get_nucl_position(&n2).unwrap()) / 2.;
+        let center = data.selection_center_of_mass(&selection).unwrap();
+        assert!((center - expected).mag() < 1e-5);
+        assert!(data.selection_center_of_mass(&[]).is_none());
+        assert!(data
+            .selection_center_of_mass(&[Selection::Helix(0, 42)])
+            .is_none());
+    }
+
+    #[test]
+    fn occupancy_of_helix_with_one_strand() {
+        let data = data_two_strands();
+        assert_eq!(data.helix_occupancy(1), Some((0, 9, 10)));
+        assert_eq!(data.helix_occupancy(42), None);
+    }
+
+    #[test]
+    fn projection_on_xy_plane() {
+        let mut data = two_grids_design();
+        // Grid 0 has the identity orientation, so its helices are parallel to the x axis.
+        data.build_helix_grid(0, 1, 2, 0, 10).unwrap();
+        data.make_hash_maps();
+        let parameters = data.design.parameters.unwrap_or_default();
+        let helix = data.design.helices.get(&1).unwrap().clone();
+        for normal in [Vec3::unit_z(), Vec3::new(0., 0., -3.), Vec3::zero()].iter() {
+            let projection = data.project_to_plane(&[1, 42], *normal);
+            assert_eq!(projection.len(), 1);
+            let (h_id, positions) = &projection[0];
+            assert_eq!(*h_id, 1);
+            assert_eq!(positions.len(), 10);
+            for (n, p) in positions.iter().enumerate() {
+                let axis = helix.axis_position(&parameters, n as isize);
+                assert!(p.z.abs() < 1e-5);
+                assert!((p.x - axis.x).abs() < 1e-5);
+                assert!((p.y - axis.y).abs() < 1e-5);
+            }
+            assert!((positions[9].x - positions[0].x - 9. * parameters.z_step).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn undoing_layout_clears_new_isometries() {
+        let mut data = data_two_strands();
+        data.design.helices.get_mut(&2).unwrap().isometry2d = None;
+        let isometry = ultraviolet::Isometry2::new(
+            ultraviolet::Vec2::new(1., 2.),
+            ultraviolet::Rotor2::identity(),
+        );
+        let layout = Layout2D {
+            isometries: vec![(2, Some(isometry))].into_iter().collect(),
+        };
+        let (initial, _) = data.apply_layout_2d(&layout);
+        assert_eq!(data.design.helices[&2].isometry2d, Some(isometry));
+        assert_eq!(initial.isometries.get(&2), Some(&None));
+        data.apply_layout_2d(&initial);
+        assert!(data.design.helices[&2].isometry2d.is_none());
+    }
+}
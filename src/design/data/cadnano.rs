@@ -51,10 +51,11 @@ impl Design {
                 GridType::square(),
             )
         };
+        // All the helices of a cadnano design lie on the same lattice
+        design.grids.push(grid.desc());
         for (i, v) in vstrands.iter().enumerate() {
             num_to_helix.insert(v.num, i);
-            let position = grid.position_helix(v.col, v.row);
-            let helix = Helix::new(position, Rotor3::identity());
+            let helix = Helix::new_on_grid(&grid, v.col, v.row, 0);
             helices.insert(i, helix);
             for (j, color) in v.stap_colors.iter() {
                 colors.insert((i, *j as usize), *color as usize);
@@ -131,6 +131,14 @@ impl GridDivision for GridType {
             GridType::Hyperboloid(grid) => grid.translate_by_edge(x1, y1, edge),
         }
     }
+
+    fn is_valid_position(&self, x: isize, y: isize) -> bool {
+        match self {
+            GridType::Square(grid) => grid.is_valid_position(x, y),
+            GridType::Honeycomb(grid) => grid.is_valid_position(x, y),
+            GridType::Hyperboloid(grid) => grid.is_valid_position(x, y),
+        }
+    }
 }
 
 impl GridType {
@@ -359,6 +367,11 @@ pub trait GridDivision {
     fn translation_to_edge(&self, x1: isize, y1: isize, x2: isize, y2: isize) -> Edge;
     fn translate_by_edge(&self, x1: isize, y1: isize, edge: Edge) -> Option<(isize, isize)>;
 
+    /// Return true if `(x, y)` is a vertex of the grid.
+    fn is_valid_position(&self, _x: isize, _y: isize) -> bool {
+        true
+    }
+
     fn orientation_helix(&self, _parameters: &Parameters, _x: isize, _y: isize) -> Rotor3 {
         Rotor3::identity()
     }
@@ -887,8 +900,53 @@ impl GridManager {
         }
     }
 
-    pub fn delete_last_grid(&mut self) {
-        self.grids.pop();
+    /// Remove grid `g_id` and return it. The identifiers of the following grids are decreased by
+    /// one.
+    ///
+    /// The positions of the helices are forgotten, they are recomputed by the next call to
+    /// `self.update`.
+    pub fn remove_grid(&mut self, g_id: usize) -> Option<Grid> {
+        if g_id >= self.grids.len() {
+            return None;
+        }
+        let grid = self.grids.remove(g_id);
+        let shift = |set: &HashSet<usize>| {
+            set.iter()
+                .filter(|g| **g != g_id)
+                .map(|g| if *g > g_id { g - 1 } else { *g })
+                .collect()
+        };
+        self.no_phantoms = shift(&self.no_phantoms);
+        self.small_spheres = shift(&self.small_spheres);
+        self.visibility = self
+            .visibility
+            .iter()
+            .filter(|(g, _)| **g != g_id)
+            .map(|(g, v)| (if *g > g_id { g - 1 } else { *g }, *v))
+            .collect();
+        self.helix_to_pos.clear();
+        self.pos_to_helix.clear();
+        Some(grid)
+    }
+
+    /// Insert `grid` with identifier `g_id`. The identifiers of the following grids are
+    /// increased by one.
+    pub fn insert_grid(&mut self, g_id: usize, grid: Grid) {
+        let shift = |set: &HashSet<usize>| {
+            set.iter()
+                .map(|g| if *g >= g_id { g + 1 } else { *g })
+                .collect()
+        };
+        self.no_phantoms = shift(&self.no_phantoms);
+        self.small_spheres = shift(&self.small_spheres);
+        self.visibility = self
+            .visibility
+            .iter()
+            .map(|(g, v)| (if *g >= g_id { g + 1 } else { *g }, *v))
+            .collect();
+        self.grids.insert(g_id.min(self.grids.len()), grid);
+        self.helix_to_pos.clear();
+        self.pos_to_helix.clear();
     }
 
     pub fn add_grid(&mut self, desc: GridDescriptor) -> usize {
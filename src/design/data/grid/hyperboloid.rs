@@ -76,6 +76,11 @@ impl GridDivision for Hyperboloid {
     fn grid_type(&self) -> GridType {
         unimplemented!()
     }
+
+    /// The helices of an hyperboloid are the vertices (i, 0) for 0 <= i < `self.radius`
+    fn is_valid_position(&self, x: isize, y: isize) -> bool {
+        y == 0 && x >= 0 && x < self.radius as isize
+    }
 }
 
 impl Hyperboloid {
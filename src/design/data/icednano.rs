@@ -582,8 +582,30 @@ impl Strand {
         self.domains.iter().map(|d| d.length()).sum()
     }
 
-    /// Merge all consecutive domains that are on the same helix
-    pub fn merge_consecutive_domains(&mut self) {
+    /// Reverse the polarity of the strand. The domains are visited in the opposite order and each
+    /// nucleotide is replaced by its complement, so that the new 5' end of the strand is the
+    /// complement of its former 3' end.
+    ///
+    /// The junctions are cleared and must be read again.
+    pub fn reverse(&mut self) {
+        self.domains.reverse();
+        for domain in self.domains.iter_mut() {
+            if let Domain::HelixDomain(interval) = domain {
+                interval.forward = !interval.forward;
+                if let Some(sequence) = interval.sequence.as_mut() {
+                    *sequence = Cow::Owned(sequence.chars().rev().collect());
+                }
+            }
+        }
+        if let Some(sequence) = self.sequence.as_mut() {
+            *sequence = Cow::Owned(sequence.chars().rev().collect());
+        }
+        self.junctions.clear();
+    }
+
+    /// Merge all consecutive domains that are on the same helix. Return the number of merges
+    /// that were made.
+    pub fn merge_consecutive_domains(&mut self) -> usize {
         let mut to_merge = vec![];
         for n in 0..self.domains.len() - 1 {
             let dom1 = &self.domains[n];
@@ -592,11 +614,13 @@ impl Strand {
                 to_merge.push(n)
             }
         }
+        let nb_merged = to_merge.len();
         while let Some(n) = to_merge.pop() {
             let dom2 = self.domains[n + 1].clone();
             self.domains.get_mut(n).unwrap().merge(&dom2);
             self.domains.remove(n + 1);
         }
+        nb_merged
     }
 
     pub fn xovers(&self) -> Vec<(Nucl, Nucl)> {
@@ -648,6 +672,64 @@ impl Strand {
         false
     }
 
+    /// Return true if the path of the strand goes through the same nucleotide several times.
+    ///
+    /// For cyclic strands, a path whose last nucleotide is its first one is not considered as
+    /// overlapping.
+    pub fn has_repeated_nucl(&self) -> bool {
+        let path = self.nucl_path();
+        let mut seen = HashSet::with_capacity(path.len());
+        !path.into_iter().all(|nucl| seen.insert(nucl))
+    }
+
+    /// Return the nucleotides of the strand in the 5' to 3' order.
+    ///
+    /// If the strand is cyclic, its 5' end is not repeated at the end of the path.
+    fn nucl_path(&self) -> Vec<Nucl> {
+        let mut path = Vec::new();
+        for d in self.domains.iter() {
+            if let Domain::HelixDomain(interval) = d {
+                for position in interval.iter() {
+                    path.push(Nucl {
+                        position,
+                        helix: interval.helix,
+                        forward: interval.forward,
+                    });
+                }
+            }
+        }
+        if self.cyclic && path.len() > 1 && path.first() == path.last() {
+            path.pop();
+        }
+        path
+    }
+
+    /// Return the nucleotide that follows `nucl` on the strand, in the 5' to 3' direction.
+    pub fn next_nucl(&self, nucl: &Nucl) -> Option<Nucl> {
+        let path = self.nucl_path();
+        let idx = path.iter().position(|n| n == nucl)?;
+        if idx + 1 < path.len() {
+            Some(path[idx + 1])
+        } else if self.cyclic && path.len() > 1 {
+            Some(path[0])
+        } else {
+            None
+        }
+    }
+
+    /// Return the nucleotide that precedes `nucl` on the strand, in the 5' to 3' direction.
+    pub fn previous_nucl(&self, nucl: &Nucl) -> Option<Nucl> {
+        let path = self.nucl_path();
+        let idx = path.iter().position(|n| n == nucl)?;
+        if idx > 0 {
+            Some(path[idx - 1])
+        } else if self.cyclic && path.len() > 1 {
+            path.last().cloned()
+        } else {
+            None
+        }
+    }
+
     pub fn find_nucl(&self, nucl: &Nucl) -> Option<usize> {
         let mut ret = 0;
         for d in self.domains.iter() {
@@ -849,6 +931,34 @@ impl Strand {
         }
     }
 
+    /// Set the length of the insertion that follows `nucl` on the strand, adding or removing the
+    /// insertion if needed. An insertion of length 0 is removed.
+    ///
+    /// Return false if `nucl` is not on the strand.
+    pub fn set_insertion_length(&mut self, nucl: &Nucl, length: usize) -> bool {
+        let (d_id, n) = if let Some(point) = self.locate_nucl(nucl) {
+            point
+        } else {
+            return false;
+        };
+        if n + 1 < self.domains[d_id].length() {
+            // nucl is not the 3' end of its domain, so there is no insertion after it yet
+            if length > 0 {
+                self.add_insertion_at_dom_position(d_id, n, length);
+            }
+            return true;
+        }
+        match self.domains.get_mut(d_id + 1) {
+            Some(Domain::Insertion(m)) if length > 0 => *m = length,
+            Some(Domain::Insertion(_)) => {
+                self.domains.remove(d_id + 1);
+            }
+            _ if length > 0 => self.domains.insert(d_id + 1, Domain::Insertion(length)),
+            _ => (),
+        }
+        true
+    }
+
     fn locate_nucl(&self, nucl: &Nucl) -> Option<(usize, usize)> {
         for (d_id, d) in self.domains.iter().enumerate() {
             if let Some(n) = d.has_nucl(nucl) {
@@ -1517,6 +1627,33 @@ impl Helix {
         }
     }
 
+    /// Return the image of this helix by the reflection across the plane going through
+    /// `plane_point` with normal `plane_normal`.
+    ///
+    /// A reflection would turn the helix into a left-handed one, so the basis of the returned
+    /// helix is the reflected one with its z axis reversed, and its roll is negated. The axis of
+    /// the helix is reflected.
+    pub fn mirrored(&self, plane_point: Vec3, plane_normal: Vec3) -> Self {
+        let normal = plane_normal.normalized();
+        let position = self.position - 2. * (self.position - plane_point).dot(normal) * normal;
+        // The reflection is the opposite of the half turn around the normal. Composing with a
+        // half turn around the z axis of the helix negates its x and y axis back.
+        let half_turn =
+            Rotor3::from_angle_plane(PI, ultraviolet::Bivec3::from_normalized_axis(normal));
+        let orientation =
+            (half_turn * self.orientation * Rotor3::from_rotation_xy(PI)).normalized();
+        Self {
+            position,
+            orientation,
+            old_orientation: orientation,
+            old_position: position,
+            isometry2d: None,
+            grid_position: None,
+            visible: self.visible,
+            roll: -self.roll,
+        }
+    }
+
     /// Angle of base number `n` around this helix.
     pub fn theta(&self, n: isize, forward: bool, cst: &Parameters) -> f32 {
         // The groove_angle goes from the backward strand to the forward strand
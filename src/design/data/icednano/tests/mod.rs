@@ -645,3 +645,48 @@ fn check_formated_strand_with_insertion() {
     let strand = strand_with_insertion();
     assert_good_strand(&strand, formated_strand_with_insertion())
 }
+
+#[test]
+fn strand_with_insertion_has_no_repeated_nucl() {
+    let mut strand = strand_with_insertion();
+    assert!(!strand.has_repeated_nucl());
+    strand.cyclic = true;
+    assert!(!strand.has_repeated_nucl());
+}
+
+#[test]
+fn cyclic_wraparound_is_not_a_repetition() {
+    let domains = vec![
+        Domain::HelixDomain(HelixInterval {
+            helix: 0,
+            start: 0,
+            end: 5,
+            forward: true,
+            sequence: None,
+        }),
+        Domain::HelixDomain(HelixInterval {
+            helix: 1,
+            start: 0,
+            end: 5,
+            forward: false,
+            sequence: None,
+        }),
+        Domain::HelixDomain(HelixInterval {
+            helix: 0,
+            start: 0,
+            end: 1,
+            forward: true,
+            sequence: None,
+        }),
+    ];
+    let mut strand = Strand {
+        domains,
+        sequence: None,
+        cyclic: false,
+        junctions: vec![],
+        color: 0,
+    };
+    assert!(strand.has_repeated_nucl());
+    strand.cyclic = true;
+    assert!(!strand.has_repeated_nucl());
+}
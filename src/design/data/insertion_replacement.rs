@@ -27,6 +27,7 @@ impl Data {
             replace_insertions_one_strand(s, helices, &parameters);
         }
         self.update_status = true;
+        self.modified = true;
         self.hash_maps_update = true;
     }
 }
@@ -17,8 +17,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 use super::icednano::{Domain, Helix};
 use super::{Data, Nucl, Parameters};
-use std::io::Write;
-use std::path::Path;
+use std::fmt;
 use ultraviolet::Vec3;
 
 struct OxDnaNucl {
@@ -37,20 +36,19 @@ struct OxDnaConfig {
     nucls: Vec<OxDnaNucl>,
 }
 
-impl OxDnaConfig {
-    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
-        let mut file = std::fs::File::create(path)?;
+impl fmt::Display for OxDnaConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let max = self.boundaries[0].max(self.boundaries[1].max(self.boundaries[2]));
-        writeln!(&mut file, "t = {}", self.time)?;
-        writeln!(&mut file, "b = {} {} {}", max, max, max)?;
+        writeln!(f, "t = {}", self.time)?;
+        writeln!(f, "b = {} {} {}", max, max, max)?;
         writeln!(
-            &mut file,
+            f,
             "E = {} {} {}",
             self.kinetic_energies[0], self.kinetic_energies[1], self.kinetic_energies[2]
         )?;
         for n in self.nucls.iter() {
             writeln!(
-                &mut file,
+                f,
                 "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
                 n.position.x,
                 n.position.y,
@@ -79,13 +77,12 @@ struct OxDnaTopology {
     bounds: Vec<OxDnaBound>,
 }
 
-impl OxDnaTopology {
-    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
-        let mut file = std::fs::File::create(path)?;
-        writeln!(&mut file, "{} {}", self.nb_nucl, self.nb_strand)?;
+impl fmt::Display for OxDnaTopology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", self.nb_nucl, self.nb_strand)?;
         for bound in self.bounds.iter() {
             writeln!(
-                &mut file,
+                f,
                 "{} {} {} {}",
                 bound.strand_id, bound.base, bound.prime5, bound.prime3
             )?;
@@ -194,18 +191,24 @@ impl Data {
         (config, topo)
     }
 
+    /// Return the content of the oxDNA topology and configuration files describing the design.
+    pub fn oxdna_strings(&self) -> (String, String) {
+        let (config, topo) = self.to_oxdna();
+        (topo.to_string(), config.to_string())
+    }
+
     pub fn oxdna_export(&self) {
         let mut config_name = self.file_name.clone();
         config_name.set_extension("oxdna");
         let mut topology_name = self.file_name.clone();
         topology_name.set_extension("top");
-        let (config, topo) = self.to_oxdna();
+        let (topology, configuration) = self.oxdna_strings();
         let mut success = true;
-        if config.write(config_name.clone()).is_err() {
+        if std::fs::write(&config_name, configuration).is_err() {
             println!("Could not write config");
             success = false;
         }
-        if topo.write(topology_name.clone()).is_err() {
+        if std::fs::write(&topology_name, topology).is_err() {
             println!("Could not write topo");
             success = false;
         }
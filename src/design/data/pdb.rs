@@ -0,0 +1,137 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use super::icednano::Domain;
+use super::{Data, Nucl};
+use std::fmt::Write;
+use std::path::Path;
+use ultraviolet::Vec3;
+
+/// The characters used as chain identifiers. Strands are given chain identifiers in that order,
+/// cycling when there are more strands than characters.
+const CHAIN_IDS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Residue numbers are written on 4 columns, so they wrap after this value.
+const MAX_RESIDUE_NUMBER: usize = 9999;
+
+/// Atom serial numbers are written on 5 columns, so they wrap after this value.
+const MAX_ATOM_SERIAL: usize = 99999;
+
+fn chain_id(strand_idx: usize) -> char {
+    CHAIN_IDS[strand_idx % CHAIN_IDS.len()] as char
+}
+
+pub(super) fn residue_number(nucl_idx: usize) -> usize {
+    nucl_idx % MAX_RESIDUE_NUMBER + 1
+}
+
+fn atom_serial(serial: usize) -> usize {
+    (serial - 1) % MAX_ATOM_SERIAL + 1
+}
+
+/// An ATOM record for the phosphorus of a nucleotide.
+fn atom_line(serial: usize, base: char, chain: char, residue: usize, position: Vec3) -> String {
+    format!(
+        "ATOM  {:>5}  P   {:>3} {}{:>4}    {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}           P",
+        atom_serial(serial),
+        base,
+        chain,
+        residue,
+        position.x,
+        position.y,
+        position.z,
+        1.,
+        0.,
+    )
+}
+
+fn ter_line(serial: usize, base: char, chain: char, residue: usize) -> String {
+    format!(
+        "TER   {:>5}      {:>3} {}{:>4}",
+        atom_serial(serial),
+        base,
+        chain,
+        residue
+    )
+}
+
+impl Data {
+    /// Return the content of a PDB file in which each nucleotide of the design is represented by
+    /// the position of its backbone.
+    pub fn to_pdb(&self) -> String {
+        let parameters = self.design.parameters.unwrap_or_default();
+        let basis_map = self.basis_map.read().unwrap();
+        let mut ret = String::new();
+        let mut serial = 1;
+        for (strand_idx, s) in self.design.strands.values().enumerate() {
+            let chain = chain_id(strand_idx);
+            let mut last_residue = None;
+            let mut nucl_idx = 0;
+            for d in s.domains.iter() {
+                if let Domain::HelixDomain(dom) = d {
+                    for position in dom.iter() {
+                        let nucl = Nucl {
+                            position,
+                            helix: dom.helix,
+                            forward: dom.forward,
+                        };
+                        let base = basis_map
+                            .get(&nucl)
+                            .cloned()
+                            .or_else(|| basis_map.get(&nucl.compl()).map(|b| compl(*b)))
+                            .unwrap_or('N');
+                        let space_position = self.design.helices[&dom.helix].space_pos(
+                            &parameters,
+                            position,
+                            dom.forward,
+                        );
+                        let residue = residue_number(nucl_idx);
+                        writeln!(
+                            &mut ret,
+                            "{}",
+                            atom_line(serial, base, chain, residue, space_position)
+                        )
+                        .unwrap();
+                        last_residue = Some((base, residue));
+                        serial += 1;
+                        nucl_idx += 1;
+                    }
+                }
+            }
+            if let Some((base, residue)) = last_residue {
+                writeln!(&mut ret, "{}", ter_line(serial, base, chain, residue)).unwrap();
+                serial += 1;
+            }
+        }
+        writeln!(&mut ret, "END").unwrap();
+        ret
+    }
+
+    pub fn export_pdb(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_pdb())
+    }
+}
+
+fn compl(c: char) -> char {
+    match c {
+        'A' => 'T',
+        'G' => 'C',
+        'T' => 'A',
+        'C' => 'G',
+        _ => 'N',
+    }
+}
@@ -18,12 +18,14 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::*;
 use ahash::RandomState;
 use mathru::algebra::linear::vector::vector::Vector;
-use mathru::analysis::differential_equation::ordinary::{ExplicitEuler, ExplicitODE, Kutta3};
+use mathru::analysis::differential_equation::ordinary::{ExplicitEuler, ExplicitODE, Heun, Kutta3};
 use ordered_float::OrderedFloat;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rand_distr::{Exp, StandardNormal};
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
+use std::time::Duration;
 use ultraviolet::{Bivec3, Mat3, Rotor3, Vec3};
 
 #[derive(Debug)]
@@ -39,11 +41,20 @@ struct HelixSystem {
     parameters: Parameters,
     anchors: Vec<(RigidNucl, Vec3)>,
     free_anchors: Vec<(usize, Vec3)>,
+    /// The indices of the helices that do not move during the simulation
+    fixed_helices: Vec<usize>,
     current_time: f32,
     next_time: f32,
     brownian_heap: BinaryHeap<(Reverse<OrderedFloat<f32>>, usize)>,
     rigid_parameters: RigidBodyConstants,
     max_time_step: f32,
+    /// The largest value that `max_time_step` can reach when it is adapted
+    time_step_limit: f32,
+    /// The step of the integrator. It is adapted together with `max_time_step` and never exceeds
+    /// `rigid_parameters.dt`.
+    time_step: f32,
+    /// The random number generator used for the brownian motion and the shaking of elements
+    rng: StdRng,
 }
 
 #[derive(Clone, Debug)]
@@ -55,6 +66,146 @@ pub struct RigidBodyConstants {
     pub brownian_motion: bool,
     pub brownian_rate: f32,
     pub brownian_amplitude: f32,
+    /// The rest length of the springs modeling the cross-overs
+    pub l0: f32,
+    /// If true, the net linear momentum of the system is removed at each step so that the
+    /// center of mass does not drift
+    pub remove_net_momentum: bool,
+    /// If true, the forces applied on each element are sent together with the state of the
+    /// system so that they can be displayed
+    pub show_forces: bool,
+    /// The integrator used to solve the system. If `None`, the explicit Euler method is used for
+    /// helices simulations and the Kutta3 method is used for grids simulations.
+    pub solver: Option<SolverKind>,
+    /// The time step of the integrator
+    pub dt: f32,
+    /// If some, the seed of the random number generator used in the simulation, so that two
+    /// simulations with the same inputs give the same results.
+    pub seed: Option<u64>,
+    /// If some, the simulation stops by itself once the kinetic energy of the system has stayed
+    /// below this value during `CONVERGENCE_STEPS` consecutive steps.
+    pub convergence_threshold: Option<f32>,
+    /// If some, the temperature, in Kelvin, of the system. The amplitude of the brownian jumps is
+    /// then derived from this temperature and `brownian_amplitude` is ignored.
+    pub temperature_kelvin: Option<f32>,
+    /// The minimum time between two readings of the state of the simulation
+    pub refresh_interval: Duration,
+}
+
+impl RigidBodyConstants {
+    pub const DEFAULT_L0: f32 = 0.7;
+    pub const DEFAULT_DT: f32 = 1e-4;
+    pub const CONVERGENCE_STEPS: usize = 100;
+    pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_millis(30);
+    /// The Boltzmann constant in the units of the simulation, i.e. in pN.nm/K
+    pub const BOLTZMANN_CONSTANT: f32 = 1.380649e-2;
+    /// The factor applied to the forces of the springs and of the anchors when they act on the
+    /// positions of the elements
+    pub const SPRING_FORCE_FACTOR: f32 = 10.;
+    /// The stiffness of the anchors relative to the one of the springs
+    pub const ANCHOR_STIFFNESS_FACTOR: f32 = 1000.;
+
+    /// Return the standard deviation, along each axis, of the displacement of a free nucleotide
+    /// during a brownian jump at temperature `temperature_kelvin`.
+    ///
+    /// This follows the fluctuation-dissipation relation: a particle with friction coefficient
+    /// γ diffuses with coefficient D = kT / γ, and its displacement along one axis during a time
+    /// t has variance 2Dt. Here, the friction force applied on an element is `k_friction` times
+    /// its momentum, so γ = `k_friction` * `mass` / 2 for a free nucleotide, and the jumps of a
+    /// nucleotide happen on average every 1 / `brownian_rate` unit of time. This gives
+    ///
+    /// σ = sqrt(2kT / (γ * `brownian_rate`))
+    ///
+    /// Non positive temperatures produce no jump.
+    pub fn brownian_amplitude_at(&self, temperature_kelvin: f32) -> f32 {
+        let kt = Self::BOLTZMANN_CONSTANT * temperature_kelvin.max(0.);
+        let friction = self.k_friction * self.mass / 2.;
+        (2. * kt / (friction * self.brownian_rate)).sqrt()
+    }
+
+    /// The stiffness of the springs, as felt by the positions of the elements
+    fn spring_stiffness(&self) -> f32 {
+        Self::SPRING_FORCE_FACTOR * self.k_spring
+    }
+
+    /// The stiffness of the anchors, as felt by the positions of the elements
+    fn anchor_stiffness(&self) -> f32 {
+        Self::ANCHOR_STIFFNESS_FACTOR * self.k_spring * self.spring_stiffness()
+    }
+
+    /// If a temperature is set, replace `brownian_amplitude` by the amplitude derived from it.
+    fn apply_temperature(&mut self) {
+        if let Some(temperature) = self.temperature_kelvin {
+            self.brownian_amplitude = self.brownian_amplitude_at(temperature);
+        }
+    }
+}
+
+/// The integrators that can be used to solve the rigid body systems
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolverKind {
+    ExplicitEuler,
+    Heun,
+    Kutta3,
+}
+
+/// Return a random number generator seeded with `seed`, or with entropy if `seed` is `None`.
+fn make_rng(seed: Option<u64>) -> StdRng {
+    seed.map(StdRng::seed_from_u64)
+        .unwrap_or_else(StdRng::from_entropy)
+}
+
+/// Update the number of consecutive steps during which the kinetic energy of a system stayed below
+/// `threshold`, and return true if the simulation has converged.
+fn has_converged(energy: f32, threshold: Option<f32>, converged_steps: &mut usize) -> bool {
+    if let Some(threshold) = threshold {
+        if energy < threshold {
+            *converged_steps += 1;
+        } else {
+            *converged_steps = 0;
+        }
+        *converged_steps >= RigidBodyConstants::CONVERGENCE_STEPS
+    } else {
+        false
+    }
+}
+
+/// Ask a simulation thread for its current state. If the simulation is over, `snd` is dropped so
+/// that the receiving end does not wait for an answer that will never come.
+fn request_state<T>(stop: &Mutex<bool>, request: &Mutex<Option<Sender<T>>>, snd: Sender<T>) {
+    let mut request = request.lock().unwrap();
+    if !*stop.lock().unwrap() {
+        *request = Some(snd);
+    }
+}
+
+/// Mark a simulation as over and answer the last request that was made to its thread, if any.
+fn answer_last_request<T>(
+    stop: &Mutex<bool>,
+    request: &Mutex<Option<Sender<T>>>,
+    state: impl FnOnce() -> T,
+) {
+    let mut request = request.lock().unwrap();
+    *stop.lock().unwrap() = true;
+    if let Some(snd) = request.take() {
+        snd.send(state()).unwrap();
+    }
+}
+
+impl SolverKind {
+    /// Solve `problem` with time step `dt` and return its last state.
+    fn last_state<P: ExplicitODE<f32>>(
+        self,
+        dt: f32,
+        problem: &P,
+    ) -> Result<Option<Vector<f32>>, &'static str> {
+        let (_, y) = match self {
+            Self::ExplicitEuler => ExplicitEuler::new(dt).solve(problem)?,
+            Self::Heun => Heun::new(dt).solve(problem)?,
+            Self::Kutta3 => Kutta3::new(dt).solve(problem)?,
+        };
+        Ok(y.last().cloned())
+    }
 }
 
 #[derive(Debug)]
@@ -93,9 +244,8 @@ impl HelixSystem {
         let mut forces = vec![Vec3::zero(); nb_element];
         let mut torques = vec![Vec3::zero(); nb_element];
 
-        const L0: f32 = 0.7;
         const C_VOLUME: f32 = 2f32;
-        let k_anchor = 1000. * self.rigid_parameters.k_spring;
+        let k_anchor = RigidBodyConstants::ANCHOR_STIFFNESS_FACTOR * self.rigid_parameters.k_spring;
 
         let point_conversion = |nucl: &RigidNucl| {
             let position = positions[nucl.helix]
@@ -112,7 +262,7 @@ impl HelixSystem {
             let point_0 = point_conversion(&spring.0);
             let point_1 = point_conversion(&spring.1);
             let len = (point_1 - point_0).mag();
-            let norm = len - L0;
+            let norm = len - self.rigid_parameters.l0;
 
             // The force applied on point 0
             let force = if len > 1e-5 {
@@ -121,8 +271,8 @@ impl HelixSystem {
                 Vec3::zero()
             };
 
-            forces[spring.0.helix] += 10. * force;
-            forces[spring.1.helix] -= 10. * force;
+            forces[spring.0.helix] += RigidBodyConstants::SPRING_FORCE_FACTOR * force;
+            forces[spring.1.helix] -= RigidBodyConstants::SPRING_FORCE_FACTOR * force;
 
             let torque0 = (point_0 - positions[spring.0.helix]).cross(force);
             let torque1 = (point_1 - positions[spring.1.helix]).cross(-force);
@@ -134,7 +284,7 @@ impl HelixSystem {
             let point_0 = point_conversion(nucl);
             let point_1 = free_nucl_pos(free_nucl_id);
             let len = (point_1 - point_0).mag();
-            let norm = len - L0;
+            let norm = len - self.rigid_parameters.l0;
 
             // The force applied on point 0
             let force = if len > 1e-5 {
@@ -142,8 +292,9 @@ impl HelixSystem {
             } else {
                 Vec3::zero()
             };
-            forces[nucl.helix] += 10. * force;
-            forces[self.helices.len() + *free_nucl_id] -= 10. * force;
+            forces[nucl.helix] += RigidBodyConstants::SPRING_FORCE_FACTOR * force;
+            forces[self.helices.len() + *free_nucl_id] -=
+                RigidBodyConstants::SPRING_FORCE_FACTOR * force;
 
             let torque0 = (point_0 - positions[nucl.helix]).cross(force);
 
@@ -153,7 +304,7 @@ impl HelixSystem {
             let point_0 = free_nucl_pos(id_0);
             let point_1 = free_nucl_pos(id_1);
             let len = (point_1 - point_0).mag();
-            let norm = len - L0;
+            let norm = len - self.rigid_parameters.l0;
 
             // The force applied on point 0
             let force = if len > 1e-5 {
@@ -161,8 +312,8 @@ impl HelixSystem {
             } else {
                 Vec3::zero()
             };
-            forces[self.helices.len() + *id_0] += 10. * force;
-            forces[self.helices.len() + *id_1] -= 10. * force;
+            forces[self.helices.len() + *id_0] += RigidBodyConstants::SPRING_FORCE_FACTOR * force;
+            forces[self.helices.len() + *id_1] -= RigidBodyConstants::SPRING_FORCE_FACTOR * force;
         }
 
         for (nucl, position) in self.anchors.iter() {
@@ -174,7 +325,7 @@ impl HelixSystem {
                 Vec3::zero()
             };
 
-            forces[nucl.helix] += 10. * force;
+            forces[nucl.helix] += RigidBodyConstants::SPRING_FORCE_FACTOR * force;
 
             let torque0 = (point_0 - positions[nucl.helix]).cross(force);
 
@@ -189,7 +340,7 @@ impl HelixSystem {
                 Vec3::zero()
             };
 
-            forces[self.helices.len() + *id] += 10. * force;
+            forces[self.helices.len() + *id] += RigidBodyConstants::SPRING_FORCE_FACTOR * force;
         }
         let segments: Vec<(Vec3, Vec3)> = (0..self.helices.len())
             .map(|n| {
@@ -233,6 +384,27 @@ impl HelixSystem {
                     }
                 }
             }
+            let r = Self::FREE_NUCL_RADIUS;
+            for i in 0..self.free_nucls.len() {
+                for j in (i + 1)..self.free_nucls.len() {
+                    let vec = free_nucl_pos(&i) - free_nucl_pos(&j);
+                    let dist = vec.mag();
+                    if dist < 2. * r {
+                        // Two nucleotides at the exact same position are separated along an
+                        // arbitrary direction
+                        let vec = if dist > 0. {
+                            vec / dist
+                        } else {
+                            Vec3::unit_x()
+                        };
+                        let norm =
+                            C_VOLUME * self.rigid_parameters.k_spring * (2. * r - dist).powi(2);
+                        let norm = norm.min(1e4);
+                        forces[self.helices.len() + i] += norm * vec;
+                        forces[self.helices.len() + j] -= norm * vec;
+                    }
+                }
+            }
         }
 
         (forces, torques)
@@ -240,6 +412,55 @@ impl HelixSystem {
 }
 
 impl HelixSystem {
+    /// The radius of the sphere occupied by a free nucleotide, used for volume exclusion. Twice
+    /// this radius is less than the rest length of the springs so that consecutive free
+    /// nucleotides do not repel each other at rest.
+    const FREE_NUCL_RADIUS: f32 = 0.3;
+    /// The largest displacement of an element during one step. Steps in which an element moves
+    /// further are rejected.
+    const MAX_STEP_DISPLACEMENT: f32 = 1.;
+    /// The smallest value that `max_time_step` can take
+    const MIN_TIME_STEP: f32 = 1e-6;
+
+    /// Adapt `max_time_step` and the step of the integrator to the displacements of the elements
+    /// since `previous_state`.
+    ///
+    /// If the new state is not finite, or if an element moved by more than
+    /// `MAX_STEP_DISPLACEMENT`, the step is rejected and both time steps are halved. If every
+    /// element moved by less than a quarter of that distance, they are doubled.
+    fn adapt_time_step(&mut self, previous_state: Vector<f32>) {
+        let new_state = if let Some(state) = self.last_state.as_ref() {
+            state
+        } else {
+            return;
+        };
+        let finite = new_state.iter().all(|x| x.is_finite());
+        let (old_positions, _, _, _) = self.read_state(&previous_state);
+        let (new_positions, _, _, _) = self.read_state(new_state);
+        let max_displacement = old_positions
+            .iter()
+            .zip(new_positions.iter())
+            .map(|(old, new)| (*new - *old).mag())
+            .fold(0f32, f32::max);
+        if !finite
+            || (max_displacement > Self::MAX_STEP_DISPLACEMENT
+                && self.max_time_step > Self::MIN_TIME_STEP)
+        {
+            self.last_state = Some(previous_state);
+            self.next_time = self.current_time;
+            self.max_time_step = (self.max_time_step / 2.).max(Self::MIN_TIME_STEP);
+            self.time_step = (self.time_step / 2.).max(Self::MIN_TIME_STEP);
+        } else if max_displacement < Self::MAX_STEP_DISPLACEMENT / 4. {
+            self.max_time_step = (2. * self.max_time_step).min(self.time_step_limit);
+            self.time_step = (2. * self.time_step).min(self.rigid_parameters.dt);
+        }
+    }
+
+    /// The step used by the integrator for the next step of the simulation
+    fn integrator_time_step(&self) -> f32 {
+        self.time_step.min(self.max_time_step)
+    }
+
     fn read_state(&self, x: &Vector<f32>) -> (Vec<Vec3>, Vec<Rotor3>, Vec<Vec3>, Vec<Vec3>) {
         let mut positions = Vec::with_capacity(self.helices.len() + self.free_nucls.len());
         let mut rotations = Vec::with_capacity(self.helices.len() + self.free_nucls.len());
@@ -280,6 +501,62 @@ impl HelixSystem {
         (positions, rotations, linear_momentums, angular_momentums)
     }
 
+    /// The total kinetic energy of the system in state `x`
+    fn kinetic_energy(&self, x: &Vector<f32>) -> f32 {
+        let (_, _, linear_momentums, angular_momentums) = self.read_state(x);
+        let mut ret = 0.;
+        for i in 0..linear_momentums.len() {
+            if i < self.helices.len() {
+                let mass = self.helices[i].height() * self.rigid_parameters.mass;
+                let omega = self.helices[i].inertia_inverse * angular_momentums[i]
+                    / self.rigid_parameters.mass;
+                ret += linear_momentums[i].mag_sq() / (2. * mass)
+                    + angular_momentums[i].dot(omega) / 2.;
+            } else {
+                let mass = self.rigid_parameters.mass / 2.;
+                ret += linear_momentums[i].mag_sq() / (2. * mass);
+            }
+        }
+        ret
+    }
+
+    /// The elastic energy stored in the springs and anchors of the system, consistent with the
+    /// forces applied on the positions of the elements by `forces_and_torques`.
+    fn elastic_energy(&self, positions: &[Vec3], orientations: &[Rotor3]) -> f32 {
+        let k_spring = self.rigid_parameters.spring_stiffness();
+        let k_anchor = self.rigid_parameters.anchor_stiffness();
+        let l0 = self.rigid_parameters.l0;
+        let point_conversion = |nucl: &RigidNucl| {
+            let position = positions[nucl.helix]
+                + self.helices[nucl.helix]
+                    .center_to_origin
+                    .rotated_by(orientations[nucl.helix]);
+            let mut helix = Helix::new(position, orientations[nucl.helix]);
+            helix.roll(self.helices[nucl.helix].roll);
+            helix.space_pos(&self.parameters, nucl.position, nucl.forward)
+        };
+        let free_nucl_pos = |n: &usize| positions[*n + self.helices.len()];
+        let spring_energy = |a: Vec3, b: Vec3| k_spring * ((b - a).mag() - l0).powi(2) / 2.;
+
+        let mut ret = 0.;
+        for (nucl_0, nucl_1) in self.springs.iter() {
+            ret += spring_energy(point_conversion(nucl_0), point_conversion(nucl_1));
+        }
+        for (nucl, free_nucl_id) in self.mixed_springs.iter() {
+            ret += spring_energy(point_conversion(nucl), free_nucl_pos(free_nucl_id));
+        }
+        for (id_0, id_1) in self.free_springs.iter() {
+            ret += spring_energy(free_nucl_pos(id_0), free_nucl_pos(id_1));
+        }
+        for (nucl, position) in self.anchors.iter() {
+            ret += k_anchor * (point_conversion(nucl) - *position).mag_sq() / 2.;
+        }
+        for (id, position) in self.free_anchors.iter() {
+            ret += k_anchor * (free_nucl_pos(id) - *position).mag_sq() / 2.;
+        }
+        ret
+    }
+
     fn next_time(&mut self) {
         self.current_time = self.next_time;
         if let Some((t, _)) = self.brownian_heap.peek() {
@@ -298,7 +575,7 @@ impl HelixSystem {
     }
 
     fn brownian_jump(&mut self) {
-        let mut rnd = rand::thread_rng();
+        let rnd = &mut self.rng;
         if let Some((t, _)) = self.brownian_heap.peek() {
             // t.0 because t is a &Reverse<_>
             if self.next_time < t.0.into_inner() {
@@ -322,10 +599,53 @@ impl HelixSystem {
         }
     }
 
-    fn update_parameters(&mut self, parameters: RigidBodyConstants) {
+    fn element_mass(&self, i: usize) -> f32 {
+        if i < self.helices.len() {
+            self.helices[i].height() * self.rigid_parameters.mass
+        } else {
+            self.rigid_parameters.mass / 2.
+        }
+    }
+
+    /// Subtract the mass-weighted average linear momentum from every element of the system.
+    fn remove_net_momentum(&mut self) {
+        let nb_element = self.helices.len() + self.free_nucls.len();
+        let masses: Vec<f32> = (0..nb_element).map(|i| self.element_mass(i)).collect();
+        let total_mass: f32 = masses.iter().sum();
+        if let Some(state) = self.last_state.as_mut() {
+            if total_mass <= 0. {
+                return;
+            }
+            let mut momentum = Vec3::zero();
+            for i in 0..nb_element {
+                let entry = 13 * i + 7;
+                momentum += Vec3::new(
+                    *state.get(entry),
+                    *state.get(entry + 1),
+                    *state.get(entry + 2),
+                );
+            }
+            let velocity = momentum / total_mass;
+            for (i, mass) in masses.iter().enumerate() {
+                let entry = 13 * i + 7;
+                *state.get_mut(entry) -= mass * velocity.x;
+                *state.get_mut(entry + 1) -= mass * velocity.y;
+                *state.get_mut(entry + 2) -= mass * velocity.z;
+            }
+        }
+    }
+
+    fn update_parameters(&mut self, mut parameters: RigidBodyConstants) {
+        parameters.apply_temperature();
+        if parameters.seed.is_some() && parameters.seed != self.rigid_parameters.seed {
+            self.rng = make_rng(parameters.seed);
+        }
+        if parameters.dt != self.rigid_parameters.dt {
+            self.time_step = parameters.dt;
+        }
         self.rigid_parameters = parameters;
         self.brownian_heap.clear();
-        let mut rnd = rand::thread_rng();
+        let rnd = &mut self.rng;
         let exp_law = Exp::new(self.rigid_parameters.brownian_rate).unwrap();
         for i in 0..self.free_nucls.len() {
             if !self.free_anchors.iter().any(|(x, _)| *x == i) {
@@ -336,10 +656,15 @@ impl HelixSystem {
     }
 
     fn shake_nucl(&mut self, nucl: ShakeTarget) {
-        let mut rnd = rand::thread_rng();
+        let rnd = &mut self.rng;
         let gx: f32 = rnd.sample(StandardNormal);
         let gy: f32 = rnd.sample(StandardNormal);
         let gz: f32 = rnd.sample(StandardNormal);
+        if let ShakeTarget::Helix(h_id) = nucl {
+            if self.fixed_helices.contains(&h_id) {
+                return;
+            }
+        }
         let entry = match nucl {
             ShakeTarget::Helix(h_id) => 13 * h_id,
             ShakeTarget::FreeNucl(n) => 13 * (self.helices.len() + n),
@@ -370,6 +695,16 @@ impl HelixSystem {
             }
         }
     }
+
+    /// Shake every helix that is not fixed and every free nucleotide of the system.
+    fn shake_all(&mut self) {
+        for h_id in 0..self.helices.len() {
+            self.shake_nucl(ShakeTarget::Helix(h_id))
+        }
+        for n in 0..self.free_nucls.len() {
+            self.shake_nucl(ShakeTarget::FreeNucl(n))
+        }
+    }
 }
 
 impl ExplicitODE<f32> for HelixSystem {
@@ -386,7 +721,9 @@ impl ExplicitODE<f32> for HelixSystem {
         let nb_element = self.helices.len() + self.free_nucls.len();
         let mut ret = Vec::with_capacity(13 * nb_element);
         for i in 0..nb_element {
-            if i < self.helices.len() {
+            if self.fixed_helices.contains(&i) {
+                ret.extend_from_slice(&[0.; 13]);
+            } else if i < self.helices.len() {
                 let d_position =
                     linear_momentums[i] / (self.helices[i].height() * self.rigid_parameters.mass);
                 ret.push(d_position.x);
@@ -516,6 +853,10 @@ struct GridsSystem {
     last_state: Option<Vector<f32>>,
     #[allow(dead_code)]
     anchors: Vec<(ApplicationPoint, Vec3)>,
+    l0: f32,
+    solver: SolverKind,
+    dt: f32,
+    convergence_threshold: Option<f32>,
 }
 
 impl GridsSystem {
@@ -528,7 +869,6 @@ impl GridsSystem {
         let mut forces = vec![Vec3::zero(); self.grids.len()];
         let mut torques = vec![Vec3::zero(); self.grids.len()];
 
-        const L0: f32 = 0.7;
         const K_SPRING: f32 = 1.;
 
         let point_conversion = |application_point: &ApplicationPoint| {
@@ -543,7 +883,7 @@ impl GridsSystem {
             let point_1 = point_conversion(&spring.1);
             let len = (point_1 - point_0).mag();
             //println!("len {}", len);
-            let norm = len - L0;
+            let norm = len - self.l0;
 
             // The force applied on point 0
             let force = K_SPRING * norm * (point_1 - point_0) / len;
@@ -673,6 +1013,18 @@ impl ExplicitODE<f32> for GridsSystem {
 }
 
 impl GridsSystem {
+    /// The total kinetic energy of the system in state `x`
+    fn kinetic_energy(&self, x: &Vector<f32>) -> f32 {
+        let (_, _, linear_momentums, angular_momentums) = self.read_state(x);
+        let mut ret = 0.;
+        for i in 0..self.grids.len() {
+            let omega = self.grids[i].inertia_inverse * angular_momentums[i];
+            ret += linear_momentums[i].mag_sq() / (2. * self.grids[i].mass)
+                + angular_momentums[i].dot(omega) / 2.;
+        }
+        ret
+    }
+
     fn read_state(&self, x: &Vector<f32>) -> (Vec<Vec3>, Vec<Rotor3>, Vec<Vec3>, Vec<Vec3>) {
         let mut positions = Vec::with_capacity(self.grids.len());
         let mut rotations = Vec::with_capacity(self.grids.len());
@@ -851,7 +1203,7 @@ fn center_of_mass_helices(helices: &[RigidHelix]) -> Vec3 {
 fn inertia_point(point: Vec3) -> Mat3 {
     Mat3::new(
         Vec3::new(
-            point.y * point.y + point.z + point.z,
+            point.y * point.y + point.z * point.z,
             -point.x * point.y,
             -point.x * point.z,
         ),
@@ -886,6 +1238,9 @@ struct GridsSystemThread {
     /// When the wrapped option takes the value of some channel, the thread that performs the
     /// simulation sends the last computed state of the system
     sender: Arc<Mutex<Option<Sender<GridSystemState>>>>,
+    /// The number of consecutive steps during which the kinetic energy of the system stayed
+    /// below the convergence threshold
+    converged_steps: usize,
 }
 
 impl GridsSystemThread {
@@ -894,6 +1249,7 @@ impl GridsSystemThread {
             grid_system,
             stop: Default::default(),
             sender: Default::default(),
+            converged_steps: 0,
         }
     }
 
@@ -914,19 +1270,36 @@ impl GridsSystemThread {
                 if let Some(snd) = self.sender.lock().unwrap().take() {
                     snd.send(self.get_state()).unwrap();
                 }
-                let solver = Kutta3::new(1e-4f32);
-                if let Ok((_, y)) = solver.solve(&self.grid_system) {
-                    self.grid_system.last_state = y.last().cloned();
+                if self.step() {
+                    break;
                 }
             }
+            answer_last_request(&self.stop, &self.sender, || self.get_state());
             *computing.lock().unwrap() = false;
         });
         (stop, sender)
     }
 
+    /// Perform one step of the simulation and return true if the system has converged.
+    fn step(&mut self) -> bool {
+        let solver = self.grid_system.solver;
+        if let Ok(state) = solver.last_state(self.grid_system.dt, &self.grid_system) {
+            self.grid_system.last_state = state;
+        }
+        let energy = self
+            .grid_system
+            .kinetic_energy(&self.grid_system.init_cond());
+        has_converged(
+            energy,
+            self.grid_system.convergence_threshold,
+            &mut self.converged_steps,
+        )
+    }
+
     fn get_state(&self) -> GridSystemState {
         let state = self.grid_system.init_cond();
         let (positions, orientations, _, _) = self.grid_system.read_state(&state);
+        let energy = self.grid_system.kinetic_energy(&state);
         let ids = self.grid_system.grids.iter().map(|g| g.id).collect();
         let center_of_mass_from_grid = self
             .grid_system
@@ -939,10 +1312,22 @@ impl GridsSystemThread {
             orientations,
             center_of_mass_from_grid,
             ids,
+            energy,
         }
     }
 }
 
+/// A snapshot of a running helices simulation, sent to the subscribers of its trajectory
+#[derive(Debug, Clone)]
+pub struct TrajectoryFrame {
+    /// The time of the simulation
+    pub time: f32,
+    /// The position of the center of mass of each element of the system
+    pub positions: Vec<Vec3>,
+    /// The sum of the kinetic and elastic energies of the system
+    pub total_energy: f32,
+}
+
 struct HelixSystemThread {
     helix_system: HelixSystem,
     /// When the wrapped boolean is set to true, stop the simulation perfomed by self.
@@ -952,17 +1337,33 @@ struct HelixSystemThread {
     sender: Arc<Mutex<Option<Sender<RigidHelixState>>>>,
     /// A nucleotide to be shaken
     nucl_shake: Arc<Mutex<Option<ShakeTarget>>>,
+    /// When the wrapped boolean is set to true, all the elements of the system are shaken
+    shake_all: Arc<Mutex<bool>>,
     parameters_update: Arc<Mutex<Option<RigidBodyConstants>>>,
+    /// The number of consecutive steps during which the kinetic energy of the system stayed
+    /// below the convergence threshold
+    converged_steps: usize,
+    /// If some, a `TrajectoryFrame` is sent through the wrapped channel every
+    /// `TRAJECTORY_FRAME_PERIOD` steps
+    trajectory: Arc<Mutex<Option<Sender<TrajectoryFrame>>>>,
+    /// The number of steps performed since the beginning of the simulation
+    nb_steps: usize,
 }
 
 impl HelixSystemThread {
+    const TRAJECTORY_FRAME_PERIOD: usize = 10;
+
     fn new(helix_system: HelixSystem) -> Self {
         Self {
             helix_system,
             stop: Default::default(),
             sender: Default::default(),
             nucl_shake: Default::default(),
+            shake_all: Default::default(),
             parameters_update: Default::default(),
+            converged_steps: 0,
+            trajectory: Default::default(),
+            nb_steps: 0,
         }
     }
 
@@ -986,23 +1387,58 @@ impl HelixSystemThread {
                 if let Some(snd) = self.sender.lock().unwrap().take() {
                     snd.send(self.get_state()).unwrap();
                 }
-                self.helix_system.next_time();
-                let solver = ExplicitEuler::new(1e-4f32);
-                if self.helix_system.rigid_parameters.brownian_motion {
-                    self.helix_system.brownian_jump();
-                }
-                if let Some(nucl) = self.nucl_shake.lock().unwrap().take() {
-                    self.helix_system.shake_nucl(nucl)
+                if self.step() {
+                    break;
                 }
-                if let Ok((_, y)) = solver.solve(&self.helix_system) {
-                    self.helix_system.last_state = y.last().cloned();
+                self.nb_steps += 1;
+                if self.nb_steps % Self::TRAJECTORY_FRAME_PERIOD == 0 {
+                    self.send_trajectory_frame();
                 }
             }
+            answer_last_request(&self.stop, &self.sender, || self.get_state());
+            // Close the trajectory channel
+            self.trajectory.lock().unwrap().take();
             *computing.lock().unwrap() = false;
         });
         (stop, sender)
     }
 
+    /// Perform one step of the simulation and return true if the system has converged.
+    fn step(&mut self) -> bool {
+        self.helix_system.next_time();
+        let solver = self
+            .helix_system
+            .rigid_parameters
+            .solver
+            .unwrap_or(SolverKind::ExplicitEuler);
+        let dt = self.helix_system.integrator_time_step();
+        if self.helix_system.rigid_parameters.brownian_motion {
+            self.helix_system.brownian_jump();
+        }
+        if let Some(nucl) = self.nucl_shake.lock().unwrap().take() {
+            self.helix_system.shake_nucl(nucl)
+        }
+        if std::mem::take(&mut *self.shake_all.lock().unwrap()) {
+            self.helix_system.shake_all()
+        }
+        if self.helix_system.rigid_parameters.remove_net_momentum {
+            self.helix_system.remove_net_momentum();
+        }
+        let previous_state = self.helix_system.init_cond();
+        if let Ok(state) = solver.last_state(dt, &self.helix_system) {
+            self.helix_system.last_state = state;
+        }
+        self.helix_system.adapt_time_step(previous_state);
+        let energy = self
+            .helix_system
+            .kinetic_energy(&self.helix_system.init_cond());
+        has_converged(
+            energy,
+            self.helix_system.rigid_parameters.convergence_threshold,
+            &mut self.converged_steps,
+        )
+    }
+
     fn get_param_ptr(&self) -> Arc<Mutex<Option<RigidBodyConstants>>> {
         self.parameters_update.clone()
     }
@@ -1011,9 +1447,38 @@ impl HelixSystemThread {
         self.nucl_shake.clone()
     }
 
+    fn get_shake_all_ptr(&self) -> Arc<Mutex<bool>> {
+        self.shake_all.clone()
+    }
+
+    fn get_trajectory_ptr(&self) -> Arc<Mutex<Option<Sender<TrajectoryFrame>>>> {
+        self.trajectory.clone()
+    }
+
+    /// Send the current state of the simulation to the subscriber of the trajectory, if there is
+    /// one. The subscriber is forgotten once it has hung up.
+    fn send_trajectory_frame(&self) {
+        let mut trajectory = self.trajectory.lock().unwrap();
+        if let Some(snd) = trajectory.as_ref() {
+            let state = self.helix_system.init_cond();
+            let (positions, orientations, _, _) = self.helix_system.read_state(&state);
+            let total_energy = self.helix_system.kinetic_energy(&state)
+                + self.helix_system.elastic_energy(&positions, &orientations);
+            let frame = TrajectoryFrame {
+                time: self.helix_system.current_time,
+                positions,
+                total_energy,
+            };
+            if snd.send(frame).is_err() {
+                *trajectory = None;
+            }
+        }
+    }
+
     fn get_state(&self) -> RigidHelixState {
         let state = self.helix_system.init_cond();
         let (positions, orientations, _, _) = self.helix_system.read_state(&state);
+        let energy = self.helix_system.kinetic_energy(&state);
         let ids = self.helix_system.helices.iter().map(|g| g.id).collect();
         let center_of_mass_from_helix = self
             .helix_system
@@ -1021,11 +1486,22 @@ impl HelixSystemThread {
             .iter()
             .map(|h| h.center_to_origin)
             .collect();
+        let forces = if self.helix_system.rigid_parameters.show_forces {
+            Some(
+                self.helix_system
+                    .forces_and_torques(&positions, &orientations)
+                    .0,
+            )
+        } else {
+            None
+        };
         RigidHelixState {
             positions,
             orientations,
             center_of_mass_from_helix,
             ids,
+            forces,
+            energy,
         }
     }
 }
@@ -1036,19 +1512,25 @@ pub struct GridSystemState {
     orientations: Vec<Rotor3>,
     center_of_mass_from_grid: Vec<Vec3>,
     ids: Vec<usize>,
+    /// The kinetic energy of the system
+    energy: f32,
 }
 
 pub(super) struct RigidBodyPtr {
     stop: Arc<Mutex<bool>>,
     state: Arc<Mutex<Option<Sender<GridSystemState>>>>,
     instant: Instant,
+    refresh_interval: Duration,
 }
 
 pub(super) struct RigidHelixPtr {
     stop: Arc<Mutex<bool>>,
     state: Arc<Mutex<Option<Sender<RigidHelixState>>>>,
     shake_nucl: Arc<Mutex<Option<ShakeTarget>>>,
+    shake_all: Arc<Mutex<bool>>,
+    trajectory: Arc<Mutex<Option<Sender<TrajectoryFrame>>>>,
     instant: Instant,
+    refresh_interval: Duration,
 }
 
 #[derive(Debug, Clone)]
@@ -1057,6 +1539,10 @@ pub struct RigidHelixState {
     orientations: Vec<Rotor3>,
     center_of_mass_from_helix: Vec<Vec3>,
     ids: Vec<usize>,
+    /// The forces applied on each element, if they were requested
+    forces: Option<Vec<Vec3>>,
+    /// The kinetic energy of the system
+    energy: f32,
 }
 
 pub(super) struct RigidHelixSimulator {
@@ -1069,6 +1555,9 @@ pub(super) struct RigidHelixSimulator {
     parameters: Parameters,
     rigid_parameters: Arc<Mutex<Option<RigidBodyConstants>>>,
     initial_state: RigidHelixState,
+    /// The position of each element of the system and the force applied on it, in the last
+    /// state that was read
+    forces: Vec<(Vec3, Vec3)>,
 }
 
 impl RigidHelixSimulator {
@@ -1079,9 +1568,12 @@ impl RigidHelixSimulator {
     ) -> Self {
         let roll = helix_system.helices.iter().map(|h| h.roll).collect();
         let parameters = helix_system.parameters.clone();
+        let refresh_interval = helix_system.rigid_parameters.refresh_interval;
         let helix_system_thread = HelixSystemThread::new(helix_system);
         let rigid_parameters = helix_system_thread.get_param_ptr();
         let shake_nucl = helix_system_thread.get_nucl_ptr();
+        let shake_all = helix_system_thread.get_shake_all_ptr();
+        let trajectory = helix_system_thread.get_trajectory_ptr();
 
         let date = Instant::now();
         let initial_state = helix_system_thread.get_state();
@@ -1090,7 +1582,10 @@ impl RigidHelixSimulator {
             instant: date,
             stop,
             shake_nucl,
+            shake_all,
+            trajectory,
             state: snd,
+            refresh_interval,
         };
         Self {
             roll,
@@ -1102,6 +1597,7 @@ impl RigidHelixSimulator {
             state_update: None,
             rigid_parameters,
             initial_state,
+            forces: Vec::new(),
         }
     }
 
@@ -1109,6 +1605,14 @@ impl RigidHelixSimulator {
         *self.rigid_parameters.lock().unwrap() = Some(rigid_parameters);
     }
 
+    /// Return a channel on which frames of the trajectory of the simulation are sent. This
+    /// replaces any previous subscription.
+    pub(super) fn subscribe_trajectory(&self) -> Receiver<TrajectoryFrame> {
+        let (snd, rcv) = std::sync::mpsc::channel();
+        *self.simulation_ptr.trajectory.lock().unwrap() = Some(snd);
+        rcv
+    }
+
     pub(super) fn shake_nucl(&mut self, nucl: Nucl) {
         if let Some(free_nucl) = self.nucl_maps.get(&nucl) {
             let shake_target = if let Some(helix) = free_nucl.helix {
@@ -1122,11 +1626,17 @@ impl RigidHelixSimulator {
         }
     }
 
+    /// Shake all the helices and free nucleotides of the system at the next step of the
+    /// simulation.
+    pub(super) fn shake_all(&mut self) {
+        *self.simulation_ptr.shake_all.lock().unwrap() = true;
+    }
+
     fn check_simulation(&mut self) {
         let now = Instant::now();
-        if (now - self.simulation_ptr.instant).as_millis() > 30 {
+        if now - self.simulation_ptr.instant > self.simulation_ptr.refresh_interval {
             let (snd, rcv) = std::sync::mpsc::channel();
-            *self.simulation_ptr.state.lock().unwrap() = Some(snd);
+            request_state(&self.simulation_ptr.stop, &self.simulation_ptr.state, snd);
             self.state_update = rcv.recv().ok();
             /*
             for i in 0..state.ids.len() {
@@ -1151,6 +1661,18 @@ impl RigidHelixSimulator {
         space_position: &mut HashMap<u32, [f32; 3], RandomState>,
     ) -> bool {
         if let Some(state) = self.state_update.take() {
+            self.forces = state
+                .forces
+                .as_ref()
+                .map(|forces| {
+                    state
+                        .positions
+                        .iter()
+                        .cloned()
+                        .zip(forces.iter().cloned())
+                        .collect()
+                })
+                .unwrap_or_default();
             let helices: Vec<Helix> = (0..self.nb_helices)
                 .map(|n| {
                     let orientation = state.orientations[n].normalized();
@@ -1200,6 +1722,7 @@ impl Data {
                 self.grid_manager.update(&mut self.design);
                 self.hash_maps_update = true;
                 self.update_status = true;
+                self.modified = true;
             } else {
                 println!("error while solving");
             }
@@ -1226,6 +1749,7 @@ impl Data {
                 }
                 self.hash_maps_update = true;
                 self.update_status = true;
+                self.modified = true;
             } else {
                 println!("error while solving");
             }
@@ -1238,8 +1762,9 @@ impl Data {
         &self,
         time_span: (f32, f32),
         interval_results: &IntervalResult,
-        rigid_parameters: RigidBodyConstants,
+        mut rigid_parameters: RigidBodyConstants,
     ) -> Option<HelixSystem> {
+        rigid_parameters.apply_temperature();
         let parameters = self.design.parameters.unwrap_or_default();
         let mut rigid_helices = Vec::with_capacity(interval_results.helix_map.len());
         for i in 0..interval_results.helix_map.len() {
@@ -1317,7 +1842,14 @@ impl Data {
                 }
             }
         }
-        let mut rnd = rand::thread_rng();
+        let fixed_helices = interval_results
+            .helix_map
+            .iter()
+            .enumerate()
+            .filter(|(_, h_id)| self.pinned_helices.contains(h_id))
+            .map(|(i, _)| i)
+            .collect();
+        let mut rnd = make_rng(rigid_parameters.seed);
         let mut brownian_heap = BinaryHeap::new();
         let exp_law = Exp::new(rigid_parameters.brownian_rate).unwrap();
         for i in 0..interval_results.free_nucls.len() {
@@ -1338,18 +1870,22 @@ impl Data {
             parameters,
             anchors,
             free_anchors,
+            fixed_helices,
             brownian_heap,
             current_time: 0.,
             next_time: 0.,
+            time_step: rigid_parameters.dt,
             rigid_parameters,
             max_time_step: time_span.1,
+            time_step_limit: time_span.1,
+            rng: rnd,
         })
     }
 
     fn make_grid_system(
         &self,
         time_span: (f32, f32),
-        _paramaters: RigidBodyConstants,
+        rigid_parameters: RigidBodyConstants,
     ) -> Option<GridsSystem> {
         let intervals = self.design.get_intervals();
         let parameters = self.design.parameters.unwrap_or_default();
@@ -1403,6 +1939,10 @@ impl Data {
             time_span,
             last_state: None,
             anchors: vec![],
+            l0: rigid_parameters.l0,
+            solver: rigid_parameters.solver.unwrap_or(SolverKind::Kutta3),
+            dt: rigid_parameters.dt,
+            convergence_threshold: rigid_parameters.convergence_threshold,
         })
     }
 
@@ -1490,12 +2030,13 @@ impl Data {
     pub(super) fn check_rigid_body(&mut self) {
         if let Some(ptrs) = self.rigid_body_ptr.as_mut() {
             let now = Instant::now();
-            if (now - ptrs.instant).as_millis() > 30 {
+            if now - ptrs.instant > ptrs.refresh_interval {
                 let (snd, rcv) = std::sync::mpsc::channel();
-                *ptrs.state.lock().unwrap() = Some(snd);
-                let state = rcv.recv().unwrap();
+                request_state(&ptrs.stop, &ptrs.state, snd);
                 ptrs.instant = now;
-                self.read_grid_system_state(state);
+                if let Ok(state) = rcv.recv() {
+                    self.read_grid_system_state(state);
+                }
             }
         }
     }
@@ -1514,17 +2055,19 @@ impl Data {
         self.update_status = true;
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     pub(super) fn check_rigid_helices(&mut self) {
         if let Some(ptrs) = self.helix_simulation_ptr.as_mut() {
             let now = Instant::now();
-            if (now - ptrs.instant).as_millis() > 30 {
+            if now - ptrs.instant > ptrs.refresh_interval {
                 let (snd, rcv) = std::sync::mpsc::channel();
-                *ptrs.state.lock().unwrap() = Some(snd);
-                let state = rcv.recv().unwrap();
+                request_state(&ptrs.stop, &ptrs.state, snd);
                 ptrs.instant = now;
-                self.read_rigid_helix_state(state);
+                if let Ok(state) = rcv.recv() {
+                    self.read_rigid_helix_state(state);
+                }
             }
         }
     }
@@ -1545,6 +2088,7 @@ impl Data {
         self.update_status = true;
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     pub(super) fn read_rigid_helix_update(&mut self) -> bool {
@@ -1556,6 +2100,15 @@ impl Data {
         }
     }
 
+    /// Return the position of each element of the simulated system together with the force that
+    /// is applied on it. The returned vector is empty if the forces were not requested.
+    pub fn get_simulation_forces(&self) -> Vec<(Vec3, Vec3)> {
+        self.rigid_helix_simulator
+            .as_ref()
+            .map(|s| s.forces.clone())
+            .unwrap_or_default()
+    }
+
     pub fn undo_grid_simulation(&mut self, initial_state: GridSystemState) {
         self.stop_rigid_body();
         self.read_grid_system_state(initial_state);
@@ -1608,6 +2161,7 @@ impl Data {
         computing: Arc<Mutex<bool>>,
         parameters: RigidBodyConstants,
     ) -> Option<GridSystemState> {
+        let refresh_interval = parameters.refresh_interval;
         if let Some(grid_system) = self.make_grid_system(request, parameters) {
             let grid_system_thread = GridsSystemThread::new(grid_system);
             let date = Instant::now();
@@ -1617,6 +2171,7 @@ impl Data {
                 instant: date,
                 stop,
                 state: snd,
+                refresh_interval,
             });
             Some(initial_state)
         } else {
@@ -1986,3 +2541,464 @@ fn distance_segment(a: Vec3, b: Vec3, c: Vec3, d: Vec3) -> (f32, Vec3, Vec3, Vec
         (min_dist, min_vec, min_point_a, min_point_c)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_free_nucls_system(l0: f32) -> HelixSystem {
+        let free_nucl = |position| FreeNucl {
+            helix: None,
+            position,
+            forward: true,
+            old_helix: Some(0),
+        };
+        HelixSystem {
+            springs: vec![],
+            free_springs: vec![(0, 1)],
+            mixed_springs: vec![],
+            free_nucls: vec![free_nucl(0), free_nucl(1)],
+            free_nucl_position: vec![Vec3::zero(), Vec3::unit_x()],
+            helices: vec![],
+            time_span: (0., 1.),
+            last_state: None,
+            parameters: Parameters::default(),
+            anchors: vec![],
+            free_anchors: vec![],
+            fixed_helices: vec![],
+            current_time: 0.,
+            next_time: 0.,
+            brownian_heap: BinaryHeap::new(),
+            rigid_parameters: RigidBodyConstants {
+                k_spring: 1.,
+                k_friction: 3.,
+                mass: 1.,
+                volume_exclusion: false,
+                brownian_motion: false,
+                brownian_rate: 1.,
+                brownian_amplitude: 0.,
+                l0,
+                remove_net_momentum: false,
+                show_forces: false,
+                solver: None,
+                dt: RigidBodyConstants::DEFAULT_DT,
+                seed: None,
+                convergence_threshold: None,
+                temperature_kelvin: None,
+                refresh_interval: RigidBodyConstants::DEFAULT_REFRESH_INTERVAL,
+            },
+            max_time_step: 1.,
+            time_step_limit: 1.,
+            time_step: RigidBodyConstants::DEFAULT_DT,
+            rng: make_rng(None),
+        }
+    }
+
+    fn settled_separation(mut system: HelixSystem) -> f32 {
+        let solver = ExplicitEuler::new(1e-3f32);
+        for _ in 0..10 {
+            let (_, y) = solver.solve(&system).unwrap();
+            system.last_state = y.last().cloned();
+        }
+        let (positions, _, _, _) = system.read_state(&system.init_cond());
+        (positions[1] - positions[0]).mag()
+    }
+
+    #[test]
+    fn larger_rest_length_gives_larger_separation() {
+        let short = settled_separation(two_free_nucls_system(RigidBodyConstants::DEFAULT_L0));
+        let long = settled_separation(two_free_nucls_system(2.));
+        assert!((short - RigidBodyConstants::DEFAULT_L0).abs() < 1e-2);
+        assert!((long - 2.).abs() < 1e-2);
+        assert!(long > short);
+    }
+
+    #[test]
+    fn removing_net_momentum_keeps_center_of_mass() {
+        let mut system = two_free_nucls_system(1.);
+        system.rigid_parameters.remove_net_momentum = true;
+        let mut state = system.init_cond();
+        // Give the same velocity to every element, as a uniform force would do.
+        for i in 0..2 {
+            *state.get_mut(13 * i + 7) = 1.;
+            *state.get_mut(13 * i + 9) = -2.;
+        }
+        system.last_state = Some(state);
+        let center_of_mass = |system: &HelixSystem| {
+            let (positions, _, _, _) = system.read_state(&system.init_cond());
+            (positions[0] + positions[1]) / 2.
+        };
+        let initial = center_of_mass(&system);
+        let solver = ExplicitEuler::new(1e-3f32);
+        for _ in 0..5 {
+            system.remove_net_momentum();
+            let (_, y) = solver.solve(&system).unwrap();
+            system.last_state = y.last().cloned();
+        }
+        assert!((center_of_mass(&system) - initial).mag() < 1e-4);
+    }
+
+    #[test]
+    fn every_solver_advances_the_system() {
+        for solver in [
+            SolverKind::ExplicitEuler,
+            SolverKind::Heun,
+            SolverKind::Kutta3,
+        ]
+        .iter()
+        {
+            let mut system = two_free_nucls_system(2.);
+            system.rigid_parameters.solver = Some(*solver);
+            let (initial_positions, _, _, _) = system.read_state(&system.init_cond());
+            for _ in 0..5 {
+                system.last_state = solver.last_state(1e-3, &system).unwrap();
+            }
+            let (positions, _, _, _) = system.read_state(&system.init_cond());
+            assert!(
+                positions
+                    .iter()
+                    .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite()),
+                "{:?} produced non finite values",
+                solver
+            );
+            assert!(
+                (positions[1] - positions[0]).mag()
+                    > (initial_positions[1] - initial_positions[0]).mag()
+            );
+        }
+    }
+
+    #[test]
+    fn inertia_of_a_point() {
+        let inertia = inertia_point(Vec3::new(1., 2., 3.));
+        let expected = Mat3::new(
+            Vec3::new(13., -2., -3.),
+            Vec3::new(-2., 10., -6.),
+            Vec3::new(-3., -6., 5.),
+        );
+        assert_eq!(inertia, expected);
+    }
+
+    fn seeded_brownian_trajectory(seed: u64) -> Vec<Vec3> {
+        let mut system = two_free_nucls_system(RigidBodyConstants::DEFAULT_L0);
+        let mut parameters = system.rigid_parameters.clone();
+        parameters.brownian_motion = true;
+        parameters.brownian_rate = 10.;
+        parameters.brownian_amplitude = 1.;
+        parameters.seed = Some(seed);
+        system.update_parameters(parameters);
+        system.max_time_step = 0.1;
+        let solver = ExplicitEuler::new(1e-3f32);
+        for _ in 0..20 {
+            system.next_time();
+            system.brownian_jump();
+            let (_, y) = solver.solve(&system).unwrap();
+            system.last_state = y.last().cloned();
+        }
+        HelixSystemThread::new(system).get_state().positions
+    }
+
+    #[test]
+    fn seeded_simulations_are_reproducible() {
+        let first = seeded_brownian_trajectory(42);
+        let second = seeded_brownian_trajectory(42);
+        assert_eq!(first, second);
+        assert_ne!(first, vec![Vec3::zero(), Vec3::unit_x()]);
+    }
+
+    /// Return the standard deviation of the displacements of the free nucleotides of a system at
+    /// temperature `temperature_kelvin` after many brownian jumps.
+    fn brownian_jumps_deviation(temperature_kelvin: f32) -> f32 {
+        const NB_JUMPS: usize = 2000;
+        let mut system = two_free_nucls_system(RigidBodyConstants::DEFAULT_L0);
+        let mut parameters = system.rigid_parameters.clone();
+        parameters.brownian_motion = true;
+        parameters.brownian_amplitude = 1.;
+        parameters.temperature_kelvin = Some(temperature_kelvin);
+        parameters.seed = Some(7);
+        system.update_parameters(parameters);
+        system.last_state = Some(system.init_cond());
+        let initial = system.init_cond();
+        system.next_time = f32::INFINITY;
+        for _ in 0..NB_JUMPS {
+            system.brownian_jump();
+        }
+        let state = system.init_cond();
+        let squared_displacement: f32 = (0..2)
+            .flat_map(|i| 13 * i..13 * i + 3)
+            .map(|entry| (state.get(entry) - initial.get(entry)).powi(2))
+            .sum();
+        (squared_displacement / (3 * NB_JUMPS) as f32).sqrt()
+    }
+
+    #[test]
+    fn hotter_systems_jump_further() {
+        let cold = brownian_jumps_deviation(100.);
+        let warm = brownian_jumps_deviation(300.);
+        let hot = brownian_jumps_deviation(1000.5);
+        assert!(cold < warm && warm < hot);
+        let expected = two_free_nucls_system(1.)
+            .rigid_parameters
+            .brownian_amplitude_at(300.);
+        assert!((warm - expected).abs() < 0.1 * expected);
+        assert_eq!(brownian_jumps_deviation(-10.), 0.);
+    }
+
+    /// A system made of a single helix at rest, anchored by one of its nucleotides.
+    fn anchored_helix_system() -> HelixSystem {
+        let mut system = two_free_nucls_system(RigidBodyConstants::DEFAULT_L0);
+        system.free_springs.clear();
+        system.free_nucls.clear();
+        system.free_nucl_position.clear();
+        system.helices.push(RigidHelix::new_from_world(
+            0.,
+            0.,
+            0.,
+            Vec3::zero(),
+            10.,
+            0.,
+            Rotor3::identity(),
+            0,
+            (0, 10),
+        ));
+        let anchor = RigidNucl {
+            helix: 0,
+            position: 0,
+            forward: true,
+        };
+        let anchor_position =
+            Helix::new(Vec3::zero(), Rotor3::identity()).space_pos(&system.parameters, 0, true);
+        system.anchors.push((anchor, anchor_position));
+        system.max_time_step = 1e-2;
+        system.rigid_parameters.dt = 1e-3;
+        system.time_step = 1e-3;
+        system.rigid_parameters.convergence_threshold = Some(1e-6);
+        system
+    }
+
+    /// A system made of a helix and a free nucleotide pulled towards one of its nucleotides.
+    fn helix_pulled_by_free_nucl_system() -> HelixSystem {
+        let mut system = anchored_helix_system();
+        system.anchors.clear();
+        system.free_nucls.push(FreeNucl {
+            helix: None,
+            position: 0,
+            forward: true,
+            old_helix: Some(0),
+        });
+        system.free_nucl_position.push(Vec3::new(0., 3., 3.));
+        let helix_nucl = RigidNucl {
+            helix: 0,
+            position: 0,
+            forward: true,
+        };
+        system.mixed_springs.push((helix_nucl, 0));
+        system
+    }
+
+    #[test]
+    fn fixed_helix_does_not_move() {
+        let run = |mut system: HelixSystem| {
+            let initial = system.init_cond();
+            let solver = ExplicitEuler::new(1e-3f32);
+            for _ in 0..10 {
+                let (_, y) = solver.solve(&system).unwrap();
+                system.last_state = y.last().cloned();
+            }
+            (initial, system.init_cond())
+        };
+
+        let (initial, moved) = run(helix_pulled_by_free_nucl_system());
+        assert!((0..7).any(|i| (moved.get(i) - initial.get(i)).abs() > 1e-6));
+
+        let mut system = helix_pulled_by_free_nucl_system();
+        system.fixed_helices.push(0);
+        let (initial, fixed) = run(system);
+        for i in 0..7 {
+            assert_eq!(fixed.get(i), initial.get(i));
+        }
+        // The free nucleotide is still pulled towards the helix
+        assert!((13..16).any(|i| (fixed.get(i) - initial.get(i)).abs() > 1e-6));
+    }
+
+    #[test]
+    fn time_step_shrinks_under_large_forces() {
+        let mut system = helix_pulled_by_free_nucl_system();
+        system.rigid_parameters.k_spring = 1e8;
+        let initial_time_step = system.max_time_step;
+        let initial_dt = system.integrator_time_step();
+        let mut thread = HelixSystemThread::new(system);
+        for _ in 0..20 {
+            thread.step();
+        }
+        let system = &thread.helix_system;
+        assert!(system.max_time_step < initial_time_step);
+        // The integrator itself must use a smaller step, not only a shorter time span
+        assert!(system.integrator_time_step() < initial_dt);
+        assert!(system.integrator_time_step() < system.rigid_parameters.dt);
+        assert!(system.init_cond().iter().all(|x| x.is_finite()));
+        let (positions, _, _, _) = system.read_state(&system.init_cond());
+        assert!(positions
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite()));
+    }
+
+    #[test]
+    fn shake_all_moves_every_element() {
+        let mut system = helix_pulled_by_free_nucl_system();
+        system.rigid_parameters.brownian_amplitude = 1.;
+        system.last_state = Some(system.init_cond());
+        let (positions, _, _, _) = system.read_state(&system.init_cond());
+        system.shake_all();
+        let (shaken, _, _, _) = system.read_state(&system.init_cond());
+        assert_eq!(positions.len(), 2);
+        let moved = positions
+            .iter()
+            .zip(shaken.iter())
+            .filter(|(p, s)| (**p - **s).mag() > 1e-6)
+            .count();
+        assert_eq!(moved, 2);
+    }
+
+    #[test]
+    fn elastic_energy_is_consistent_with_forces() {
+        let mut system = two_free_nucls_system(RigidBodyConstants::DEFAULT_L0);
+        system.rigid_parameters.k_spring = 2.;
+        system.free_anchors = vec![(0, Vec3::new(0.1, 0., 0.))];
+        system.free_nucl_position[1] = Vec3::new(1.5, 0.2, 0.);
+        let (positions, rotations, _, _) = system.read_state(&system.init_cond());
+        let forces = system.forces_and_torques(&positions, &rotations).0;
+        let h = 1e-3;
+        for i in 0..2 {
+            let mut forward = positions.clone();
+            forward[i].x += h;
+            let mut backward = positions.clone();
+            backward[i].x -= h;
+            let derivative = (system.elastic_energy(&forward, &rotations)
+                - system.elastic_energy(&backward, &rotations))
+                / (2. * h);
+            assert!((forces[i].x + derivative).abs() < 1e-2 * forces[i].x.abs().max(1.));
+        }
+    }
+
+    #[test]
+    fn overlapping_free_nucls_repel_each_other() {
+        let mut system = two_free_nucls_system(RigidBodyConstants::DEFAULT_L0);
+        system.free_springs.clear();
+        system.free_nucl_position[1] = Vec3::new(0.2, 0., 0.);
+        let forces = |system: &HelixSystem| {
+            let (positions, rotations, _, _) = system.read_state(&system.init_cond());
+            system.forces_and_torques(&positions, &rotations).0
+        };
+        assert!(forces(&system).iter().all(|f| f.mag() == 0.));
+
+        system.rigid_parameters.volume_exclusion = true;
+        let repulsion = forces(&system);
+        assert!(repulsion[0].x < 0.);
+        assert!(repulsion[1].x > 0.);
+        assert!((repulsion[0] + repulsion[1]).mag() < 1e-6);
+
+        system.free_nucl_position[1] = Vec3::unit_x();
+        assert!(forces(&system).iter().all(|f| f.mag() == 0.));
+    }
+
+    #[test]
+    fn simulator_uses_the_requested_refresh_interval() {
+        let mut system = anchored_helix_system();
+        let refresh_interval = Duration::from_millis(5);
+        system.rigid_parameters.refresh_interval = refresh_interval;
+        let interval_results = IntervalResult {
+            nucl_map: HashMap::new(),
+            helix_map: vec![0],
+            free_nucls: vec![],
+            free_nucl_ids: HashMap::new(),
+            free_nucl_position: vec![],
+            intervals: vec![(0, 10)],
+        };
+        let computing = Arc::new(Mutex::new(false));
+        let simulator = RigidHelixSimulator::start_simulation(system, computing, interval_results);
+        assert_eq!(simulator.simulation_ptr.refresh_interval, refresh_interval);
+        *simulator.simulation_ptr.stop.lock().unwrap() = true;
+    }
+
+    #[test]
+    fn trajectory_frames_are_sent_to_subscriber() {
+        let mut system = anchored_helix_system();
+        system.rigid_parameters.convergence_threshold = None;
+        let interval_results = IntervalResult {
+            nucl_map: HashMap::new(),
+            helix_map: vec![0],
+            free_nucls: vec![],
+            free_nucl_ids: HashMap::new(),
+            free_nucl_position: vec![],
+            intervals: vec![(0, 10)],
+        };
+        let computing = Arc::new(Mutex::new(false));
+        let simulator = RigidHelixSimulator::start_simulation(system, computing, interval_results);
+        let trajectory = simulator.subscribe_trajectory();
+        let frame = trajectory
+            .recv_timeout(Duration::from_secs(10))
+            .expect("No frame received");
+        *simulator.simulation_ptr.stop.lock().unwrap() = true;
+        assert!(frame.total_energy.is_finite());
+        assert_eq!(frame.positions.len(), 1);
+        // The channel is closed once the simulation is over
+        while trajectory.recv_timeout(Duration::from_secs(10)).is_ok() {}
+    }
+
+    #[test]
+    fn kinetic_energy_of_free_nucls() {
+        let mut system = two_free_nucls_system(1.);
+        assert_eq!(system.kinetic_energy(&system.init_cond()), 0.);
+        let mut state = system.init_cond();
+        *state.get_mut(7) = 1.;
+        system.last_state = Some(state);
+        // A free nucleotide weighs half the mass of the system parameters
+        assert!((system.kinetic_energy(&system.init_cond()) - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn anchored_helix_simulation_converges() {
+        let mut thread = HelixSystemThread::new(anchored_helix_system());
+        let nb_steps = (0..10 * RigidBodyConstants::CONVERGENCE_STEPS).position(|_| thread.step());
+        assert!(nb_steps.is_some());
+        assert!(thread.get_state().energy < 1e-6);
+    }
+
+    #[test]
+    fn converged_simulation_stops_by_itself() {
+        let thread = HelixSystemThread::new(anchored_helix_system());
+        let computing = Arc::new(Mutex::new(false));
+        let (stop, state_request) = thread.run(computing.clone());
+        let start = std::time::Instant::now();
+        while *computing.lock().unwrap() {
+            assert!(start.elapsed() < std::time::Duration::from_secs(30));
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert!(*stop.lock().unwrap());
+        // Requests made after the end of the simulation are not left waiting
+        let (snd, rcv) = std::sync::mpsc::channel();
+        request_state(&stop, &state_request, snd);
+        assert!(rcv.recv().is_err());
+    }
+
+    #[test]
+    fn state_contains_forces_when_requested() {
+        let system = two_free_nucls_system(RigidBodyConstants::DEFAULT_L0);
+        assert!(HelixSystemThread::new(system).get_state().forces.is_none());
+
+        let mut system = two_free_nucls_system(RigidBodyConstants::DEFAULT_L0);
+
+        system.rigid_parameters.show_forces = true;
+        let thread = HelixSystemThread::new(system);
+        let state = thread.get_state();
+        let (expected, _) = thread
+            .helix_system
+            .forces_and_torques(&state.positions, &state.orientations);
+        let forces = state.forces.expect("forces were requested");
+        assert_eq!(forces, expected);
+        // The spring is stretched so the two nucleotides attract each other
+        assert!(forces[0].x > 0.);
+        assert!((forces[0] + forces[1]).mag() < 1e-5);
+    }
+}
@@ -344,6 +344,7 @@ impl Data {
         }
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
     }
 
     pub fn apply_copy(&mut self) -> Option<(StrandState, StrandState)> {
@@ -429,6 +430,7 @@ impl Data {
         self.update_pasted_strand(domains_vec);
         self.hash_maps_update = true;
         self.update_status = true;
+        self.modified = true;
         self.apply_copy()
     }
 
@@ -529,6 +531,7 @@ impl Data {
             //self.make_hash_maps();
             self.hash_maps_update = true;
             self.update_status = true;
+            self.modified = true;
             self.view_need_reset = true;
         }
         self.xover_copy_manager.applied = None;
@@ -572,6 +575,7 @@ impl Data {
                             if !self.is_middle_xover(&copy_1) && !self.is_middle_xover(&copy_2) {
                                 self.general_cross_over(copy_1, copy_2);
                                 self.update_status = true;
+                                self.modified = true;
                                 self.view_need_reset = true;
                             }
                         }
@@ -620,5 +624,6 @@ impl Data {
         self.template_manager = Default::default();
         self.xover_copy_manager = Default::default();
         self.update_status = true;
+        self.modified = true;
     }
 }
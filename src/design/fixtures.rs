@@ -0,0 +1,42 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Designs shared by the tests of the different modules.
+
+use super::{Data, Design};
+use std::path::PathBuf;
+
+/// The path of the file `name` in the `test_designs` directory.
+pub fn test_design_path(name: &str) -> PathBuf {
+    [
+        env!("CARGO_MANIFEST_DIR"),
+        "src/design/data/test_designs",
+        name,
+    ]
+    .iter()
+    .collect()
+}
+
+/// A design with two strands: H1: 0 -> 9 ; H2: 0 <- 9
+pub fn design_two_strands() -> Design {
+    Design::new_with_path(0, &test_design_path("two_strands.json")).expect("Could parse file")
+}
+
+/// The data of `design_two_strands`.
+pub(super) fn data_two_strands() -> Data {
+    Data::new_with_path(&test_design_path("two_strands.json")).expect("Could parse file")
+}
@@ -78,3 +78,75 @@ pub struct XoverInfo {
     /// The source nucl Strand extremity status
     pub source_strand_end: Extremity,
 }
+
+/// An error that occurred while reading a sequence from a FASTA file
+#[derive(Debug)]
+pub enum FastaError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The first record of the file contains no sequence
+    EmptySequence,
+    /// The sequence contains a character that is not a nucleotide. The position is the index of
+    /// the character in the cleaned sequence.
+    InvalidCharacter { character: char, position: usize },
+}
+
+impl std::fmt::Display for FastaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not read file: {}", e),
+            Self::EmptySequence => write!(f, "The file contains no sequence"),
+            Self::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "Invalid character {:?} at position {} of the sequence",
+                character, position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FastaError {}
+
+impl From<std::io::Error> for FastaError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Return the sequence of the first record of a FASTA file.
+///
+/// Header and comment lines are ignored, as well as whitespaces. The sequence is uppercased and
+/// uracils are replaced by thymines.
+pub fn parse_fasta(content: &str) -> Result<String, FastaError> {
+    let mut sequence = String::new();
+    let mut in_first_record = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('>') {
+            if in_first_record || !sequence.is_empty() {
+                break;
+            }
+            in_first_record = true;
+        } else if !line.starts_with(';') {
+            sequence.extend(line.chars().filter(|c| !c.is_whitespace()));
+        }
+    }
+    if sequence.is_empty() {
+        return Err(FastaError::EmptySequence);
+    }
+    sequence
+        .chars()
+        .enumerate()
+        .map(|(position, c)| match c.to_ascii_uppercase() {
+            c @ 'A' | c @ 'C' | c @ 'G' | c @ 'T' => Ok(c),
+            'U' => Ok('T'),
+            _ => Err(FastaError::InvalidCharacter {
+                character: c,
+                position,
+            }),
+        })
+        .collect()
+}
@@ -52,4 +52,21 @@ impl View {
     pub fn get_model_matrix(&self) -> Mat4 {
         self.model_matrix
     }
+
+    /// Return true if the linear part of the model matrix has a negative determinant, i.e. if the
+    /// model matrix changes the handedness of the design.
+    pub fn is_mirrored(&self) -> bool {
+        let cols = self.model_matrix.cols;
+        cols[0].xyz().dot(cols[1].xyz().cross(cols[2].xyz())) < 0.
+    }
+
+    /// If the model matrix is mirrored, negate its first axis. The two other axes and the
+    /// translation are not modified.
+    pub fn fix_mirroring(&mut self) {
+        if self.is_mirrored() {
+            let mut matrix = self.model_matrix;
+            matrix.cols[0] = -matrix.cols[0];
+            self.set_matrix(matrix);
+        }
+    }
 }
@@ -538,6 +538,12 @@ impl Application for FlatScene {
                     }
                 }
             },
+            Notification::SecondarySelection(_) => {
+                self.needs_redraw(Duration::from_nanos(1));
+                for data in self.data.iter() {
+                    data.borrow_mut().update_secondary_selection();
+                }
+            }
             Notification::Save(d_id) => self.data[d_id].borrow_mut().save_isometry(),
             Notification::ToggleText(b) => {
                 self.view[self.selected_design].borrow_mut().set_show_sec(b)
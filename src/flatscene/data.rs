@@ -49,6 +49,7 @@ pub struct Data {
     selection_mode: SelectionMode,
     pub selection: Vec<Selection>,
     pub candidates: Vec<Selection>,
+    secondary_selection: Vec<Selection>,
     id: u32,
     selection_updated: bool,
 }
@@ -71,6 +72,7 @@ impl Data {
             selection_mode: SelectionMode::default(),
             selection: vec![],
             candidates: vec![],
+            secondary_selection: vec![],
             selection_updated: false,
             id,
         }
@@ -111,6 +113,8 @@ impl Data {
         let mut candidate_strands = HashSet::new();
         let mut selected_xovers = HashSet::new();
         let mut candidate_xovers = HashSet::new();
+        let mut secondary_strands = HashSet::new();
+        let mut secondary_xovers = HashSet::new();
         let mut selected_helices = Vec::new();
         let mut candidate_helices = Vec::new();
         let id_map = self.design.id_map();
@@ -154,6 +158,22 @@ impl Data {
                 _ => (),
             }
         }
+        for s in self.secondary_selection.iter() {
+            match s {
+                Selection::Strand(_, s_id) => {
+                    secondary_strands.insert(*s_id as usize);
+                }
+                Selection::Bound(_, n1, n2) => {
+                    secondary_xovers.insert((*n1, *n2));
+                }
+                Selection::Xover(_, xover_id) => {
+                    if let Some((n1, n2)) = self.design.get_xover_with_id(*xover_id) {
+                        secondary_xovers.insert((n1, n2));
+                    }
+                }
+                _ => (),
+            }
+        }
         let mut selection_highlight = Vec::new();
         let mut candidate_highlight = Vec::new();
         for s in self.design.get_strands().iter() {
@@ -163,6 +183,9 @@ impl Data {
             if candidate_strands.contains(&s.id) {
                 candidate_highlight.push(s.highlighted(CANDIDATE_COLOR));
             }
+            if secondary_strands.contains(&s.id) {
+                selection_highlight.push(s.highlighted(SECONDARY_SELECTION_COLOR));
+            }
         }
         for xover in selected_xovers.iter() {
             selection_highlight.push(self.design.strand_from_xover(xover, SELECTED_COLOR));
@@ -170,6 +193,12 @@ impl Data {
         for xover in candidate_xovers.iter() {
             candidate_highlight.push(self.design.strand_from_xover(xover, CANDIDATE_COLOR));
         }
+        for xover in secondary_xovers.iter() {
+            selection_highlight.push(
+                self.design
+                    .strand_from_xover(xover, SECONDARY_SELECTION_COLOR),
+            );
+        }
         self.view
             .borrow_mut()
             .update_selection(&selection_highlight, &self.helices);
@@ -812,6 +841,12 @@ impl Data {
         self.selection_updated = true;
     }
 
+    /// Fetch the secondary selection of the design
+    pub fn update_secondary_selection(&mut self) {
+        self.secondary_selection = self.design.get_secondary_selection();
+        self.selection_updated = true;
+    }
+
     fn xover_containing_nucl(&self, nucl: &FlatNucl) -> Option<usize> {
         let xovers_list = self.design.get_xovers_list();
         xovers_list.iter().find_map(|(id, (n1, n2))| {
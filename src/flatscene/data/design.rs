@@ -21,6 +21,7 @@ use std::sync::{Arc, RwLock};
 use super::super::{FlatHelix, FlatIdx, FlatNucl};
 use super::{Flat, HelixVec, Nucl, Strand};
 use crate::design::{Design, Helix as DesignHelix, Strand as StrandDesign, StrandBuilder, Torsion};
+use crate::mediator::Selection;
 use ultraviolet::{Isometry2, Rotor2, Vec2};
 
 pub(super) struct Design2d {
@@ -403,6 +404,10 @@ impl Design2d {
     pub fn get_xover_with_id(&self, xover_id: usize) -> Option<(Nucl, Nucl)> {
         self.design.read().unwrap().get_xover_with_id(xover_id)
     }
+
+    pub fn get_secondary_selection(&self) -> Vec<Selection> {
+        self.design.read().unwrap().get_secondary_selection()
+    }
 }
 
 /// Store the informations needed to represent an helix from the design
@@ -29,7 +29,7 @@ use lyon::tessellation;
 use lyon::tessellation::{
     FillVertex, FillVertexConstructor, StrokeVertex, StrokeVertexConstructor,
 };
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use ultraviolet::{Isometry2, Mat2, Rotor2, Vec2, Vec4};
 
@@ -635,6 +635,7 @@ impl Helix {
         char_map: &mut HashMap<char, Vec<CharInstance>>,
         char_drawers: &HashMap<char, crate::utils::chars2d::CharDrawer>,
         show_seq: bool,
+        shown_nucls: Option<&HashSet<FlatNucl>>,
     ) {
         let show_seq = show_seq && camera.borrow().get_globals().zoom >= ZOOM_THRESHOLD;
         let size_id = 3.;
@@ -654,7 +655,11 @@ impl Helix {
             }
             let x_shift = -advances[nb_chars] / 2. * scale;
             for (c_idx, c) in self.real_id.to_string().chars().enumerate() {
-                let instances = char_map.get_mut(&c).unwrap();
+                let instances = if let Some(instances) = char_map.get_mut(&c) {
+                    instances
+                } else {
+                    continue;
+                };
                 instances.push(CharInstance {
                     center: circle.center + (x_shift + advances[c_idx] * scale) * Vec2::unit_x()
                         - scale * height / 2. * Vec2::unit_y(),
@@ -678,7 +683,11 @@ impl Helix {
             }
             let x_shift = if pos >= 0 { 0. } else { -advances[1] / 2. };
             for (c_idx, c) in pos.to_string().chars().enumerate() {
-                let instances = char_map.get_mut(&c).unwrap();
+                let instances = if let Some(instances) = char_map.get_mut(&c) {
+                    instances
+                } else {
+                    continue;
+                };
                 let center = self.num_position_top(
                     pos,
                     advances[nb_chars] * scale,
@@ -705,6 +714,16 @@ impl Helix {
         }
 
         let mut print_basis = |position: isize, forward: bool| {
+            let flat_nucl = FlatNucl {
+                helix: self.flat_id,
+                position,
+                forward,
+            };
+            if let Some(shown_nucls) = shown_nucls {
+                if !shown_nucls.contains(&flat_nucl) {
+                    return;
+                }
+            }
             let scale = size_pos;
             let nucl = Nucl {
                 helix: self.real_id,
@@ -719,13 +738,14 @@ impl Helix {
                 } else {
                     self.char_position_bottom(position, advances[1] * scale, height * scale)
                 };
-                let instances = char_map.get_mut(&c).unwrap();
-                instances.push(CharInstance {
-                    center,
-                    rotation: self.isometry.rotation.into_matrix(),
-                    size: scale,
-                    z_index: self.flat_id.flat.0 as i32,
-                })
+                if let Some(instances) = char_map.get_mut(&c) {
+                    instances.push(CharInstance {
+                        center,
+                        rotation: self.isometry.rotation.into_matrix(),
+                        size: scale,
+                        z_index: self.flat_id.flat.0 as i32,
+                    })
+                }
             }
         };
 
@@ -26,6 +26,7 @@ use ultraviolet::Vec2;
 
 type Vertices = lyon::tessellation::VertexBuffers<StrandVertex, u16>;
 
+#[derive(Clone)]
 pub struct Strand {
     pub color: u32,
     pub points: Vec<FlatNucl>,
@@ -51,6 +52,24 @@ impl Strand {
         }
     }
 
+    /// Return all the nucleotides of the strand.
+    pub fn nucls(&self) -> Vec<FlatNucl> {
+        let mut ret = Vec::new();
+        // The points come by pairs of domain extremities. If the strand is cyclic, the last point
+        // is a copy of the first one and is ignored.
+        for domain in self.points.chunks_exact(2) {
+            let left = domain[0].position.min(domain[1].position);
+            let right = domain[0].position.max(domain[1].position);
+            for position in left..=right {
+                ret.push(FlatNucl {
+                    position,
+                    ..domain[0]
+                });
+            }
+        }
+        ret
+    }
+
     pub fn to_vertices(
         &self,
         helices: &[Helix],
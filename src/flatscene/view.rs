@@ -15,17 +15,20 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
+use super::camera::{Camera, FitRectangle, Globals};
 use super::data::{
     FlatTorsion, FreeEnd, GpuVertex, Helix, HelixModel, Shift, Strand, StrandVertex,
 };
 use super::{CameraPtr, FlatIdx, FlatNucl};
 use crate::utils::bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
 use crate::utils::texture::Texture;
-use crate::utils::Ndc;
+use crate::utils::{read_texture_rgba, Ndc};
 use crate::{DrawArea, PhySize};
 use iced_wgpu::wgpu;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
 use std::rc::Rc;
+use ultraviolet::Vec2;
 use wgpu::{Device, Queue, RenderPipeline};
 
 mod helix_view;
@@ -48,10 +51,24 @@ use rectangle::Rectangle;
 
 const SHOW_SUGGESTION: bool = false;
 
+/// The characters that can be drawn by default
+const DEFAULT_CHAR_SET: &[char] = &['A', 'T', 'G', 'C'];
+
+/// The characters needed to write the helices identifiers and positions
+const NUMBER_CHARS: &[char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '-'];
+
+/// The half width of the strands in world coordinates, as set in the strand vertex shader
+const STRAND_HALF_WIDTH: f32 = 0.1125;
+
+/// The minimum half width of the strands in pixels, as set in the strand vertex shader
+const MIN_STRAND_HALF_WIDTH_PX: f32 = 0.75;
+
 pub struct View {
     device: Rc<Device>,
     queue: Rc<Queue>,
     depth_texture: Texture,
+    /// The number of samples used for multisample anti-aliasing
+    sample_count: u32,
     helices: Vec<Helix>,
     helices_view: Vec<HelixView>,
     helices_background: Vec<HelixView>,
@@ -80,6 +97,10 @@ pub struct View {
     char_map_bottom: HashMap<char, Vec<CharInstance>>,
     selection: FlatSelection,
     show_sec: bool,
+    /// The strands on which the display of the sequence differs from `show_sec`
+    show_sec_strands: HashMap<usize, bool>,
+    /// The strands of the design, used to know which letters must be displayed
+    flat_strands: Vec<Strand>,
     suggestions: Vec<(FlatNucl, FlatNucl)>,
     suggestions_view: Vec<StrandView>,
     selected_strands: Vec<StrandView>,
@@ -90,6 +111,8 @@ pub struct View {
     torsions: HashMap<(FlatNucl, FlatNucl), FlatTorsion>,
     show_torsion: bool,
     rectangle: Rectangle,
+    /// The colors of the circles of the helices that belong to a group, indexed by helix id
+    group_colors: HashMap<usize, u32>,
 }
 
 impl View {
@@ -100,6 +123,28 @@ impl View {
         camera_top: CameraPtr,
         camera_bottom: CameraPtr,
         splited: bool,
+    ) -> Self {
+        Self::with_char_set(
+            device,
+            queue,
+            area,
+            camera_top,
+            camera_bottom,
+            splited,
+            DEFAULT_CHAR_SET,
+        )
+    }
+
+    /// Create a view that can draw the characters of `chars`. The digits and the '-' sign are
+    /// always drawable.
+    pub(super) fn with_char_set(
+        device: Rc<Device>,
+        queue: Rc<Queue>,
+        area: DrawArea,
+        camera_top: CameraPtr,
+        camera_bottom: CameraPtr,
+        splited: bool,
+        chars: &[char],
     ) -> Self {
         let depth_texture =
             Texture::create_depth_texture(device.as_ref(), &area.size, SAMPLE_COUNT);
@@ -115,33 +160,28 @@ impl View {
             camera_bottom.borrow().get_globals(),
         );
 
-        let depth_stencil_state = Some(wgpu::DepthStencilState {
-            format: wgpu::TextureFormat::Depth32Float,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState {
-                front: wgpu::StencilFaceState::IGNORE,
-                back: wgpu::StencilFaceState::IGNORE,
-                read_mask: 0,
-                write_mask: 0,
-            },
-            bias: Default::default(),
-            clamp_depth: false,
-        });
+        let depth_stencil_state = depth_stencil_state();
 
         let helices_pipeline = helices_pipeline_descr(
             &device,
             globals_top.get_layout(), // the layout is the same for both globals
             models.get_layout(),
             depth_stencil_state.clone(),
+            SAMPLE_COUNT,
         );
         let strand_pipeline = strand_pipeline_descr(
             &device,
             globals_top.get_layout(),
             depth_stencil_state.clone(),
+            SAMPLE_COUNT,
         );
 
-        let background = Background::new(&device, globals_top.get_layout(), &depth_stencil_state);
+        let background = Background::new(
+            &device,
+            globals_top.get_layout(),
+            &depth_stencil_state,
+            SAMPLE_COUNT,
+        );
         let circle_drawer_top = CircleDrawer::new(
             device.clone(),
             queue.clone(),
@@ -160,38 +200,26 @@ impl View {
             globals_top.get_layout(),
             CircleKind::RotationWidget,
         );
-        let rectangle = Rectangle::new(&device, queue.clone());
-        let chars = [
-            'A', 'T', 'G', 'C', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '-',
-        ];
-        let mut char_drawers_top = HashMap::new();
-        let mut char_map_top = HashMap::new();
-        let mut char_drawers_bottom = HashMap::new();
-        let mut char_map_bottom = HashMap::new();
-        for c in chars.iter() {
-            char_drawers_top.insert(
-                *c,
-                CharDrawer::new(device.clone(), queue.clone(), globals_top.get_layout(), *c),
-            );
-            char_drawers_bottom.insert(
-                *c,
-                CharDrawer::new(device.clone(), queue.clone(), globals_top.get_layout(), *c),
-            );
-            char_map_top.insert(*c, Vec::new());
-            char_map_bottom.insert(*c, Vec::new());
-        }
+        let rectangle = Rectangle::new(&device, queue.clone(), SAMPLE_COUNT);
+        let chars = full_char_set(chars);
+        let char_drawers_top = make_char_drawers(&device, &queue, &globals_top, &chars);
+        let char_drawers_bottom = make_char_drawers(&device, &queue, &globals_top, &chars);
+        let char_map_top = empty_char_map(&chars);
+        let char_map_bottom = empty_char_map(&chars);
 
         let insertion_drawer = InsertionDrawer::new(
             device.clone(),
             queue.clone(),
             globals_top.get_layout(),
-            depth_stencil_state.clone(),
+            depth_stencil_state,
+            SAMPLE_COUNT,
         );
 
         Self {
             device,
             queue,
             depth_texture,
+            sample_count: SAMPLE_COUNT,
             helices: Vec::new(),
             helices_view: Vec::new(),
             strands: Vec::new(),
@@ -219,6 +247,8 @@ impl View {
             char_map_bottom,
             selection: FlatSelection::Nothing,
             show_sec: false,
+            show_sec_strands: HashMap::new(),
+            flat_strands: Vec::new(),
             suggestions: vec![],
             suggestions_view: vec![],
             selected_strands: vec![],
@@ -228,6 +258,7 @@ impl View {
             suggestion_candidate: None,
             torsions: HashMap::new(),
             show_torsion: false,
+            group_colors: HashMap::new(),
             rectangle,
             insertion_drawer,
         }
@@ -238,6 +269,28 @@ impl View {
         self.was_updated = true;
     }
 
+    /// Show or hide the sequence of a strand, regardless of the global setting.
+    pub fn set_show_sec_for_strand(&mut self, flat_strand_id: usize, show: bool) {
+        self.show_sec_strands.insert(flat_strand_id, show);
+        self.was_updated = true;
+    }
+
+    /// Return true if the sequence of the strand is displayed
+    pub fn get_show_sec_for_strand(&self, flat_strand_id: usize) -> bool {
+        *self
+            .show_sec_strands
+            .get(&flat_strand_id)
+            .unwrap_or(&self.show_sec)
+    }
+
+    /// Set the colors of the circles of the helices that belong to a group. The keys of
+    /// `group_colors` are helix identifiers; the helices that are not in `group_colors` keep
+    /// their default color.
+    pub fn set_group_colors(&mut self, group_colors: HashMap<usize, u32>) {
+        self.group_colors = group_colors;
+        self.was_updated = true;
+    }
+
     pub fn set_show_torsion(&mut self, show: bool) {
         self.show_torsion = show;
         self.was_updated = true;
@@ -249,12 +302,81 @@ impl View {
     }
 
     pub fn resize(&mut self, area: DrawArea) {
-        self.depth_texture =
-            Texture::create_depth_texture(self.device.clone().as_ref(), &area.size, SAMPLE_COUNT);
+        self.depth_texture = Texture::create_depth_texture(
+            self.device.clone().as_ref(),
+            &area.size,
+            self.sample_count,
+        );
         self.area_size = area.size;
         self.was_updated = true;
     }
 
+    /// Set the number of samples used for multisample anti-aliasing. The depth texture and the
+    /// pipelines drawing on it are re-created accordingly.
+    ///
+    /// Return false, and leave the view unchanged, if `sample_count` is not supported by the
+    /// device.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> bool {
+        if !Texture::SUPPORTED_SAMPLE_COUNTS.contains(&sample_count) {
+            return false;
+        }
+        if sample_count == self.sample_count {
+            return true;
+        }
+        self.sample_count = sample_count;
+        self.depth_texture =
+            Texture::create_depth_texture(self.device.as_ref(), &self.area_size, sample_count);
+        let globals_layout = self.globals_top.get_layout();
+        self.helices_pipeline = helices_pipeline_descr(
+            &self.device,
+            globals_layout,
+            self.models.get_layout(),
+            depth_stencil_state(),
+            sample_count,
+        );
+        self.strand_pipeline = strand_pipeline_descr(
+            &self.device,
+            globals_layout,
+            depth_stencil_state(),
+            sample_count,
+        );
+        self.background = Background::new(
+            &self.device,
+            globals_layout,
+            &depth_stencil_state(),
+            sample_count,
+        );
+        for drawer in [
+            &mut self.circle_drawer_top,
+            &mut self.circle_drawer_bottom,
+            &mut self.rotation_widget,
+        ]
+        .iter_mut()
+        {
+            drawer.set_sample_count(globals_layout, sample_count);
+        }
+        for drawer in self
+            .char_drawers_top
+            .values_mut()
+            .chain(self.char_drawers_bottom.values_mut())
+        {
+            drawer.set_sample_count(globals_layout, sample_count);
+        }
+        self.insertion_drawer.set_sample_count(
+            &self.device,
+            globals_layout,
+            depth_stencil_state(),
+            sample_count,
+        );
+        self.rectangle.set_sample_count(&self.device, sample_count);
+        self.was_updated = true;
+        true
+    }
+
+    pub fn get_sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
     fn add_helix(&mut self, helix: &Helix) {
         let id_helix = self.helices_view.len() as u32;
         self.helices_view.push(HelixView::new(
@@ -361,6 +483,7 @@ impl View {
             }
         }
         self.insertion_drawer.new_instances(insertions);
+        self.flat_strands = strands.to_vec();
         self.was_updated = true;
     }
 
@@ -547,11 +670,11 @@ impl View {
             a: 0.,
         };
 
-        let msaa_texture = if SAMPLE_COUNT > 1 {
+        let msaa_texture = if self.sample_count > 1 {
             Some(crate::utils::texture::Texture::create_msaa_texture(
                 self.device.clone().as_ref(),
                 &self.area_size,
-                SAMPLE_COUNT,
+                self.sample_count,
                 wgpu::TextureFormat::Bgra8UnormSrgb,
             ))
         } else {
@@ -868,14 +991,7 @@ impl View {
 
     /// Add the helices circles to the list of circle instances
     fn collect_helices_circles(&self, circles: &mut Vec<CircleInstance>, camera: &CameraPtr) {
-        for h in self.helices.iter() {
-            if let Some(circle) = h.get_circle(camera) {
-                circles.push(circle);
-            }
-            for circle in h.handle_circles() {
-                circles.push(circle)
-            }
-        }
+        circles.extend(helices_circles(&self.helices, &self.group_colors, camera));
         for h_id in self.selected_helices.iter() {
             if let Some(mut circle) = self.helices.get(h_id.0).and_then(|h| h.get_circle(camera)) {
                 circle.set_radius(circle.radius * 1.4);
@@ -972,6 +1088,20 @@ impl View {
         self.suggestion_candidate = candidate.zip(other);
     }
 
+    /// Change the set of characters that can be drawn. The digits and the '-' sign are always
+    /// drawable.
+    pub fn set_char_set(&mut self, chars: &[char]) {
+        let chars = full_char_set(chars);
+        self.char_drawers_top =
+            make_char_drawers(&self.device, &self.queue, &self.globals_top, &chars);
+        self.char_drawers_bottom =
+            make_char_drawers(&self.device, &self.queue, &self.globals_top, &chars);
+        self.char_map_top = empty_char_map(&chars);
+        self.char_map_bottom = empty_char_map(&chars);
+        self.generate_char_instances();
+        self.was_updated = true;
+    }
+
     fn generate_char_instances(&mut self) {
         for v in self.char_map_top.values_mut() {
             v.clear();
@@ -980,18 +1110,31 @@ impl View {
             v.clear();
         }
 
+        let shown_nucls = if self.show_sec_strands.is_empty() {
+            None
+        } else {
+            Some(nucls_showing_sequence(
+                &self.flat_strands,
+                self.show_sec,
+                &self.show_sec_strands,
+            ))
+        };
+        let show_sec = self.show_sec || self.show_sec_strands.values().any(|b| *b);
+
         for h in self.helices.iter() {
             h.add_char_instances(
                 &self.camera_top,
                 &mut self.char_map_top,
                 &self.char_drawers_top,
-                self.show_sec,
+                show_sec,
+                shown_nucls.as_ref(),
             );
             h.add_char_instances(
                 &self.camera_bottom,
                 &mut self.char_map_bottom,
                 &self.char_drawers_bottom,
-                self.show_sec,
+                show_sec,
+                shown_nucls.as_ref(),
             )
         }
 
@@ -1013,6 +1156,324 @@ impl View {
         self.was_updated = true;
         self.rotation_widget.new_instances(Rc::new(wheels));
     }
+
+    /// Return an SVG document showing the helices, strands and letters as they are currently seen
+    /// through the top camera. When the view is splited, only the top half is exported.
+    pub fn export_svg(&self) -> String {
+        svg_export(
+            &self.camera_top,
+            &self.helices,
+            &self.group_colors,
+            &self.flat_strands,
+            &self.char_map_top,
+        )
+    }
+
+    /// Render all the helices and strands in an offscreen texture of size `width` x `height`, with
+    /// a zoom that fits the whole design, and return the RGBA values of its pixels, row by row.
+    ///
+    /// The selection, the candidates, the suggestions and the rotation widget are not drawn.
+    /// Return an error if the rendered texture could not be read.
+    pub fn render_minimap(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, wgpu::BufferAsyncError> {
+        let size = PhySize::new(width, height);
+        let globals = Globals {
+            resolution: [width as f32, height as f32],
+            scroll_offset: [0., 0.],
+            zoom: 1.,
+            _padding: 0.,
+        };
+        let mut camera = Camera::new(globals, false);
+        camera.fit(minimap_rectangle(&self.helices));
+        let minimap_globals = UniformBindGroup::new(
+            self.device.clone(),
+            self.queue.clone(),
+            camera.get_globals(),
+        );
+
+        let extent = wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+            label: Some("minimap texture"),
+        });
+        let target = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_texture =
+            Texture::create_depth_texture(self.device.as_ref(), &size, self.sample_count);
+        let msaa_texture = if self.sample_count > 1 {
+            Some(crate::utils::texture::Texture::create_msaa_texture(
+                self.device.as_ref(),
+                &size,
+                self.sample_count,
+                wgpu::TextureFormat::Bgra8UnormSrgb,
+            ))
+        } else {
+            None
+        };
+        let (attachment, resolve_target) = if let Some(msaa) = msaa_texture.as_ref() {
+            (msaa, Some(&target))
+        } else {
+            (&target, None)
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let depth_stencil_attachment = || wgpu::RenderPassDepthStencilAttachmentDescriptor {
+            attachment: &depth_texture.view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.),
+                store: true,
+            }),
+            stencil_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(0),
+                store: true,
+            }),
+        };
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.,
+                        g: 0.,
+                        b: 0.,
+                        a: 0.,
+                    }),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: Some(depth_stencil_attachment()),
+        });
+        render_pass.set_bind_group(0, minimap_globals.get_bindgroup(), &[]);
+        render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
+        render_pass.set_pipeline(&self.helices_pipeline);
+        for background in self.helices_background.iter() {
+            background.draw(&mut render_pass);
+        }
+        for helix in self.helices_view.iter() {
+            helix.draw(&mut render_pass);
+        }
+        render_pass.set_pipeline(&self.strand_pipeline);
+        for strand in self.strands.iter() {
+            strand.draw(&mut render_pass, false);
+        }
+        drop(render_pass);
+        // The cross-overs are drawn on top of the helices, as in `Self::draw`
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: Some(depth_stencil_attachment()),
+        });
+        render_pass.set_bind_group(0, minimap_globals.get_bindgroup(), &[]);
+        render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
+        render_pass.set_pipeline(&self.strand_pipeline);
+        for strand in self.strands.iter() {
+            strand.draw_split(&mut render_pass, false);
+        }
+        drop(render_pass);
+
+        read_texture_rgba(&self.device, &self.queue, encoder, &texture, extent)
+    }
+}
+
+/// The rectangle containing the extremities of all the helices.
+fn minimap_rectangle(helices: &[Helix]) -> FitRectangle {
+    let mut ret = FitRectangle::new();
+    for h in helices.iter() {
+        let left = h.get_pivot(h.get_left());
+        ret.add_point(Vec2::new(left.x, left.y));
+        let right = h.get_pivot(h.get_right());
+        ret.add_point(Vec2::new(right.x, right.y));
+    }
+    ret
+}
+
+/// The circles of the helices and their handles. The circles of the visible helices that appear in
+/// `group_colors` get the corresponding color.
+fn helices_circles(
+    helices: &[Helix],
+    group_colors: &HashMap<usize, u32>,
+    camera: &CameraPtr,
+) -> Vec<CircleInstance> {
+    let mut ret = helix_circles(helices, group_colors, camera);
+    for h in helices.iter() {
+        ret.extend(h.handle_circles());
+    }
+    ret
+}
+
+/// The circle of each helix, without the handles.
+fn helix_circles(
+    helices: &[Helix],
+    group_colors: &HashMap<usize, u32>,
+    camera: &CameraPtr,
+) -> Vec<CircleInstance> {
+    let mut ret = Vec::new();
+    for h in helices.iter() {
+        if let Some(mut circle) = h.get_circle(camera) {
+            if let Some(color) = group_colors.get(&h.real_id).filter(|_| h.visible) {
+                circle.set_color(*color);
+            }
+            ret.push(circle);
+        }
+    }
+    ret
+}
+
+/// The points through which each strand goes, together with the color of the strand.
+fn strand_polylines(strands: &[Strand], helices: &[Helix]) -> Vec<(u32, Vec<Vec2>)> {
+    strands
+        .iter()
+        .map(|s| {
+            // The points come by pairs of domain extremities.
+            let points = s
+                .points
+                .iter()
+                .enumerate()
+                .filter(|(_, nucl)| nucl.helix.flat.0 < helices.len())
+                .map(|(i, nucl)| {
+                    let shift = if i % 2 == 0 {
+                        Shift::Prime5
+                    } else {
+                        Shift::Prime3
+                    };
+                    helices[nucl.helix].get_nucl_position(nucl, shift)
+                })
+                .collect();
+            (s.color, points)
+        })
+        .collect()
+}
+
+fn svg_color(color: u32) -> String {
+    format!("#{:06X}", color & 0xFF_FFFF)
+}
+
+/// Serialize the helices, strands and letters as seen through `camera` into an SVG document.
+/// Only the circle of each helix is exported: the handles, the selection and the candidates are
+/// not part of the figure.
+fn svg_export(
+    camera: &CameraPtr,
+    helices: &[Helix],
+    group_colors: &HashMap<usize, u32>,
+    strands: &[Strand],
+    char_map: &HashMap<char, Vec<CharInstance>>,
+) -> String {
+    let circles = helix_circles(helices, group_colors, camera);
+    let polylines = strand_polylines(strands, helices);
+    svg_document(camera, &circles, &polylines, char_map)
+}
+
+/// Serialize circles, strands and letters as seen through `camera` into an SVG document whose
+/// size is the resolution of the camera.
+fn svg_document(
+    camera: &CameraPtr,
+    circles: &[CircleInstance],
+    polylines: &[(u32, Vec<Vec2>)],
+    char_map: &HashMap<char, Vec<CharInstance>>,
+) -> String {
+    let camera = camera.borrow();
+    let globals = camera.get_globals();
+    let [width, height] = globals.resolution;
+    let zoom = globals.zoom;
+    let to_screen = |point: Vec2| {
+        let (x, y) = camera.world_to_norm_screen(point.x, point.y);
+        (x * width, y * height)
+    };
+
+    let mut ret = String::new();
+    writeln!(
+        &mut ret,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    )
+    .unwrap();
+    let stroke_width = 2. * (STRAND_HALF_WIDTH * zoom).max(MIN_STRAND_HALF_WIDTH_PX);
+    for (color, points) in polylines.iter().filter(|(_, points)| points.len() > 1) {
+        let points: Vec<String> = points
+            .iter()
+            .map(|p| {
+                let (x, y) = to_screen(*p);
+                format!("{},{}", x, y)
+            })
+            .collect();
+        writeln!(
+            &mut ret,
+            r#"  <polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            points.join(" "),
+            svg_color(*color),
+            stroke_width
+        )
+        .unwrap();
+    }
+    for circle in circles.iter() {
+        let (x, y) = to_screen(circle.center);
+        writeln!(
+            &mut ret,
+            r#"  <circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            x,
+            y,
+            circle.radius * zoom,
+            svg_color(circle.color())
+        )
+        .unwrap();
+    }
+    let mut chars: Vec<(&char, &Vec<CharInstance>)> = char_map.iter().collect();
+    chars.sort_by_key(|(c, _)| **c);
+    for (c, instances) in chars {
+        for instance in instances.iter() {
+            let (x, y) = to_screen(instance.center);
+            writeln!(
+                &mut ret,
+                r#"  <text x="{}" y="{}" font-size="{}" font-family="monospace">{}</text>"#,
+                x,
+                y,
+                instance.size * zoom,
+                c
+            )
+            .unwrap();
+        }
+    }
+    ret.push_str("</svg>\n");
+    ret
+}
+
+fn depth_stencil_state() -> Option<wgpu::DepthStencilState> {
+    Some(wgpu::DepthStencilState {
+        format: wgpu::TextureFormat::Depth32Float,
+        depth_write_enabled: true,
+        depth_compare: wgpu::CompareFunction::Less,
+        stencil: wgpu::StencilState {
+            front: wgpu::StencilFaceState::IGNORE,
+            back: wgpu::StencilFaceState::IGNORE,
+            read_mask: 0,
+            write_mask: 0,
+        },
+        bias: Default::default(),
+        clamp_depth: false,
+    })
 }
 
 fn helices_pipeline_descr(
@@ -1020,6 +1481,7 @@ fn helices_pipeline_descr(
     globals_layout: &wgpu::BindGroupLayout,
     models_layout: &wgpu::BindGroupLayout,
     depth_stencil: Option<wgpu::DepthStencilState>,
+    sample_count: u32,
 ) -> wgpu::RenderPipeline {
     let vs_module = &device.create_shader_module(&wgpu::include_spirv!("view/grid.vert.spv"));
     let fs_module = &device.create_shader_module(&wgpu::include_spirv!("view/grid.frag.spv"));
@@ -1060,7 +1522,7 @@ fn helices_pipeline_descr(
             }],
         },
         multisample: wgpu::MultisampleState {
-            count: SAMPLE_COUNT,
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -1074,6 +1536,7 @@ fn strand_pipeline_descr(
     device: &Device,
     globals: &wgpu::BindGroupLayout,
     depth_stencil: Option<wgpu::DepthStencilState>,
+    sample_count: u32,
 ) -> wgpu::RenderPipeline {
     let vs_module = &device.create_shader_module(&wgpu::include_spirv!("view/strand.vert.spv"));
     let fs_module = &device.create_shader_module(&wgpu::include_spirv!("view/strand.frag.spv"));
@@ -1123,7 +1586,7 @@ fn strand_pipeline_descr(
             entry_point: "main",
         },
         multisample: wgpu::MultisampleState {
-            count: SAMPLE_COUNT,
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -1145,3 +1608,232 @@ fn torsion_color(strength: f32) -> u32 {
     let rgb = color_space::Rgb::from(hsv);
     (0xFF << 24) | ((rgb.r as u32) << 16) | ((rgb.g as u32) << 8) | (rgb.b as u32)
 }
+
+/// Return the nucleotides of the strands whose sequence is displayed. The sequence of a strand is
+/// displayed if it is set so in `show_sec_strands`, or by default if `show_sec` is true.
+fn nucls_showing_sequence(
+    strands: &[Strand],
+    show_sec: bool,
+    show_sec_strands: &HashMap<usize, bool>,
+) -> HashSet<FlatNucl> {
+    strands
+        .iter()
+        .filter(|s| *show_sec_strands.get(&s.id).unwrap_or(&show_sec))
+        .flat_map(|s| s.nucls())
+        .collect()
+}
+
+/// Return the characters of `chars` together with the ones needed to write numbers, without
+/// duplicates.
+fn full_char_set(chars: &[char]) -> Vec<char> {
+    let mut ret: Vec<char> = Vec::new();
+    for c in chars.iter().chain(NUMBER_CHARS.iter()) {
+        if !ret.contains(c) {
+            ret.push(*c);
+        }
+    }
+    ret
+}
+
+fn make_char_drawers(
+    device: &Rc<Device>,
+    queue: &Rc<Queue>,
+    globals: &UniformBindGroup,
+    chars: &[char],
+) -> HashMap<char, CharDrawer> {
+    chars
+        .iter()
+        .map(|c| {
+            (
+                *c,
+                CharDrawer::new(device.clone(), queue.clone(), globals.get_layout(), *c),
+            )
+        })
+        .collect()
+}
+
+fn empty_char_map(chars: &[char]) -> HashMap<char, Vec<CharInstance>> {
+    chars.iter().map(|c| (*c, Vec::new())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::FlatHelix;
+    use super::*;
+    use crate::utils::gpu;
+    use std::cell::RefCell;
+    use ultraviolet::{Isometry2, Rotor2};
+
+    fn flat_nucl(flat: usize, position: isize, forward: bool) -> FlatNucl {
+        FlatNucl {
+            helix: FlatHelix {
+                flat: FlatIdx(flat),
+                real: flat + 1,
+            },
+            position,
+            forward,
+        }
+    }
+
+    fn test_camera() -> CameraPtr {
+        let globals = Globals {
+            resolution: [800., 600.],
+            scroll_offset: [0., 0.],
+            zoom: 10.,
+            _padding: 0.,
+        };
+        Rc::new(RefCell::new(Camera::new(globals, false)))
+    }
+
+    fn test_helix(flat: usize) -> Helix {
+        Helix::new(
+            0,
+            10,
+            Isometry2::new(Vec2::new(0., 5. * flat as f32), Rotor2::identity()),
+            FlatHelix {
+                flat: FlatIdx(flat),
+                real: flat,
+            },
+            flat,
+            true,
+            Default::default(),
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn svg_has_one_circle_per_helix() {
+        let camera = test_camera();
+        let helices: Vec<Helix> = (0..3).map(test_helix).collect();
+        // The helices have handles, which must not be exported
+        assert!(helices_circles(&helices, &HashMap::new(), &camera).len() > helices.len());
+        let strands = vec![Strand::new(
+            0xFF_0000FF,
+            vec![flat_nucl(0, 0, true), flat_nucl(0, 9, true)],
+            vec![],
+            0,
+            false,
+        )];
+        let svg = svg_export(
+            &camera,
+            &helices,
+            &HashMap::new(),
+            &strands,
+            &HashMap::new(),
+        );
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), helices.len());
+        assert_eq!(svg.matches("<polyline").count(), 1);
+        assert!(svg.contains("stroke=\"#0000FF\""));
+    }
+
+    #[test]
+    fn group_color_is_applied_to_helix_circle() {
+        let camera = test_camera();
+        let helices: Vec<Helix> = (0..2).map(test_helix).collect();
+        let default_color = helices[0].get_circle(&camera).unwrap().color();
+        let mut group_colors = HashMap::new();
+        group_colors.insert(1, 0xFF_123456);
+        let circles = helices_circles(&helices, &group_colors, &camera);
+        let color_of = |flat: i32| {
+            circles
+                .iter()
+                .find(|c| c.z_index == flat && c.radius > 0.5)
+                .map(|c| c.color())
+        };
+        assert_eq!(color_of(0), Some(default_color));
+        assert_eq!(color_of(1), Some(0xFF_123456));
+    }
+
+    #[test]
+    fn svg_of_empty_design() {
+        let svg = svg_document(&test_camera(), &[], &[], &HashMap::new());
+        assert_eq!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"600\" viewBox=\"0 0 800 600\">\n</svg>\n"
+        );
+    }
+
+    #[test]
+    fn char_set_with_uracil() {
+        let chars = full_char_set(&['A', 'U', 'G', 'C', '-']);
+        let char_map = empty_char_map(&chars);
+        for c in "AUGC0123456789-".chars() {
+            assert!(char_map.contains_key(&c), "missing {}", c);
+        }
+        assert!(!char_map.contains_key(&'T'));
+        assert_eq!(chars.len(), 15);
+    }
+
+    #[test]
+    fn sequence_shown_only_on_selected_strand() {
+        let strands = vec![
+            Strand::new(
+                0,
+                vec![flat_nucl(0, 0, true), flat_nucl(0, 9, true)],
+                vec![],
+                0,
+                false,
+            ),
+            Strand::new(
+                0,
+                vec![
+                    flat_nucl(1, 9, false),
+                    flat_nucl(1, 0, false),
+                    flat_nucl(0, 0, false),
+                    flat_nucl(0, 4, false),
+                ],
+                vec![],
+                1,
+                false,
+            ),
+        ];
+        let mut show_sec_strands = HashMap::new();
+        show_sec_strands.insert(1, true);
+        let shown = nucls_showing_sequence(&strands, false, &show_sec_strands);
+
+        assert_eq!(shown.len(), 15);
+        assert!(shown.contains(&flat_nucl(1, 5, false)));
+        assert!(shown.contains(&flat_nucl(0, 2, false)));
+        assert!(!shown.contains(&flat_nucl(0, 2, true)));
+        assert!(!shown.contains(&flat_nucl(0, 6, false)));
+
+        show_sec_strands.insert(1, false);
+        assert!(nucls_showing_sequence(&strands, true, &show_sec_strands)
+            .iter()
+            .all(|n| n.helix.flat == FlatIdx(0) && n.forward));
+    }
+
+    #[test]
+    #[ignore = "requires a GPU"]
+    fn minimap_has_requested_size() {
+        let (device, queue) = gpu();
+        let area = DrawArea {
+            position: PhysicalPosition::new(0, 0),
+            size: PhySize::new(800, 600),
+        };
+        let mut view = View::new(device, queue, area, test_camera(), test_camera(), false);
+        view.update_helices(&[test_helix(0), test_helix(1)]);
+        let minimap = view.render_minimap(200, 150).unwrap();
+        assert_eq!(minimap.len(), 200 * 150 * 4);
+    }
+
+    #[test]
+    #[ignore = "requires a GPU"]
+    fn sample_count_can_be_changed() {
+        let (device, queue) = gpu();
+        let area = DrawArea {
+            position: PhysicalPosition::new(0, 0),
+            size: PhySize::new(800, 600),
+        };
+        let mut view = View::new(device, queue, area, test_camera(), test_camera(), false);
+        view.update_helices(&[test_helix(0), test_helix(1)]);
+        assert!(!view.set_sample_count(3));
+        assert_eq!(view.get_sample_count(), SAMPLE_COUNT);
+        assert!(view.set_sample_count(1));
+        assert_eq!(view.get_sample_count(), 1);
+        let minimap = view.render_minimap(200, 150).unwrap();
+        assert_eq!(minimap.len(), 200 * 150 * 4);
+    }
+}
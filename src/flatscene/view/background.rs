@@ -39,6 +39,7 @@ impl Background {
         device: &Device,
         globals_layout: &wgpu::BindGroupLayout,
         depth_stencil: &Option<wgpu::DepthStencilState>,
+        sample_count: u32,
     ) -> Self {
         let mut bg_geometry: VertexBuffers<BgPoint, u16> = VertexBuffers::new();
         let mut fill_tess = FillTessellator::new();
@@ -111,7 +112,7 @@ impl Background {
             depth_stencil: depth_stencil.clone(),
             primitive: primitive.clone(),
             multisample: wgpu::MultisampleState {
-                count: SAMPLE_COUNT,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -136,7 +137,7 @@ impl Background {
             depth_stencil: depth_stencil.clone(),
             primitive,
             multisample: wgpu::MultisampleState {
-                count: SAMPLE_COUNT,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -41,6 +41,7 @@ impl InsertionDrawer {
         queue: Rc<Queue>,
         globals: &BindGroupLayout,
         depth_stencil_state: Option<DepthStencilState>,
+        sample_count: u32,
     ) -> Self {
         let instances = DynamicBindGroup::new(device.clone(), queue.clone());
         let pipeline = insertion_pipeline(
@@ -48,6 +49,7 @@ impl InsertionDrawer {
             globals,
             instances.get_layout(),
             depth_stencil_state,
+            sample_count,
         );
         let vertices = make_vertices();
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -81,6 +83,23 @@ impl InsertionDrawer {
         }
     }
 
+    /// Re-create the pipeline so that it draws on textures with `sample_count` samples.
+    pub fn set_sample_count(
+        &mut self,
+        device: &Device,
+        globals: &BindGroupLayout,
+        depth_stencil_state: Option<DepthStencilState>,
+        sample_count: u32,
+    ) {
+        self.pipeline = insertion_pipeline(
+            device,
+            globals,
+            self.instances.get_layout(),
+            depth_stencil_state,
+            sample_count,
+        );
+    }
+
     pub fn draw<'a>(&'a mut self, render_pass: &mut RenderPass<'a>) {
         self.update_instances();
         render_pass.set_pipeline(&self.pipeline);
@@ -176,6 +195,7 @@ fn insertion_pipeline(
     globals: &wgpu::BindGroupLayout,
     insertions: &wgpu::BindGroupLayout,
     depth_stencil: Option<wgpu::DepthStencilState>,
+    sample_count: u32,
 ) -> wgpu::RenderPipeline {
     let vs_module = &device.create_shader_module(&wgpu::include_spirv!("insertion.vert.spv"));
     let fs_module = &device.create_shader_module(&wgpu::include_spirv!("strand.frag.spv"));
@@ -226,7 +246,7 @@ fn insertion_pipeline(
         primitive,
         depth_stencil,
         multisample: wgpu::MultisampleState {
-            count: SAMPLE_COUNT,
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -54,10 +54,7 @@ unsafe impl bytemuck::Zeroable for Vertex {}
 unsafe impl bytemuck::Pod for Vertex {}
 
 impl Rectangle {
-    pub fn new(device: &Device, queue: Rc<Queue>) -> Self {
-        let vs_module = device.create_shader_module(&wgpu::include_spirv!("rectangle.vert.spv"));
-        let fs_module = device.create_shader_module(&wgpu::include_spirv!("rectangle.frag.spv"));
-
+    pub fn new(device: &Device, queue: Rc<Queue>, sample_count: u32) -> Self {
         let vertices = [Vertex::default(); 4];
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
@@ -73,66 +70,7 @@ impl Rectangle {
             usage: wgpu::BufferUsage::INDEX,
         });
 
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[],
-                push_constant_ranges: &[],
-            });
-
-        let targets = &[wgpu::ColorTargetState {
-            format: wgpu::TextureFormat::Bgra8UnormSrgb,
-            color_blend: wgpu::BlendState {
-                src_factor: wgpu::BlendFactor::SrcAlpha,
-                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                operation: wgpu::BlendOperation::Add,
-            },
-            alpha_blend: wgpu::BlendState {
-                src_factor: wgpu::BlendFactor::One,
-                dst_factor: wgpu::BlendFactor::One,
-                operation: wgpu::BlendOperation::Add,
-            },
-            write_mask: wgpu::ColorWrite::ALL,
-        }];
-
-        let depth_stencil = Some(wgpu::DepthStencilState {
-            format: wgpu::TextureFormat::Depth32Float,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: Default::default(),
-            bias: Default::default(),
-            clamp_depth: false,
-        });
-
-        let primitive = wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleStrip,
-            strip_index_format: Some(wgpu::IndexFormat::Uint16),
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: wgpu::CullMode::None,
-            ..Default::default()
-        };
-
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &vs_module,
-                entry_point: "main",
-                buffers: &[Vertex::desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &fs_module,
-                entry_point: "main",
-                targets,
-            }),
-            primitive,
-            depth_stencil,
-            multisample: wgpu::MultisampleState {
-                count: crate::consts::SAMPLE_COUNT,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-        });
+        let render_pipeline = rectangle_pipeline(device, sample_count);
 
         Self {
             pipeline: render_pipeline,
@@ -143,6 +81,11 @@ impl Rectangle {
         }
     }
 
+    /// Re-create the pipeline so that it draws on textures with `sample_count` samples.
+    pub fn set_sample_count(&mut self, device: &Device, sample_count: u32) {
+        self.pipeline = rectangle_pipeline(device, sample_count);
+    }
+
     pub fn update_corners(&mut self, corner: Option<[Ndc; 2]>) {
         self.corner = Some(corner)
     }
@@ -188,3 +131,68 @@ impl Rectangle {
             .write_buffer(&self.vbo, 0, bytemuck::cast_slice(&vertices));
     }
 }
+
+fn rectangle_pipeline(device: &Device, sample_count: u32) -> RenderPipeline {
+    let vs_module = device.create_shader_module(&wgpu::include_spirv!("rectangle.vert.spv"));
+    let fs_module = device.create_shader_module(&wgpu::include_spirv!("rectangle.frag.spv"));
+
+    let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Render Pipeline Layout"),
+        bind_group_layouts: &[],
+        push_constant_ranges: &[],
+    });
+
+    let targets = &[wgpu::ColorTargetState {
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
+        color_blend: wgpu::BlendState {
+            src_factor: wgpu::BlendFactor::SrcAlpha,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+        alpha_blend: wgpu::BlendState {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        },
+        write_mask: wgpu::ColorWrite::ALL,
+    }];
+
+    let depth_stencil = Some(wgpu::DepthStencilState {
+        format: wgpu::TextureFormat::Depth32Float,
+        depth_write_enabled: true,
+        depth_compare: wgpu::CompareFunction::Less,
+        stencil: Default::default(),
+        bias: Default::default(),
+        clamp_depth: false,
+    });
+
+    let primitive = wgpu::PrimitiveState {
+        topology: wgpu::PrimitiveTopology::TriangleStrip,
+        strip_index_format: Some(wgpu::IndexFormat::Uint16),
+        front_face: wgpu::FrontFace::Ccw,
+        cull_mode: wgpu::CullMode::None,
+        ..Default::default()
+    };
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(&render_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &vs_module,
+            entry_point: "main",
+            buffers: &[Vertex::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &fs_module,
+            entry_point: "main",
+            targets,
+        }),
+        primitive,
+        depth_stencil,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+    })
+}
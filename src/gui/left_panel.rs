@@ -161,6 +161,7 @@ pub enum Message {
     Redim2dHelices(bool),
     InvertScroll(bool),
     BrownianMotion(bool),
+    ShowForces(bool),
     Nothing,
     CancelHyperboloid,
     CanMakeGrid(bool),
@@ -410,6 +411,11 @@ impl Program for LeftPanel {
                 let request = &mut self.requests.lock().unwrap().rigid_body_parameters;
                 self.simulation_tab.make_rigid_body_request(request);
             }
+            Message::ShowForces(b) => {
+                self.simulation_tab.set_show_forces(b);
+                let request = &mut self.requests.lock().unwrap().rigid_body_parameters;
+                self.simulation_tab.make_rigid_body_request(request);
+            }
             Message::HelixRoll(roll) => {
                 self.edition_tab.update_roll(roll);
             }
@@ -1154,11 +1160,13 @@ pub struct RigidBodyParametersRequest {
     pub brownian_motion: bool,
     pub brownian_rate: f32,
     pub brownian_amplitude: f32,
+    pub show_forces: bool,
 }
 
 struct RigidBodyFactory {
     pub volume_exclusion: bool,
     pub brownian_motion: bool,
+    pub show_forces: bool,
     pub brownian_parameters: BrownianParametersFactory,
 }
 
@@ -1233,6 +1241,7 @@ impl Requestable for RigidBodyFactory {
             brownian_motion: self.brownian_motion,
             brownian_rate: self.brownian_parameters.rate,
             brownian_amplitude: self.brownian_parameters.amplitude,
+            show_forces: self.show_forces,
         }
     }
     fn nb_values(&self) -> usize {
@@ -888,6 +888,7 @@ impl SimulationTab {
                 RigidBodyFactory {
                     volume_exclusion: false,
                     brownian_motion: false,
+                    show_forces: false,
                     brownian_parameters: init_brownian.clone(),
                 },
             ),
@@ -956,6 +957,12 @@ impl SimulationTab {
         for view in self.brownian_factory.view(brownian_motion).into_iter() {
             ret = ret.push(view);
         }
+        ret = ret.push(right_checkbox(
+            self.rigid_body_factory.requestable.show_forces,
+            "Show forces",
+            Message::ShowForces,
+            ui_size.clone(),
+        ));
 
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
@@ -968,6 +975,10 @@ impl SimulationTab {
         self.rigid_body_factory.requestable.brownian_motion = brownian_motion;
     }
 
+    pub(super) fn set_show_forces(&mut self, show_forces: bool) {
+        self.rigid_body_factory.requestable.show_forces = show_forces;
+    }
+
     pub(super) fn make_rigid_body_request(
         &mut self,
         request: &mut Option<RigidBodyParametersRequest>,
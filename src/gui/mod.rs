@@ -109,6 +109,7 @@ pub struct Requests {
     pub rigid_grid_simulation: Option<RigidBodyParametersRequest>,
     pub rigid_helices_simulation: Option<RigidBodyParametersRequest>,
     pub anchor: bool,
+    pub secondary_selection: bool,
     pub rigid_body_parameters: Option<RigidBodyParametersRequest>,
     pub stapples_file: Option<(usize, PathBuf)>,
     pub keep_proceed: Option<KeepProceed>,
@@ -203,6 +204,7 @@ impl Requests {
             rigid_helices_simulation: None,
             rigid_grid_simulation: None,
             anchor: false,
+            secondary_selection: false,
             rigid_body_parameters: None,
             keep_proceed: None,
             stapples_file: None,
@@ -412,9 +412,24 @@ fn main() {
                     }
 
                     if let Some(ref path) = requests.file_add.take() {
-                        let design = Design::new_with_path(0, path);
+                        let design = Design::new_with_path_and_threshold(
+                            0,
+                            path,
+                            consts::LARGE_DESIGN_NUCL_THRESHOLD,
+                        );
                         let path_end = formated_path_end(path);
-                        if let Some(design) = design {
+                        if let Some((design, warning)) = design {
+                            if let Some(design::DesignLoadWarning::Large(nb_nucl)) = warning {
+                                crate::utils::message(
+                                    format!(
+                                        "This design is very large ({} nucleotides), \
+                                        the application may be slow",
+                                        nb_nucl
+                                    )
+                                    .into(),
+                                    rfd::MessageLevel::Warning,
+                                );
+                            }
                             window.set_title(&format!("ENSnano: {}", path_end));
                             messages.lock().unwrap().notify_new_design();
                             if let Some(tree) = design.get_organizer_tree() {
@@ -611,6 +626,10 @@ fn main() {
                         mediator.lock().unwrap().request_anchor();
                         requests.anchor = false;
                     }
+                    if requests.secondary_selection {
+                        mediator.lock().unwrap().request_secondary_selection();
+                        requests.secondary_selection = false;
+                    }
                     if let Some(proceed) = requests.keep_proceed.take() {
                         match proceed {
                             KeepProceed::CustomScaffold => {
@@ -34,16 +34,17 @@ use iced_winit::winit::{
 };
 use simple_excel_writer::{row, Row, Workbook};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
 use ultraviolet::Vec3;
 
 use crate::{design, ApplicationState};
 
 use design::{
-    Design, DesignNotification, DesignRotation, DesignTranslation, DnaAttribute, DnaElementKey,
-    GridDescriptor, GridHelixDescriptor, Helix, Hyperboloid, Nucl, OperationResult,
-    Parameters as DNAParameters, RigidBodyConstants, Stapple, Strand, StrandBuilder, StrandState,
+    Design, DesignNotification, DesignNotificationContent, DesignRotation, DesignTranslation,
+    DnaAttribute, DnaElementKey, GridDescriptor, GridHelixDescriptor, Helix, HelixRemovalError,
+    Hyperboloid, Nucl, OperationResult, Parameters as DNAParameters, RigidBodyConstants, Stapple,
+    Strand, StrandBuilder, StrandState,
 };
 use ensnano_organizer::OrganizerTree;
 
@@ -185,6 +186,8 @@ pub enum Notification {
     NewCandidate(Vec<Selection>, AppId),
     /// An element has been selected in the 3d view
     Selection3D(Vec<Selection>, AppId),
+    /// The secondary selection has been modified
+    SecondarySelection(Vec<Selection>),
     /// A save request has been filled
     Save(usize),
     /// The 3d camera must face a given target
@@ -397,6 +400,48 @@ impl Mediator {
         });
     }
 
+    /// Adjust the roll of the helices of a design to minimize the tension of its cross-overs
+    pub fn optimize_rolls(&mut self, d_id: usize) {
+        let (send, _rcv) = std::sync::mpsc::channel::<f32>();
+        let (_, initial_rolls) = self.designs[d_id].read().unwrap().optimize_rolls(send);
+        let final_rolls = {
+            let design = self.designs[d_id].read().unwrap();
+            initial_rolls
+                .keys()
+                .filter_map(|h_id| design.get_roll_helix(*h_id).map(|roll| (*h_id, roll)))
+                .collect()
+        };
+        self.finish_op();
+        self.undo_stack.push(Arc::new(RollsModification {
+            initial_rolls,
+            final_rolls,
+            reverse: false,
+            design_id: d_id,
+        }));
+        self.redo_stack.clear();
+    }
+
+    /// Move grid `g_b` of design `d_id` so that it faces grid `g_a` at distance `gap`.
+    pub fn align_grids(&mut self, d_id: usize, g_a: usize, g_b: usize, gap: f32) {
+        let mut design = self.designs[d_id].write().unwrap();
+        let initial_isometry = design.align_grids(g_a, g_b, gap);
+        let final_isometry = design
+            .get_grid_position(g_b)
+            .zip(design.get_grid_basis(g_b));
+        drop(design);
+        if let Some((initial_isometry, final_isometry)) = initial_isometry.zip(final_isometry) {
+            self.finish_op();
+            self.undo_stack.push(Arc::new(GridAlignment {
+                grid_id: g_b,
+                initial_isometry,
+                final_isometry,
+                reverse: false,
+                design_id: d_id,
+            }));
+            self.redo_stack.clear();
+        }
+    }
+
     pub fn download_stapples(&self, requests: Arc<Mutex<Requests>>) {
         let d_id = if let Some(d_id) = self.selected_design() {
             d_id as usize
@@ -409,23 +454,8 @@ impl Mediator {
             }
             0
         };
-        if !self.designs[d_id].read().unwrap().scaffold_is_set() {
-            message(
-                "No scaffold set. \n
-                    Chose a strand and set it as the scaffold by checking the scaffold checkbox\
-                    in the status bar"
-                    .into(),
-                rfd::MessageLevel::Error,
-            );
-            return;
-        }
-        if !self.designs[d_id].read().unwrap().scaffold_sequence_set() {
-            message(
-                "No sequence uploaded for scaffold. \n
-                Upload a sequence for the scaffold by pressing the \"Load scaffold\" button"
-                    .into(),
-                rfd::MessageLevel::Error,
-            );
+        if let Some(msg) = self.designs[d_id].read().unwrap().stapples_export_error() {
+            message(msg.into(), rfd::MessageLevel::Error);
             return;
         }
         if let Some(nucl) = self.designs[d_id].read().unwrap().get_stapple_mismatch() {
@@ -517,10 +547,13 @@ impl Mediator {
     pub fn save_design(&mut self, path: &PathBuf) {
         if let Some(d_id) = self.selected_design() {
             self.notify_apps(Notification::Save(d_id as usize));
-            self.designs[d_id as usize].read().unwrap().save_to(path)
+            self.designs[d_id as usize]
+                .read()
+                .unwrap()
+                .save_to_with_dialog(path)
         } else {
             self.notify_apps(Notification::Save(0));
-            self.designs[0].read().unwrap().save_to(path);
+            self.designs[0].read().unwrap().save_to_with_dialog(path);
             if self.designs.len() > 1 {
                 message(
                     "No design selected, saved design 0".into(),
@@ -567,6 +600,23 @@ impl Mediator {
         }
     }
 
+    /// Set the secondary selection. It is independent of the main selection and is used to define
+    /// a second group of elements for operations that involve two sets.
+    pub fn notify_secondary_selection(&mut self, selection: Vec<Selection>) {
+        for (d_id, design) in self.designs.iter().enumerate() {
+            let design_selection = selection
+                .iter()
+                .filter(|s| s.get_design() == Some(d_id as u32))
+                .cloned()
+                .collect();
+            design
+                .write()
+                .unwrap()
+                .set_secondary_selection(design_selection);
+        }
+        self.notify_apps(Notification::SecondarySelection(selection))
+    }
+
     fn cancel_pasting(&mut self) {
         self.pasting = PastingMode::Nothing;
         self.notify_all_designs(AppNotification::ResetCopyPaste);
@@ -663,19 +713,23 @@ impl Mediator {
         let mut ret = false;
         let mut notifications = Vec::new();
         for design_wrapper in self.designs.clone() {
-            if let Some(notification) = design_wrapper.read().unwrap().view_was_updated() {
-                ret = true;
-                notifications.push(Notification::DesignNotification(notification))
-            }
-            if let Some(notification) = design_wrapper.read().unwrap().data_was_updated() {
+            let design_notifications = design_wrapper.write().unwrap().drain_notifications();
+            let data_changed = design_notifications
+                .iter()
+                .any(|n| !matches!(n.content, DesignNotificationContent::ModelChanged(_)));
+            if data_changed {
                 let scaffold_info = design_wrapper.read().unwrap().get_scaffold_info();
                 self.messages
                     .lock()
                     .unwrap()
                     .push_scaffold_info(scaffold_info);
-                ret = true;
-                notifications.push(Notification::DesignNotification(notification))
             }
+            ret |= !design_notifications.is_empty();
+            notifications.extend(
+                design_notifications
+                    .into_iter()
+                    .map(Notification::DesignNotification),
+            );
         }
         if let Some(elements) = self
             .designs
@@ -880,6 +934,10 @@ impl Mediator {
                 }
             }
             OperationResult::NoChange => (),
+            OperationResult::GridPlacementFailure(e) => message(
+                format!("Could not add helix: {}", e).into(),
+                rfd::MessageLevel::Error,
+            ),
             OperationResult::BigChange(init, after) => {
                 self.current_operation = None;
                 self.undo_stack.push(Arc::new(BigStrandModification {
@@ -1077,7 +1135,7 @@ impl Mediator {
     pub fn clean_designs(&mut self) {
         if !*self.computing.lock().unwrap() {
             for d in self.designs.iter() {
-                d.write().unwrap().clean_up_domains()
+                d.write().unwrap().clean_up_domains();
             }
         }
     }
@@ -1173,6 +1231,99 @@ impl Mediator {
         }
     }
 
+    /// Set the 2d layout of the selected design to the one saved in a file
+    pub fn import_layout_2d(&mut self, path: &Path) {
+        let d_id = self.selected_design().unwrap_or(0) as usize;
+        let result = self
+            .designs
+            .get(d_id)
+            .map(|d| d.write().unwrap().import_layout_2d(path));
+        match result {
+            Some(Ok((initial_layout, final_layout))) => {
+                self.finish_op();
+                self.undo_stack.push(Arc::new(Layout2DModification {
+                    initial_layout,
+                    final_layout,
+                    reverse: false,
+                    design_id: d_id,
+                }));
+                self.redo_stack.clear();
+            }
+            Some(Err(e)) => message(
+                format!("Could not import layout: {}", e).into(),
+                rfd::MessageLevel::Error,
+            ),
+            None => (),
+        }
+    }
+
+    /// Assign sequences to the strands of the selected design from a csv file
+    pub fn import_sequences_csv(&mut self, path: &Path) {
+        let d_id = self.selected_design().unwrap_or(0) as usize;
+        let result = self
+            .designs
+            .get(d_id)
+            .map(|d| d.write().unwrap().import_sequences_csv(path));
+        match result {
+            Some(Ok((_, initial_state, final_state))) => {
+                self.finish_op();
+                self.undo_stack.push(Arc::new(BigStrandModification {
+                    initial_state,
+                    final_state,
+                    reverse: false,
+                    design_id: d_id,
+                }));
+                self.redo_stack.clear();
+            }
+            Some(Err(e)) => message(
+                format!("Could not import sequences: {}", e).into(),
+                rfd::MessageLevel::Error,
+            ),
+            None => (),
+        }
+    }
+
+    /// Straighten all the selected helices
+    pub fn straighten_helices(&mut self) {
+        for h in self.selection.clone().iter() {
+            if let Selection::Helix(d_id, h_id) = h {
+                let helix = self.designs[*d_id as usize]
+                    .read()
+                    .unwrap()
+                    .get_raw_helix(*h_id as usize);
+                if let Some(helix) = helix {
+                    self.update_opperation(Arc::new(StraightenHelix {
+                        helix,
+                        helix_id: *h_id as usize,
+                        design_id: *d_id as usize,
+                        undo: false,
+                    }));
+                }
+            }
+        }
+    }
+
+    /// Align the axis of the selected free helices with the closest principal axis
+    pub fn snap_helices_orientation(&mut self) {
+        for h in self.selection.clone().iter() {
+            if let Selection::Helix(d_id, h_id) = h {
+                let helix = self.designs[*d_id as usize]
+                    .read()
+                    .unwrap()
+                    .get_raw_helix(*h_id as usize)
+                    .filter(|h| h.grid_position.is_none());
+                if let Some(helix) = helix {
+                    self.update_opperation(Arc::new(SnapHelixOrientation {
+                        helix,
+                        helix_id: *h_id as usize,
+                        design_id: *d_id as usize,
+                        undo: false,
+                    }));
+                }
+            }
+        }
+    }
+
     pub fn roll_helix(&mut self, roll: f32) {
         for h in self.selection.iter() {
             if let Selection::Helix(d_id, h_id) = h {
@@ -1296,6 +1447,11 @@ impl Mediator {
         }
     }
 
+    /// Make the current selection the secondary selection
+    pub fn request_secondary_selection(&mut self) {
+        self.notify_secondary_selection(self.selection.clone())
+    }
+
     pub fn request_anchor(&mut self) {
         let selection = self.selection.get(0).cloned();
         if let Some(Selection::Nucleotide(d_id, nucl)) = selection {
@@ -1307,6 +1463,142 @@ impl Mediator {
         }
     }
 
+    /// Delete all the strands of the selected design except its scaffold
+    pub fn delete_all_staples(&mut self, even_without_scaffold: bool) {
+        let d_id = self.last_selected_design;
+        let result = self.designs[d_id]
+            .write()
+            .unwrap()
+            .delete_all_staples(even_without_scaffold);
+        if let Some((initial_state, final_state)) = result {
+            self.finish_op();
+            self.undo_stack.push(Arc::new(BigStrandModification {
+                initial_state,
+                final_state,
+                reverse: false,
+                design_id: d_id,
+            }));
+            self.redo_stack.clear();
+            self.notify_multiple_selection(vec![], AppId::Mediator);
+        }
+    }
+
+    pub fn remove_singleton_strands(&mut self) {
+        let d_id = self.last_selected_design;
+        let result = self.designs[d_id]
+            .write()
+            .unwrap()
+            .remove_singleton_strands();
+        if let Some((initial_state, final_state)) = result {
+            self.finish_op();
+            self.undo_stack.push(Arc::new(BigStrandModification {
+                initial_state,
+                final_state,
+                reverse: false,
+                design_id: d_id,
+            }));
+            self.redo_stack.clear();
+            self.notify_multiple_selection(vec![], AppId::Mediator);
+        }
+    }
+
+    /// Remove an helix of a design. If strands lie on the helix, they are deleted or split when
+    /// `remove_strands` is true, and the helix is kept otherwise.
+    pub fn remove_helix_with_strands(
+        &mut self,
+        d_id: usize,
+        h_id: usize,
+        remove_strands: bool,
+    ) -> Result<(), HelixRemovalError> {
+        let (initial_state, final_state) = self.designs[d_id]
+            .write()
+            .unwrap()
+            .remove_helix_with_strands(h_id, remove_strands)?;
+        self.finish_op();
+        self.undo_stack.push(Arc::new(ExtendedStrandModification {
+            initial_state,
+            final_state,
+            reverse: false,
+            design_id: d_id,
+        }));
+        self.redo_stack.clear();
+        self.notify_multiple_selection(vec![], AppId::Mediator);
+        Ok(())
+    }
+
+    /// Add mirrored copies of the selected helices and strands, reflected across one of the
+    /// principal planes going through the origin of the selected design.
+    pub fn duplicate_mirrored(&mut self, plane: crate::design::MirrorPlane) {
+        let d_id = self.last_selected_design;
+        let result = self.designs[d_id]
+            .write()
+            .unwrap()
+            .duplicate_mirrored(&self.selection, plane);
+        if let Some((initial_state, final_state)) = result {
+            self.finish_op();
+            self.undo_stack.push(Arc::new(ExtendedStrandModification {
+                initial_state,
+                final_state,
+                reverse: false,
+                design_id: d_id,
+            }));
+            self.redo_stack.clear();
+        }
+    }
+
+    /// Delete a grid of a design. The helices that were attached to it become free helices.
+    pub fn delete_grid(&mut self, d_id: usize, g_id: usize) {
+        let result = self.designs[d_id].write().unwrap().delete_grid(g_id);
+        if let Some(deleted_grid) = result {
+            self.finish_op();
+            self.undo_stack.push(Arc::new(GridDeletion {
+                deleted_grid,
+                design_id: d_id,
+                undo: false,
+            }));
+            self.redo_stack.clear();
+            self.notify_multiple_selection(vec![], AppId::Mediator);
+        }
+    }
+
+    /// Create an helix on every empty position of a grid
+    pub fn fill_grid(&mut self, d_id: usize, g_id: usize, position: isize, length: usize) {
+        let result = self.designs[d_id]
+            .write()
+            .unwrap()
+            .undoable_fill_grid(g_id, position, length);
+        if let Some((initial_state, final_state)) = result {
+            self.finish_op();
+            self.undo_stack.push(Arc::new(ExtendedStrandModification {
+                initial_state,
+                final_state,
+                reverse: false,
+                design_id: d_id,
+            }));
+            self.redo_stack.clear();
+        }
+    }
+
+    /// Route a scaffold on the selected grid
+    pub fn auto_route_scaffold(&mut self) {
+        if let Some(Selection::Grid(d_id, g_id)) = self.selection.get(0).cloned() {
+            let result = self.designs[d_id as usize]
+                .write()
+                .unwrap()
+                .auto_route_scaffold(g_id);
+            if let Some((initial_state, final_state)) = result {
+                self.finish_op();
+                self.undo_stack.push(Arc::new(ExtendedStrandModification {
+                    initial_state,
+                    final_state,
+                    reverse: false,
+                    design_id: d_id as usize,
+                }));
+                self.redo_stack.clear();
+            }
+        }
+    }
+
     pub fn new_shift_hyperboloid(&mut self, shift: f32) {
         if let Some(Selection::Grid(d_id, g_id)) = self.selection.get(0) {
             self.designs[*d_id as usize]
@@ -1504,7 +1796,8 @@ pub enum UndoableOp {
     AddGrid(GridDescriptor),
     MoveBuilder(Box<StrandBuilder>, Option<(usize, u32)>),
     ResetBuilder(Box<StrandBuilder>),
-    RmGrid,
+    RmGrid(usize),
+    RestoreGrid(crate::design::DeletedGrid),
     NewHyperboloid {
         position: Vec3,
         orientation: ultraviolet::Rotor3,
@@ -1512,9 +1805,27 @@ pub enum UndoableOp {
     },
     ClearHyperboloid,
     NewStrandState(StrandState),
+    NewExtendedStrandState(crate::design::ExtendedStrandState),
     ResetCopyPaste,
     UndoGridSimulation(crate::design::GridSystemState),
     UndoHelixSimulation(crate::design::RigidHelixState),
+    StraightenHelix {
+        helix: Helix,
+        h_id: usize,
+        undo: bool,
+    },
+    SnapHelixOrientation {
+        helix: Helix,
+        h_id: usize,
+        undo: bool,
+    },
+    NewLayout2D(crate::design::Layout2D),
+    NewRolls(std::collections::BTreeMap<usize, f32>),
+    SetGridIsometry {
+        g_id: usize,
+        position: Vec3,
+        orientation: ultraviolet::Rotor3,
+    },
 }
 
 fn write_stapples(stapples: Vec<Stapple>, path: PathBuf) {
@@ -1602,6 +1913,15 @@ fn rigid_parameters(parameters: RigidBodyParametersRequest) -> RigidBodyConstant
         brownian_motion: parameters.brownian_motion,
         brownian_rate: 10f32.powf(parameters.brownian_rate),
         brownian_amplitude: parameters.brownian_amplitude,
+        l0: RigidBodyConstants::DEFAULT_L0,
+        remove_net_momentum: false,
+        show_forces: parameters.show_forces,
+        solver: None,
+        dt: RigidBodyConstants::DEFAULT_DT,
+        seed: None,
+        convergence_threshold: None,
+        temperature_kelvin: None,
+        refresh_interval: RigidBodyConstants::DEFAULT_REFRESH_INTERVAL,
     };
     println!("{:?}", ret);
     ret
@@ -1639,3 +1959,35 @@ impl Default for MainState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::{CANDIDATE_COLOR, SECONDARY_SELECTION_COLOR, SELECTED_COLOR};
+    use crate::design::fixtures::design_two_strands;
+
+    #[test]
+    fn secondary_selection_is_independent() {
+        let messages = Arc::new(Mutex::new(IcedMessages::new()));
+        let mut mediator = Mediator::new(messages, Default::default());
+        let design = Arc::new(RwLock::new(design_two_strands()));
+        mediator.add_design(design.clone());
+
+        mediator.notify_multiple_selection(vec![Selection::Strand(0, 0)], AppId::Scene);
+        mediator.notify_secondary_selection(vec![Selection::Strand(0, 1)]);
+        assert_eq!(mediator.selection, vec![Selection::Strand(0, 0)]);
+        assert_eq!(
+            design.read().unwrap().get_secondary_selection(),
+            vec![Selection::Strand(0, 1)]
+        );
+
+        mediator.notify_multiple_selection(vec![], AppId::Scene);
+        assert_eq!(
+            design.read().unwrap().get_secondary_selection(),
+            vec![Selection::Strand(0, 1)]
+        );
+
+        assert_ne!(SECONDARY_SELECTION_COLOR, SELECTED_COLOR);
+        assert_ne!(SECONDARY_SELECTION_COLOR, CANDIDATE_COLOR);
+    }
+}
@@ -19,9 +19,15 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 pub enum RenderingMode {
     Normal,
     Cartoon,
+    /// Nucleotides are colored according to their base
+    ByBase,
 }
 
-pub const ALL_RENDERING_MODE: [RenderingMode; 2] = [RenderingMode::Normal, RenderingMode::Cartoon];
+pub const ALL_RENDERING_MODE: [RenderingMode; 3] = [
+    RenderingMode::Normal,
+    RenderingMode::Cartoon,
+    RenderingMode::ByBase,
+];
 
 impl Default for RenderingMode {
     fn default() -> Self {
@@ -58,6 +64,7 @@ impl std::fmt::Display for RenderingMode {
         let ret = match self {
             Self::Normal => "Normal",
             Self::Cartoon => "Cartoon",
+            Self::ByBase => "By base",
         };
         write!(f, "{}", ret)
     }
@@ -23,8 +23,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! Moreover, these operations are meant to be modifiable via GUI component or user interaction.
 use super::{DesignRotation, DesignTranslation, GridDescriptor, GridHelixDescriptor, UndoableOp};
 use crate::design::{
-    GridTypeDescr, Helix, Hyperboloid, IsometryTarget, Nucl, Strand, StrandBuilder, StrandState,
+    DeletedGrid, ExtendedStrandState, GridTypeDescr, Helix, Hyperboloid, IsometryTarget, Layout2D,
+    Nucl, Strand, StrandBuilder, StrandState,
 };
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use ultraviolet::{Bivec3, Rotor3, Vec3};
 
@@ -820,6 +822,116 @@ impl Operation for RawHelixCreation {
     }
 }
 
+/// Reset the roll of an helix and realign its axis. `helix` is the helix as it was before the
+/// operation.
+#[derive(Clone, Debug)]
+pub struct StraightenHelix {
+    pub helix: Helix,
+    pub helix_id: usize,
+    pub design_id: usize,
+    pub undo: bool,
+}
+
+impl Operation for StraightenHelix {
+    fn descr(&self) -> OperationDescriptor {
+        OperationDescriptor::StraightenHelix
+    }
+
+    fn compose(&self, _other: &dyn Operation) -> Option<Arc<dyn Operation>> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn reverse(&self) -> Arc<dyn Operation> {
+        Arc::new(StraightenHelix {
+            undo: !self.undo,
+            ..self.clone()
+        })
+    }
+
+    fn effect(&self) -> UndoableOp {
+        UndoableOp::StraightenHelix {
+            helix: self.helix.clone(),
+            h_id: self.helix_id,
+            undo: self.undo,
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Straighten helix {}", self.helix_id)
+    }
+
+    fn target(&self) -> usize {
+        self.design_id
+    }
+
+    fn with_new_value(&self, _n: usize, _val: String) -> Option<Arc<dyn Operation>> {
+        None
+    }
+}
+
+/// Align the axis of a free helix with the closest principal axis. `helix` is the helix as it
+/// was before the operation.
+#[derive(Clone, Debug)]
+pub struct SnapHelixOrientation {
+    pub helix: Helix,
+    pub helix_id: usize,
+    pub design_id: usize,
+    pub undo: bool,
+}
+
+impl Operation for SnapHelixOrientation {
+    fn descr(&self) -> OperationDescriptor {
+        OperationDescriptor::SnapHelixOrientation
+    }
+
+    fn compose(&self, _other: &dyn Operation) -> Option<Arc<dyn Operation>> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn reverse(&self) -> Arc<dyn Operation> {
+        Arc::new(SnapHelixOrientation {
+            undo: !self.undo,
+            ..self.clone()
+        })
+    }
+
+    fn effect(&self) -> UndoableOp {
+        UndoableOp::SnapHelixOrientation {
+            helix: self.helix.clone(),
+            h_id: self.helix_id,
+            undo: self.undo,
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Snap orientation of helix {}", self.helix_id)
+    }
+
+    fn target(&self) -> usize {
+        self.design_id
+    }
+
+    fn with_new_value(&self, _n: usize, _val: String) -> Option<Arc<dyn Operation>> {
+        None
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Cut a strand at a given nucleotide.
 ///
@@ -1080,6 +1192,8 @@ impl Operation for CrossCut {
 
 #[derive(Clone, Debug)]
 pub struct CreateGrid {
+    /// The identifier that the grid has once created
+    pub grid_id: usize,
     pub position: Vec3,
     pub orientation: Rotor3,
     pub grid_type: GridTypeDescr,
@@ -1116,7 +1230,7 @@ impl Operation for CreateGrid {
 
     fn effect(&self) -> UndoableOp {
         if self.delete {
-            UndoableOp::RmGrid
+            UndoableOp::RmGrid(self.grid_id)
         } else {
             UndoableOp::AddGrid(GridDescriptor {
                 position: self.position,
@@ -1156,6 +1270,64 @@ impl Operation for CreateGrid {
     }
 }
 
+/// The deletion of a grid. The helices that were attached to the grid are re-attached to it when
+/// the deletion is undone.
+#[derive(Clone, Debug)]
+pub struct GridDeletion {
+    pub deleted_grid: DeletedGrid,
+    pub design_id: usize,
+    pub undo: bool,
+}
+
+impl Operation for GridDeletion {
+    fn descr(&self) -> OperationDescriptor {
+        OperationDescriptor::GridDeletion
+    }
+
+    fn compose(&self, _other: &dyn Operation) -> Option<Arc<dyn Operation>> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn reverse(&self) -> Arc<dyn Operation> {
+        Arc::new(GridDeletion {
+            undo: !self.undo,
+            ..self.clone()
+        })
+    }
+
+    fn effect(&self) -> UndoableOp {
+        if self.undo {
+            UndoableOp::RestoreGrid(self.deleted_grid.clone())
+        } else {
+            UndoableOp::RmGrid(self.deleted_grid.g_id())
+        }
+    }
+
+    fn description(&self) -> String {
+        if self.undo {
+            format!("Restore grid")
+        } else {
+            format!("Delete grid")
+        }
+    }
+
+    fn target(&self) -> usize {
+        self.design_id
+    }
+
+    fn with_new_value(&self, _n: usize, _val: String) -> Option<Arc<dyn Operation>> {
+        None
+    }
+}
+
 #[derive(Clone)]
 pub struct BigStrandModification {
     pub initial_state: StrandState,
@@ -1221,6 +1393,64 @@ impl Operation for BigStrandModification {
     }
 }
 
+/// A modification of the strands that also creates or deletes helices or changes the scaffold
+#[derive(Clone, Debug)]
+pub struct ExtendedStrandModification {
+    pub initial_state: ExtendedStrandState,
+    pub final_state: ExtendedStrandState,
+    pub reverse: bool,
+    pub design_id: usize,
+}
+
+impl Operation for ExtendedStrandModification {
+    fn descr(&self) -> OperationDescriptor {
+        OperationDescriptor::ExtendedStrandModification
+    }
+
+    fn compose(&self, _other: &dyn Operation) -> Option<Arc<dyn Operation>> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn reverse(&self) -> Arc<dyn Operation> {
+        Arc::new(ExtendedStrandModification {
+            reverse: !self.reverse,
+            ..self.clone()
+        })
+    }
+
+    fn effect(&self) -> UndoableOp {
+        if self.reverse {
+            UndoableOp::NewExtendedStrandState(self.initial_state.clone())
+        } else {
+            UndoableOp::NewExtendedStrandState(self.final_state.clone())
+        }
+    }
+
+    fn description(&self) -> String {
+        if self.reverse {
+            format!("Reverse Big Change")
+        } else {
+            format!("Redo Big Change")
+        }
+    }
+
+    fn target(&self) -> usize {
+        self.design_id
+    }
+
+    fn with_new_value(&self, _n: usize, _val: String) -> Option<Arc<dyn Operation>> {
+        None
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct NewHyperboloid {
     pub position: Vec3,
@@ -1477,7 +1707,14 @@ pub enum OperationDescriptor {
     RmStrand,
     BuildStrand(std::time::SystemTime),
     CreateGrid,
+    GridDeletion,
     BigStrandModification,
+    ExtendedStrandModification,
+    StraightenHelix,
+    SnapHelixOrientation,
+    Layout2DModification,
+    RollsModification,
+    GridAlignment,
 }
 
 impl PartialEq<Self> for OperationDescriptor {
@@ -1508,3 +1745,185 @@ impl PartialEq<Self> for OperationDescriptor {
         !self.eq(rhs)
     }
 }
+
+/// A modification of the 2d isometries of several helices
+#[derive(Clone, Debug)]
+pub struct Layout2DModification {
+    pub initial_layout: Layout2D,
+    pub final_layout: Layout2D,
+    pub reverse: bool,
+    pub design_id: usize,
+}
+
+impl Operation for Layout2DModification {
+    fn descr(&self) -> OperationDescriptor {
+        OperationDescriptor::Layout2DModification
+    }
+
+    fn compose(&self, _other: &dyn Operation) -> Option<Arc<dyn Operation>> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn reverse(&self) -> Arc<dyn Operation> {
+        Arc::new(Layout2DModification {
+            reverse: !self.reverse,
+            ..self.clone()
+        })
+    }
+
+    fn effect(&self) -> UndoableOp {
+        if self.reverse {
+            UndoableOp::NewLayout2D(self.initial_layout.clone())
+        } else {
+            UndoableOp::NewLayout2D(self.final_layout.clone())
+        }
+    }
+
+    fn description(&self) -> String {
+        if self.reverse {
+            format!("Undo layout import")
+        } else {
+            format!("Import layout")
+        }
+    }
+
+    fn target(&self) -> usize {
+        self.design_id
+    }
+
+    fn with_new_value(&self, _n: usize, _val: String) -> Option<Arc<dyn Operation>> {
+        None
+    }
+}
+
+/// A modification of the rolls of several helices
+#[derive(Clone, Debug)]
+pub struct RollsModification {
+    pub initial_rolls: BTreeMap<usize, f32>,
+    pub final_rolls: BTreeMap<usize, f32>,
+    pub reverse: bool,
+    pub design_id: usize,
+}
+
+impl Operation for RollsModification {
+    fn descr(&self) -> OperationDescriptor {
+        OperationDescriptor::RollsModification
+    }
+
+    fn compose(&self, _other: &dyn Operation) -> Option<Arc<dyn Operation>> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn reverse(&self) -> Arc<dyn Operation> {
+        Arc::new(RollsModification {
+            reverse: !self.reverse,
+            ..self.clone()
+        })
+    }
+
+    fn effect(&self) -> UndoableOp {
+        if self.reverse {
+            UndoableOp::NewRolls(self.initial_rolls.clone())
+        } else {
+            UndoableOp::NewRolls(self.final_rolls.clone())
+        }
+    }
+
+    fn description(&self) -> String {
+        if self.reverse {
+            format!("Undo rolls optimization")
+        } else {
+            format!("Optimize rolls")
+        }
+    }
+
+    fn target(&self) -> usize {
+        self.design_id
+    }
+
+    fn with_new_value(&self, _n: usize, _val: String) -> Option<Arc<dyn Operation>> {
+        None
+    }
+}
+
+/// The alignment of a grid on an other one
+#[derive(Clone, Debug)]
+pub struct GridAlignment {
+    pub grid_id: usize,
+    /// The position and orientation of the grid before the alignment
+    pub initial_isometry: (Vec3, Rotor3),
+    /// The position and orientation of the grid after the alignment
+    pub final_isometry: (Vec3, Rotor3),
+    pub reverse: bool,
+    pub design_id: usize,
+}
+
+impl Operation for GridAlignment {
+    fn descr(&self) -> OperationDescriptor {
+        OperationDescriptor::GridAlignment
+    }
+
+    fn compose(&self, _other: &dyn Operation) -> Option<Arc<dyn Operation>> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn reverse(&self) -> Arc<dyn Operation> {
+        Arc::new(GridAlignment {
+            reverse: !self.reverse,
+            ..self.clone()
+        })
+    }
+
+    fn effect(&self) -> UndoableOp {
+        let (position, orientation) = if self.reverse {
+            self.initial_isometry
+        } else {
+            self.final_isometry
+        };
+        UndoableOp::SetGridIsometry {
+            g_id: self.grid_id,
+            position,
+            orientation,
+        }
+    }
+
+    fn description(&self) -> String {
+        if self.reverse {
+            format!("Undo alignment of grid {}", self.grid_id)
+        } else {
+            format!("Align grid {}", self.grid_id)
+        }
+    }
+
+    fn target(&self) -> usize {
+        self.design_id
+    }
+
+    fn with_new_value(&self, _n: usize, _val: String) -> Option<Arc<dyn Operation>> {
+        None
+    }
+}
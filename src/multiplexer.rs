@@ -506,6 +506,9 @@ impl Multiplexer {
                     VirtualKeyCode::L if ctrl(&self.modifiers) => {
                         self.requests.lock().unwrap().anchor = true;
                     }
+                    VirtualKeyCode::B if ctrl(&self.modifiers) => {
+                        self.requests.lock().unwrap().secondary_selection = true;
+                    }
                     VirtualKeyCode::R if !ctrl(&self.modifiers) => {
                         self.requests.lock().unwrap().action_mode = Some(ActionMode::Rotate)
                     }
@@ -51,12 +51,13 @@ mod controller;
 use controller::{Consequence, Controller};
 /// Handling of designs and internal data
 mod data;
+pub use camera::Axis6;
 pub use controller::ClickMode;
 use data::Data;
 use design::{Design, DesignNotification, DesignNotificationContent};
 mod element_selector;
 use element_selector::{ElementSelector, SceneElement};
-mod maths_3d;
+pub(crate) mod maths_3d;
 
 type ViewPtr = Rc<RefCell<View>>;
 type DataPtr = Rc<RefCell<Data>>;
@@ -251,6 +252,7 @@ impl Scene {
                 position,
                 x,
                 y,
+                preview,
             } => {
                 self.mediator
                     .lock()
@@ -263,12 +265,22 @@ impl Scene {
                         length,
                         position,
                     }));
-                self.select(Some(SceneElement::Grid(design_id, grid_id)));
-                self.view.borrow_mut().update(ViewUpdate::Camera);
-                self.mediator.lock().unwrap().suspend_op();
+                if !preview {
+                    self.select(Some(SceneElement::Grid(design_id, grid_id)));
+                    self.view.borrow_mut().update(ViewUpdate::Camera);
+                    self.mediator.lock().unwrap().suspend_op();
+                }
             }
             Consequence::PasteCandidate(element) => self.pasting_candidate(element),
             Consequence::Paste(element) => self.attempt_paste(element),
+            Consequence::BoxSelected(elements, adding) => {
+                let selection = self.data.borrow_mut().select_elements(elements, adding);
+                self.mediator
+                    .lock()
+                    .unwrap()
+                    .notify_multiple_selection(selection, AppId::Scene);
+                self.update_handle();
+            }
             Consequence::DoubleClick(element) => {
                 let selection = self.data.borrow().to_selection(element);
                 if let Some(selection) = selection {
@@ -291,6 +303,7 @@ impl Scene {
             .unwrap()
             .update_opperation(Arc::new(CreateGrid {
                 design_id: 0,
+                grid_id: self.data.borrow().get_nb_grids(0),
                 position,
                 orientation,
                 grid_type,
@@ -508,6 +521,7 @@ impl Scene {
     fn update_handle(&mut self) {
         let origin = self.data.borrow().get_selected_position();
         let orientation = self.data.borrow().get_widget_basis();
+        let scale = self.view.borrow().get_widget_scale();
         let descr = origin
             .clone()
             .zip(orientation.clone())
@@ -515,6 +529,7 @@ impl Scene {
                 origin,
                 orientation: HandleOrientation::Rotor(orientation),
                 size: 0.25,
+                scale,
             });
         self.view.borrow_mut().update(ViewUpdate::Handles(descr));
         let only_right = !self.data.borrow().selection_can_rotate_freely();
@@ -525,6 +540,7 @@ impl Scene {
                 origin,
                 orientation: RotationWidgetOrientation::Rotor(orientation),
                 size: 0.2,
+                scale,
                 only_right,
             });
         self.view
@@ -567,14 +583,56 @@ impl Scene {
         self.fit_design();
     }
 
+    /// Make the camera look along the axis of an helix, to get a cross-section view of the
+    /// helices that are parallel to it.
+    pub fn look_along_helix(&mut self, h_id: usize, design_id: usize) {
+        let axis = self.data.borrow().get_helix_axis(h_id, design_id);
+        if let Some((origin, direction)) = axis {
+            self.view.borrow_mut().look_along_helix(origin, direction);
+            let camera = self.view.borrow().get_camera();
+            let (position, rotor) = (camera.borrow().position, camera.borrow().rotor);
+            // The camera controller must be informed of the new camera position
+            self.controller.teleport_camera(position, rotor);
+            self.notify(SceneNotification::CameraMoved);
+        }
+    }
+
+    /// Make the fog center follow a nucleotide, or center the fog on the camera if `target` is
+    /// `None`.
+    pub fn set_fog_follow_nucl(&mut self, target: Option<(usize, Nucl)>) {
+        self.view.borrow_mut().set_fog_follow_nucl(target);
+        self.data.borrow_mut().notify_matrices_update();
+    }
+
+    /// Make the camera look straight down one of the world's axes.
+    pub fn snap_camera_to(&mut self, axis: Axis6) {
+        let pivot = self.camera_pivot();
+        self.view.borrow_mut().snap_camera_to(axis, pivot);
+        let camera = self.view.borrow().get_camera();
+        let (position, rotor) = (camera.borrow().position, camera.borrow().rotor);
+        self.controller.teleport_camera(position, rotor);
+        self.notify(SceneNotification::CameraMoved);
+    }
+
+    /// Set the factor applied to the size of the handles and of the rotation widget.
+    pub fn set_widget_scale(&mut self, scale: f32) {
+        self.view.borrow_mut().set_widget_scale(scale);
+    }
+
     fn request_camera_rotation(&mut self, xz: f32, yz: f32, xy: f32) {
+        let pivot = self.camera_pivot();
+        self.controller.rotate_camera(xz, yz, xy, pivot);
+    }
+
+    /// The point around which the camera turns: the position of the selection, or of the element
+    /// at the center of the screen, which becomes selected, if nothing is selected.
+    fn camera_pivot(&mut self) -> Option<Vec3> {
         let pivot = self.data.borrow().get_selected_position();
-        let pivot = pivot.or_else(|| {
+        pivot.or_else(|| {
             let element_center = self.element_center();
             self.data.borrow_mut().set_selection(element_center);
             self.data.borrow().get_selected_position()
-        });
-        self.controller.rotate_camera(xz, yz, xy, pivot);
+        })
     }
 }
 
@@ -656,6 +714,12 @@ impl Scene {
     pub fn fog_request(&mut self, fog: FogParameters) {
         self.view.borrow_mut().update(ViewUpdate::Fog(fog))
     }
+
+    /// Set the colors of the A, T, G and C bases used when nucleotides are colored by base
+    pub fn set_base_color_scheme(&mut self, scheme: [u32; 4]) {
+        self.view.borrow_mut().set_base_color_scheme(scheme);
+        self.data.borrow_mut().notify_instance_update();
+    }
 }
 
 impl Application for Scene {
@@ -686,6 +750,9 @@ impl Application for Scene {
                     self.data.borrow_mut().notify_selection(selection)
                 }
             }
+            Notification::SecondarySelection(selection) => {
+                self.data.borrow_mut().notify_secondary_selection(selection)
+            }
             Notification::Save(_) => (),
             Notification::CameraTarget((target, up)) => {
                 self.set_camera_target(target, up);
@@ -724,7 +791,10 @@ impl Application for Scene {
                 self.data.borrow_mut().toggle_widget_basis(b);
                 self.update_handle();
             }
-            Notification::RenderingMode(mode) => self.view.borrow_mut().rendering_mode(mode),
+            Notification::RenderingMode(mode) => {
+                self.view.borrow_mut().rendering_mode(mode);
+                self.data.borrow_mut().notify_instance_update();
+            }
             Notification::Background3D(bg) => self.view.borrow_mut().background3d(bg),
         }
     }
@@ -762,6 +832,10 @@ impl Scene {
             DesignNotificationContent::InstanceChanged => {
                 self.data.borrow_mut().notify_instance_update()
             }
+            DesignNotificationContent::StrandsChanged(strands) => self
+                .data
+                .borrow_mut()
+                .notify_strands_update(notification.design_id as u32, strands),
             DesignNotificationContent::ViewNeedReset => {
                 self.data.borrow_mut().notify_instance_update();
                 self.data.borrow_mut().set_selection(None);
@@ -72,9 +72,80 @@ impl Camera {
     pub fn get_basis(&self) -> maths_3d::Basis3D {
         maths_3d::Basis3D::from_vecs(self.right_vec(), self.up_vec(), -self.direction())
     }
+
+    /// Place the camera at distance `distance` of `origin` so that it looks at `origin` in the
+    /// direction `direction`.
+    pub fn look_along(&mut self, origin: Vec3, direction: Vec3, distance: f32) {
+        let direction = direction.normalized();
+        // The world's up vector cannot be used if the camera looks along it
+        let world_up = if direction.dot(Vec3::unit_y()).abs() > 0.99 {
+            Vec3::unit_z()
+        } else {
+            Vec3::unit_y()
+        };
+        let right = direction.cross(world_up).normalized();
+        let up = right.cross(direction);
+        self.rotor = Mat3::new(right, up, -direction).into_rotor3();
+        self.position = origin - distance * direction;
+    }
+
+    /// Make the camera look straight at the face of the scene that is orthogonal to `axis`.
+    ///
+    /// If `pivot` is not `None`, the camera is moved so that it stays at the same distance of
+    /// `pivot` and looks at it. Otherwise only the orientation of the camera is modified.
+    pub fn snap_to(&mut self, axis: Axis6, pivot: Option<Vec3>) {
+        let direction = -axis.unit_vec();
+        if let Some(pivot) = pivot {
+            let distance = (self.position - pivot).mag();
+            self.look_along(pivot, direction, distance);
+        } else {
+            let position = self.position;
+            self.look_along(position, direction, 0.);
+        }
+    }
+
+    /// Remove the roll of the camera so that its right vector is horizontal, without modifying
+    /// its direction.
+    ///
+    /// Return false, and leave the camera unchanged, if the camera looks (almost) vertically.
+    pub fn level(&mut self) -> bool {
+        let direction = self.direction().normalized();
+        if direction.dot(Vec3::unit_y()).abs() > 0.99 {
+            return false;
+        }
+        let right = direction.cross(Vec3::unit_y()).normalized();
+        let up = right.cross(direction);
+        self.rotor = Mat3::new(right, up, -direction).into_rotor3();
+        true
+    }
 }
 
-#[derive(Debug)]
+/// One of the six directions of the world's axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis6 {
+    PlusX,
+    MinusX,
+    PlusY,
+    MinusY,
+    PlusZ,
+    MinusZ,
+}
+
+impl Axis6 {
+    /// The unit vector pointing in this direction
+    pub fn unit_vec(&self) -> Vec3 {
+        match self {
+            Self::PlusX => Vec3::unit_x(),
+            Self::MinusX => -Vec3::unit_x(),
+            Self::PlusY => Vec3::unit_y(),
+            Self::MinusY => -Vec3::unit_y(),
+            Self::PlusZ => Vec3::unit_z(),
+            Self::MinusZ => -Vec3::unit_z(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 /// This structure holds the information needed to compute the projection matrix.
 pub struct Projection {
     aspect: f32,
@@ -82,10 +153,25 @@ pub struct Projection {
     fovy: f32,
     znear: f32,
     zfar: f32,
+    kind: ProjectionKind,
+}
+
+/// The kind of projection used to draw the scene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectionKind {
+    Perspective,
+    /// An orthographic projection that shows, in every plane parallel to the screen, the area
+    /// that the perspective projection shows at distance `distance` from the camera.
+    Orthographic {
+        distance: f32,
+    },
 }
 
 pub type ProjectionPtr = Rc<RefCell<Projection>>;
 
+/// The smallest allowed distance of the near clipping plane
+const MIN_ZNEAR: f32 = 1e-3;
+
 impl Projection {
     pub fn new(width: u32, height: u32, fovy: f32, znear: f32, zfar: f32) -> Self {
         Self {
@@ -93,6 +179,7 @@ impl Projection {
             fovy,
             znear,
             zfar,
+            kind: ProjectionKind::Perspective,
         }
     }
 
@@ -102,12 +189,65 @@ impl Projection {
 
     /// Computes the projection matrix.
     pub fn calc_matrix(&self) -> Mat4 {
-        ultraviolet::projection::rh_yup::perspective_wgpu_dx(
-            self.fovy,
-            self.aspect,
-            self.znear,
-            self.zfar,
-        )
+        match self.kind {
+            ProjectionKind::Perspective => ultraviolet::projection::rh_yup::perspective_wgpu_dx(
+                self.fovy,
+                self.aspect,
+                self.znear,
+                self.zfar,
+            ),
+            ProjectionKind::Orthographic { distance } => {
+                let half_height = self.half_height_at(distance);
+                let half_width = half_height * self.aspect;
+                ultraviolet::projection::rh_yup::orthographic_wgpu_dx(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.znear,
+                    self.zfar,
+                )
+            }
+        }
+    }
+
+    /// Set the distances of the near and far clipping planes.
+    ///
+    /// `znear` is clamped to be at least `MIN_ZNEAR`, and `zfar` to be larger than `znear`.
+    pub fn set_clip_planes(&mut self, znear: f32, zfar: f32) {
+        self.znear = znear.max(MIN_ZNEAR);
+        self.zfar = zfar.max(2. * self.znear);
+    }
+
+    /// The distances of the near and far clipping planes
+    pub fn get_clip_planes(&self) -> (f32, f32) {
+        (self.znear, self.zfar)
+    }
+
+    pub fn set_kind(&mut self, kind: ProjectionKind) {
+        self.kind = kind;
+    }
+
+    pub fn get_kind(&self) -> ProjectionKind {
+        self.kind
+    }
+
+    /// A copy of self that uses a perspective projection
+    pub fn perspective(&self) -> Self {
+        Self {
+            kind: ProjectionKind::Perspective,
+            ..self.clone()
+        }
+    }
+
+    /// Half the height of the visible area, in the plane parallel to the screen at distance `dist`
+    /// from the camera.
+    pub fn half_height_at(&self, dist: f32) -> f32 {
+        let dist = match self.kind {
+            ProjectionKind::Perspective => dist,
+            ProjectionKind::Orthographic { distance } => distance,
+        };
+        dist * (self.fovy / 2.).tan()
     }
 
     pub fn get_fovy(&self) -> f32 {
@@ -545,3 +685,96 @@ struct Plane {
     origin: Vec3,
     normal: Vec3,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_parallel(u: Vec3, v: Vec3) {
+        let cos = u.normalized().dot(v.normalized());
+        assert!(
+            (cos - 1.).abs() < 1e-4,
+            "{:?} is not parallel to {:?}",
+            u,
+            v
+        );
+    }
+
+    #[test]
+    fn orthographic_projection_matrix() {
+        let mut projection = Projection::new(800, 600, 70f32.to_radians(), 0.1, 1000.);
+        let perspective = projection.calc_matrix();
+        projection.set_kind(ProjectionKind::Orthographic { distance: 10. });
+        let orthographic = projection.calc_matrix();
+        assert_ne!(perspective.cols[3].w, orthographic.cols[3].w);
+        assert_eq!(orthographic.cols[3].w, 1.);
+        assert_eq!(projection.perspective().calc_matrix(), perspective);
+    }
+
+    #[test]
+    fn look_along_axis() {
+        let mut camera = Camera::new(Vec3::new(10., 10., 10.), Rotor3::identity());
+        let origin = Vec3::new(1., 2., 3.);
+        let direction = Vec3::new(1., -0.5, 2.);
+        camera.look_along(origin, direction, 20.);
+        assert_parallel(camera.direction(), direction);
+        assert_parallel(origin - camera.position, direction);
+        assert!(((origin - camera.position).mag() - 20.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn leveled_camera_is_upright() {
+        let rotor = Rotor3::from_euler_angles(0.7, -0.4, 1.3);
+        let mut camera = Camera::new(Vec3::zero(), rotor);
+        let direction = camera.direction();
+        assert!(camera.level());
+        assert!(camera.right_vec().y.abs() < 1e-4);
+        assert!(camera.up_vec().y > 0.);
+        assert_parallel(camera.direction(), direction);
+
+        let mut vertical = Camera::new(Vec3::zero(), Rotor3::identity());
+        vertical.look_along(Vec3::zero(), -Vec3::unit_y(), 5.);
+        let rotor = vertical.rotor;
+        assert!(!vertical.level());
+        assert_eq!(vertical.rotor, rotor);
+    }
+
+    #[test]
+    fn camera_snapped_to_z_looks_down() {
+        let rotor = Rotor3::from_euler_angles(0.7, -0.4, 1.3);
+        let mut camera = Camera::new(Vec3::new(3., 4., 5.), rotor);
+        let pivot = Vec3::new(1., 1., 1.);
+        let distance = (camera.position - pivot).mag();
+        camera.snap_to(Axis6::PlusZ, Some(pivot));
+        assert!((camera.direction() - Vec3::new(0., 0., -1.)).mag() < 1e-4);
+        assert!(((camera.position - pivot).mag() - distance).abs() < 1e-4);
+        assert_parallel(pivot - camera.position, camera.direction());
+    }
+
+    #[test]
+    fn clip_planes_are_encoded_in_projection() {
+        let mut projection = Projection::new(800, 600, 70f32.to_radians(), 0.1, 1000.);
+        projection.set_clip_planes(1., 5000.);
+        assert_eq!(projection.get_clip_planes(), (1., 5000.));
+        let depth = |z: f32| {
+            let clip = projection.calc_matrix() * ultraviolet::Vec4::new(0., 0., -z, 1.);
+            clip.z / clip.w
+        };
+        assert!((depth(5000.) - 1.).abs() < 1e-4);
+        assert!(depth(1.).abs() < 1e-4);
+
+        projection.set_clip_planes(-1., 0.);
+        let (znear, zfar) = projection.get_clip_planes();
+        assert!(znear > 0.);
+        assert!(zfar > znear);
+    }
+
+    #[test]
+    fn look_along_vertical_axis() {
+        let mut camera = Camera::new(Vec3::zero(), Rotor3::identity());
+        camera.look_along(Vec3::zero(), -Vec3::unit_y(), 5.);
+        assert_parallel(camera.direction(), -Vec3::unit_y());
+        assert!(camera.up_vec().mag() > 0.99);
+        assert!(camera.direction().dot(camera.up_vec()).abs() < 1e-4);
+    }
+}
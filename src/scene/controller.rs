@@ -57,8 +57,13 @@ pub struct Controller {
     click_mode: ClickMode,
     state: State,
     pub(super) pasting: bool,
+    /// The maximal delay, in milliseconds, between the two clicks of a double click
+    double_click_ms: u128,
 }
 
+/// The default maximal delay, in milliseconds, between the two clicks of a double click
+const DEFAULT_DOUBLE_CLICK_MS: u128 = 250;
+
 pub enum Consequence {
     CameraMoved,
     CameraTranslated(f64, f64),
@@ -88,10 +93,15 @@ pub enum Consequence {
         length: usize,
         x: isize,
         y: isize,
+        /// True if the helix is only previewed while its length is being chosen
+        preview: bool,
     },
     PasteCandidate(Option<super::SceneElement>),
     Paste(Option<super::SceneElement>),
     DoubleClick(Option<super::SceneElement>),
+    /// Some elements have been selected with a selection box. The boolean indicates if they
+    /// must be added to the current selection.
+    BoxSelected(Vec<super::SceneElement>, bool),
 }
 
 enum TransistionConsequence {
@@ -121,9 +131,15 @@ impl Controller {
             click_mode: ClickMode::TranslateCam,
             state: automata::initial_state(),
             pasting: false,
+            double_click_ms: DEFAULT_DOUBLE_CLICK_MS,
         }
     }
 
+    /// Set the maximal delay, in milliseconds, between the two clicks of a double click.
+    pub fn set_double_click_timeout(&mut self, double_click_ms: u128) {
+        self.double_click_ms = double_click_ms;
+    }
+
     pub fn update_modifiers(&mut self, modifiers: ModifiersState) {
         self.current_modifiers = modifiers;
     }
@@ -167,6 +183,7 @@ impl Controller {
             Transition {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: PhysicalPosition::new(-1., -1.),
+                    last_candidate: None,
                 })),
                 consequences: Consequence::Nothing,
             }
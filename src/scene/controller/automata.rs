@@ -19,13 +19,14 @@ use super::*;
 use crate::mediator::ActionMode;
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub(super) type State = RefCell<Box<dyn ControllerState>>;
 
 pub(super) fn initial_state() -> State {
     RefCell::new(Box::new(NormalState {
         mouse_position: PhysicalPosition::new(-1., -1.),
+        last_candidate: None,
     }))
 }
 
@@ -75,8 +76,29 @@ pub(super) trait ControllerState {
     }
 }
 
+/// The time during which a candidate identical to the last one is not emitted again
+const CANDIDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
 pub struct NormalState {
     pub mouse_position: PhysicalPosition<f64>,
+    /// The last candidate that was emitted, and the date of its emission
+    pub last_candidate: Option<(Option<SceneElement>, Instant)>,
+}
+
+impl NormalState {
+    /// Emit `candidate` unless it is the same as the last candidate and the cursor moved less
+    /// than `CANDIDATE_DEBOUNCE` ago. This avoids redrawing the scene every time the cursor moves
+    /// over the same element: the candidate is only emitted again once the cursor has settled.
+    fn candidate_transition(&mut self, candidate: Option<SceneElement>) -> Transition {
+        let now = Instant::now();
+        let last_candidate = self.last_candidate.replace((candidate, now));
+        if let Some((last_candidate, date)) = last_candidate {
+            if last_candidate == candidate && now.duration_since(date) < CANDIDATE_DEBOUNCE {
+                return Transition::nothing();
+            }
+        }
+        Transition::consequence(Consequence::Candidate(candidate))
+    }
 }
 
 impl ControllerState for NormalState {
@@ -114,12 +136,12 @@ impl ControllerState for NormalState {
                         } else {
                             element
                         };
-                        Transition::consequence(Consequence::Candidate(candidate))
+                        self.candidate_transition(candidate)
                     } else {
-                        Transition::consequence(Consequence::Candidate(element))
+                        self.candidate_transition(element)
                     }
                 } else {
-                    Transition::consequence(Consequence::Candidate(element))
+                    self.candidate_transition(element)
                 }
             }
             WindowEvent::MouseInput {
@@ -177,6 +199,7 @@ impl ControllerState for NormalState {
                                         grid_id: intersection.grid_id,
                                         design_id: d_id,
                                         clicked_position: position,
+                                        dragging: false,
                                     })),
                                     consequences: Consequence::Nothing,
                                 }
@@ -239,6 +262,15 @@ impl ControllerState for NormalState {
                             }
                         }
                     }
+                    None => Transition {
+                        new_state: Some(Box::new(DraggingSelectionBox {
+                            clicked_position: position,
+                            mouse_position: position,
+                            adding: controller.current_modifiers.shift()
+                                | ctrl(&controller.current_modifiers),
+                        })),
+                        consequences: Consequence::Nothing,
+                    },
                     _ => Transition {
                         new_state: Some(Box::new(Selecting {
                             element,
@@ -329,6 +361,7 @@ impl ControllerState for TranslatingCamera {
             } if *button == self.button_pressed => Transition {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: self.mouse_position,
+                    last_candidate: None,
                 })),
                 consequences: Consequence::MovementEnded,
             },
@@ -386,6 +419,7 @@ impl ControllerState for SettingPivot {
                 Transition {
                     new_state: Some(Box::new(NormalState {
                         mouse_position: position,
+                        last_candidate: None,
                     })),
                     consequences: Consequence::PivotElement(element),
                 }
@@ -435,6 +469,7 @@ impl ControllerState for RotatingCamera {
             } if *button == self.button_pressed => Transition {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: position,
+                    last_candidate: None,
                 })),
                 consequences: Consequence::Nothing,
             },
@@ -458,7 +493,7 @@ impl ControllerState for Selecting {
 
     fn check_timers(&mut self, controller: &Controller) -> Transition {
         let now = Instant::now();
-        if (now - self.click_date).as_millis() > 250 {
+        if (now - self.click_date).as_millis() > controller.double_click_ms {
             if let Some((nucl, d_id)) = controller
                 .data
                 .borrow()
@@ -487,6 +522,7 @@ impl ControllerState for Selecting {
                 Transition {
                     new_state: Some(Box::new(NormalState {
                         mouse_position: self.mouse_position,
+                        last_candidate: None,
                     })),
                     consequences: Consequence::Nothing,
                 }
@@ -519,6 +555,7 @@ impl ControllerState for Selecting {
                         Transition {
                             new_state: Some(Box::new(NormalState {
                                 mouse_position: self.mouse_position,
+                                last_candidate: None,
                             })),
                             consequences: Consequence::Nothing,
                         }
@@ -549,6 +586,90 @@ impl ControllerState for Selecting {
     }
 }
 
+/// The user clicked in an empty region of the scene and may be drawing a selection box.
+struct DraggingSelectionBox {
+    clicked_position: PhysicalPosition<f64>,
+    mouse_position: PhysicalPosition<f64>,
+    adding: bool,
+}
+
+impl ControllerState for DraggingSelectionBox {
+    fn display(&self) -> Cow<'static, str> {
+        "Dragging Selection Box".into()
+    }
+
+    fn input(
+        &mut self,
+        event: &WindowEvent,
+        position: PhysicalPosition<f64>,
+        controller: &Controller,
+        _pixel_reader: &mut ElementSelector,
+    ) -> Transition {
+        match event {
+            WindowEvent::CursorMoved { .. } => {
+                self.mouse_position = position;
+                Transition::nothing()
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } => {
+                if let Some((corner1, corner2)) =
+                    self.selection_rectangle(position, controller.area_size)
+                {
+                    let elements = controller
+                        .data
+                        .borrow()
+                        .nucls_in_rectangle(corner1, corner2);
+                    Transition {
+                        new_state: Some(Box::new(NormalState {
+                            mouse_position: position,
+                            last_candidate: None,
+                        })),
+                        consequences: Consequence::BoxSelected(elements, self.adding),
+                    }
+                } else {
+                    Transition {
+                        // The mouse did not move, this is a click in the void
+                        new_state: Some(Box::new(WaitDoubleClick {
+                            click_date: Instant::now(),
+                            element: None,
+                            mouse_position: position,
+                            clicked_position: self.clicked_position,
+                        })),
+                        consequences: Consequence::ElementSelected(None, self.adding),
+                    }
+                }
+            }
+            _ => Transition::nothing(),
+        }
+    }
+}
+
+impl DraggingSelectionBox {
+    /// Return the opposite corners, in normalized screen coordinates, of the selection box
+    /// going from the clicked position to `position`. Return `None` if the mouse did not move
+    /// enough for the click to be a selection box.
+    fn selection_rectangle(
+        &self,
+        position: PhysicalPosition<f64>,
+        area_size: PhySize,
+    ) -> Option<((f32, f32), (f32, f32))> {
+        if position_difference(position, self.clicked_position) > 5. {
+            let normalize = |p: PhysicalPosition<f64>| {
+                (
+                    (p.x / area_size.width as f64) as f32,
+                    (p.y / area_size.height as f64) as f32,
+                )
+            };
+            Some((normalize(self.clicked_position), normalize(position)))
+        } else {
+            None
+        }
+    }
+}
+
 struct WaitDoubleClick {
     click_date: Instant,
     element: Option<SceneElement>,
@@ -556,13 +677,16 @@ struct WaitDoubleClick {
     clicked_position: PhysicalPosition<f64>,
 }
 
-impl ControllerState for WaitDoubleClick {
-    fn check_timers(&mut self, _controller: &Controller) -> Transition {
+impl WaitDoubleClick {
+    /// Go back to the normal state if no second click happened during the last
+    /// `double_click_ms` milliseconds.
+    fn check_timeout(&self, double_click_ms: u128) -> Transition {
         let now = Instant::now();
-        if (now - self.click_date).as_millis() > 250 {
+        if (now - self.click_date).as_millis() > double_click_ms {
             Transition {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: self.mouse_position,
+                    last_candidate: None,
                 })),
                 consequences: Consequence::Nothing,
             }
@@ -570,6 +694,12 @@ impl ControllerState for WaitDoubleClick {
             Transition::nothing()
         }
     }
+}
+
+impl ControllerState for WaitDoubleClick {
+    fn check_timers(&mut self, controller: &Controller) -> Transition {
+        self.check_timeout(controller.double_click_ms)
+    }
 
     fn display(&self) -> Cow<'static, str> {
         "Waiting Double Click".into()
@@ -590,6 +720,7 @@ impl ControllerState for WaitDoubleClick {
             } => Transition {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: self.mouse_position,
+                    last_candidate: None,
                 })),
                 consequences: Consequence::DoubleClick(self.element.clone()),
             },
@@ -599,6 +730,7 @@ impl ControllerState for WaitDoubleClick {
                     Transition {
                         new_state: Some(Box::new(NormalState {
                             mouse_position: self.mouse_position,
+                            last_candidate: None,
                         })),
                         consequences: Consequence::Nothing,
                     }
@@ -635,6 +767,7 @@ impl ControllerState for TranslatingWidget {
             } => Transition {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: position,
+                    last_candidate: None,
                 })),
                 consequences: Consequence::MovementEnded,
             },
@@ -672,6 +805,7 @@ impl ControllerState for RotatingWidget {
             } => Transition {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: position,
+                    last_candidate: None,
                 })),
                 consequences: Consequence::MovementEnded,
             },
@@ -718,6 +852,7 @@ impl ControllerState for BuildingStrand {
                 Transition {
                     new_state: Some(Box::new(NormalState {
                         mouse_position: position,
+                        last_candidate: None,
                     })),
                     consequences: consequence,
                 }
@@ -777,6 +912,7 @@ impl ControllerState for Xovering {
                     Transition {
                         new_state: Some(Box::new(NormalState {
                             mouse_position: position,
+                            last_candidate: None,
                         })),
                         consequences: Consequence::XoverAtempt(source, target, design_id),
                     }
@@ -784,6 +920,7 @@ impl ControllerState for Xovering {
                     Transition {
                         new_state: Some(Box::new(NormalState {
                             mouse_position: position,
+                            last_candidate: None,
                         })),
                         consequences: Consequence::EndFreeXover,
                     }
@@ -813,6 +950,8 @@ struct BuildingHelix {
     length_helix: usize,
     position_helix: isize,
     clicked_position: PhysicalPosition<f64>,
+    /// True if the user is dragging the mouse to choose the length of the helix
+    dragging: bool,
 }
 
 impl ControllerState for BuildingHelix {
@@ -824,18 +963,21 @@ impl ControllerState for BuildingHelix {
         &mut self,
         event: &WindowEvent,
         position: PhysicalPosition<f64>,
-        _controller: &Controller,
+        controller: &Controller,
         _pixel_reader: &mut ElementSelector,
     ) -> Transition {
         match event {
             WindowEvent::CursorMoved { .. } => {
-                if position_difference(self.clicked_position, position) > 5. {
-                    Transition {
-                        new_state: Some(Box::new(NormalState {
-                            mouse_position: position,
-                        })),
-                        consequences: Consequence::Nothing,
+                if self.dragging || position_difference(self.clicked_position, position) > 5. {
+                    self.dragging = true;
+                    if let Some(length) = self.dragged_length(position, controller) {
+                        self.length_helix = length;
+                        Transition::consequence(self.build_helix(true))
+                    } else {
+                        Transition::nothing()
                     }
+                } else if self.snap_to_free_cell(controller) {
+                    Transition::consequence(self.build_helix(true))
                 } else {
                     Transition::nothing()
                 }
@@ -844,24 +986,91 @@ impl ControllerState for BuildingHelix {
                 state: ElementState::Released,
                 button: MouseButton::Left,
                 ..
-            } => Transition {
-                new_state: Some(Box::new(NormalState {
-                    mouse_position: position,
-                })),
-                consequences: Consequence::BuildHelix {
-                    design_id: self.design_id,
-                    grid_id: self.grid_id,
-                    length: self.length_helix,
-                    x: self.x_helix,
-                    y: self.y_helix,
-                    position: self.position_helix,
-                },
-            },
+            } => {
+                if !self.dragging {
+                    self.snap_to_free_cell(controller);
+                }
+                Transition {
+                    new_state: Some(Box::new(NormalState {
+                        mouse_position: position,
+                        last_candidate: None,
+                    })),
+                    consequences: self.build_helix(false),
+                }
+            }
             _ => Transition::nothing(),
         }
     }
 }
 
+impl BuildingHelix {
+    /// Move the helix to the nearest empty cell of the grid if the clicked cell is occupied.
+    /// Return true if a free cell was found.
+    fn snap_to_free_cell(&mut self, controller: &Controller) -> bool {
+        let mouse_x = self.clicked_position.x / controller.area_size.width as f64;
+        let mouse_y = self.clicked_position.y / controller.area_size.height as f64;
+        let intersection = controller
+            .data
+            .borrow()
+            .get_design(self.design_id)
+            .and_then(|design| {
+                controller.view.borrow().grid_intersection_free(
+                    mouse_x as f32,
+                    mouse_y as f32,
+                    &design.read().unwrap(),
+                )
+            })
+            .filter(|intersection| intersection.grid_id == self.grid_id);
+        if let Some(intersection) = intersection {
+            self.x_helix = intersection.x;
+            self.y_helix = intersection.y;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The length of the helix going from `self.position_helix` to the nucleotide whose
+    /// projection on the screen is the closest to the cursor.
+    fn dragged_length(
+        &self,
+        position: PhysicalPosition<f64>,
+        controller: &Controller,
+    ) -> Option<usize> {
+        let axis = controller.data.borrow().get_grid_helix_axis(
+            self.design_id,
+            self.grid_id,
+            self.x_helix,
+            self.y_helix,
+        )?;
+        let mouse_x = position.x / controller.area_size.width as f64;
+        let mouse_y = position.y / controller.area_size.height as f64;
+        let nucl = controller
+            .view
+            .borrow()
+            .compute_projection_axis(&axis, mouse_x, mouse_y)?;
+        Some(self.length_to_nucl(nucl))
+    }
+
+    /// The length of the helix going from `self.position_helix` to the nucleotide at position
+    /// `nucl`. The helix has at least one nucleotide.
+    fn length_to_nucl(&self, nucl: isize) -> usize {
+        (nucl - self.position_helix + 1).max(1) as usize
+    }
+
+    fn build_helix(&self, preview: bool) -> Consequence {
+        Consequence::BuildHelix {
+            design_id: self.design_id,
+            grid_id: self.grid_id,
+            length: self.length_helix,
+            x: self.x_helix,
+            y: self.y_helix,
+            position: self.position_helix,
+            preview,
+        }
+    }
+}
+
 struct Pasting {
     clicked_position: PhysicalPosition<f64>,
     element: Option<SceneElement>,
@@ -885,6 +1094,7 @@ impl ControllerState for Pasting {
                     Transition {
                         new_state: Some(Box::new(NormalState {
                             mouse_position: position,
+                            last_candidate: None,
                         })),
                         consequences: Consequence::Nothing,
                     }
@@ -899,6 +1109,7 @@ impl ControllerState for Pasting {
             } => Transition {
                 new_state: Some(Box::new(NormalState {
                     mouse_position: position,
+                    last_candidate: None,
                 })),
                 consequences: Consequence::Paste(self.element),
             },
@@ -918,3 +1129,107 @@ fn ctrl(modifiers: &ModifiersState) -> bool {
         modifiers.ctrl()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::camera::{Camera, Projection};
+    use super::super::super::maths_3d;
+    use super::*;
+    use crate::design::Axis;
+    use crate::PhySize;
+    use std::rc::Rc;
+    use ultraviolet::Rotor3;
+
+    #[test]
+    fn double_click_timeout_is_configurable() {
+        let state = WaitDoubleClick {
+            click_date: Instant::now() - std::time::Duration::from_millis(500),
+            element: None,
+            mouse_position: PhysicalPosition::new(0., 0.),
+            clicked_position: PhysicalPosition::new(0., 0.),
+        };
+        assert!(state.check_timeout(60_000).new_state.is_none());
+
+        let transition = state.check_timeout(100);
+        assert_eq!(transition.new_state.unwrap().display(), "Normal");
+    }
+
+    #[test]
+    fn selection_box_is_given_in_normalized_coordinates() {
+        let state = DraggingSelectionBox {
+            clicked_position: PhysicalPosition::new(200., 450.),
+            mouse_position: PhysicalPosition::new(200., 450.),
+            adding: false,
+        };
+        let area_size = PhySize::new(800, 600);
+        let (corner1, corner2) = state
+            .selection_rectangle(PhysicalPosition::new(600., 150.), area_size)
+            .unwrap();
+        assert_eq!(corner1, (0.25, 0.75));
+        assert_eq!(corner2, (0.75, 0.25));
+
+        // A click without drag is not a selection box
+        assert!(state
+            .selection_rectangle(PhysicalPosition::new(202., 451.), area_size)
+            .is_none());
+    }
+
+    #[test]
+    fn dragging_sets_the_length_of_built_helix() {
+        let camera = Rc::new(RefCell::new(Camera::new(
+            Vec3::new(3., 2., 20.),
+            Rotor3::identity(),
+        )));
+        let projection = Rc::new(RefCell::new(Projection::new(800, 600, 0.8, 0.1, 1000.)));
+        let axis = Axis {
+            origin: Vec3::zero(),
+            direction: 0.332 * Vec3::unit_x(),
+        };
+        let state = BuildingHelix {
+            design_id: 0,
+            grid_id: 0,
+            x_helix: 0,
+            y_helix: 0,
+            length_helix: 10,
+            position_helix: 0,
+            clicked_position: PhysicalPosition::new(400., 300.),
+            dragging: false,
+        };
+        // Drag the cursor to the projection of the 20th nucleotide of the helix
+        let (x, y) = maths_3d::project_point(
+            axis.origin + 19. * axis.direction,
+            camera.clone(),
+            projection.clone(),
+        )
+        .unwrap();
+        let nucl = maths_3d::project_cursor_on_axis(&axis, camera, projection, x as f64, y as f64)
+            .unwrap();
+        assert_eq!(nucl, 19);
+        assert_eq!(state.length_to_nucl(nucl), 20);
+        // Dragging before the start of the helix gives a helix of length one
+        assert_eq!(state.length_to_nucl(-5), 1);
+    }
+
+    #[test]
+    fn identical_candidates_are_emitted_once() {
+        let mut state = NormalState {
+            mouse_position: PhysicalPosition::new(0., 0.),
+            last_candidate: None,
+        };
+        let element = Some(SceneElement::DesignElement(0, 12));
+        let nb_candidates = (0..10)
+            .filter(|_| {
+                matches!(
+                    state.candidate_transition(element).consequences,
+                    Consequence::Candidate(_)
+                )
+            })
+            .count();
+        assert_eq!(nb_candidates, 1);
+        let other = Some(SceneElement::DesignElement(0, 13));
+        assert!(matches!(
+            state.candidate_transition(other).consequences,
+            Consequence::Candidate(c) if c == other
+        ));
+    }
+}
@@ -49,6 +49,8 @@ pub struct Data {
     candidate_element: Option<SceneElement>,
     selection: Vec<Selection>,
     candidates: Vec<Selection>,
+    /// A second set of selected elements, independent of `self.selection`
+    secondary_selection: Vec<Selection>,
     /// The kind of selection being perfomed on the scene.
     selection_mode: SelectionMode,
     /// The kind of selection being performed if self.selection_mode is SelectionMode::Nucl.
@@ -72,6 +74,8 @@ pub struct Data {
     pivot_position: Option<Vec3>,
     free_xover: Option<FreeXover>,
     free_xover_update: bool,
+    /// The strands whose instances must be updated, for each design
+    strands_update: HashMap<u32, HashSet<usize>>,
 }
 
 impl Data {
@@ -83,6 +87,7 @@ impl Data {
             candidate_element: None,
             selection: Vec::new(),
             candidates: Vec::new(),
+            secondary_selection: Vec::new(),
             selection_mode: SelectionMode::default(),
             sub_selection_mode: SelectionMode::Nucleotide,
             action_mode: Default::default(),
@@ -97,6 +102,7 @@ impl Data {
             pivot_position: None,
             free_xover: None,
             free_xover_update: false,
+            strands_update: HashMap::new(),
         }
     }
 
@@ -108,6 +114,13 @@ impl Data {
         self.notify_matrices_update();
     }
 
+    /// Return a pointer to the design `design_id`
+    pub fn get_design(&self, design_id: u32) -> Option<Arc<RwLock<Design>>> {
+        self.designs
+            .get(design_id as usize)
+            .map(|design| design.get_design())
+    }
+
     /// Remove all designs to be drawn
     pub fn clear_designs(&mut self) {
         self.designs = Vec::new();
@@ -115,6 +128,7 @@ impl Data {
         self.candidate_element = None;
         self.selection = Vec::new();
         self.candidates = Vec::new();
+        self.secondary_selection = Vec::new();
         self.reset_selection();
         self.reset_candidate();
         self.notify_instance_update();
@@ -134,6 +148,9 @@ impl Data {
         if self.instance_update {
             self.update_instances();
             self.instance_update = false;
+            self.strands_update.clear();
+        } else if !self.strands_update.is_empty() {
+            self.update_strands_instances();
         }
 
         if self.selection_update {
@@ -291,19 +308,21 @@ impl Data {
         ret
     }*/
 
-    /// Return the instances of selected spheres
-    pub fn get_selected_spheres(&self) -> Rc<Vec<RawDnaInstance>> {
+    /// Return the instances of the elements of `selection`, drawn with color `color`
+    fn highlight_instances(
+        &self,
+        selection: &[Selection],
+        object_type: ObjectType,
+        color: u32,
+    ) -> Vec<RawDnaInstance> {
         let mut ret = Vec::new();
-        for selection in self.selection.iter() {
-            for element in self
-                .expand_selection(ObjectType::Nucleotide(0), selection)
-                .iter()
-            {
+        for selection in selection.iter() {
+            for element in self.expand_selection(object_type, selection).iter() {
                 match element {
                     SceneElement::DesignElement(d_id, id) => {
                         if let Some(instance) = self.designs[*d_id as usize].make_instance(
                             *id,
-                            SELECTED_COLOR,
+                            color,
                             SELECT_SCALE_FACTOR,
                         ) {
                             ret.push(instance)
@@ -314,11 +333,7 @@ impl Data {
                             .designs
                             .get(phantom_element.design_id as usize)
                             .and_then(|d| {
-                                d.make_instance_phantom(
-                                    phantom_element,
-                                    SELECTED_COLOR,
-                                    SELECT_SCALE_FACTOR,
-                                )
+                                d.make_instance_phantom(phantom_element, color, SELECT_SCALE_FACTOR)
                             })
                         {
                             ret.push(instance);
@@ -328,127 +343,49 @@ impl Data {
                 }
             }
         }
+        ret
+    }
+
+    /// Return the instances of selected spheres, including the ones of the secondary selection
+    pub fn get_selected_spheres(&self) -> Rc<Vec<RawDnaInstance>> {
+        let mut ret =
+            self.highlight_instances(&self.selection, ObjectType::Nucleotide(0), SELECTED_COLOR);
+        ret.extend(self.highlight_instances(
+            &self.secondary_selection,
+            ObjectType::Nucleotide(0),
+            SECONDARY_SELECTION_COLOR,
+        ));
         Rc::new(ret)
     }
 
-    /// Return the instances of selected tubes
+    /// Return the instances of selected tubes, including the ones of the secondary selection
     pub fn get_selected_tubes(&self) -> Rc<Vec<RawDnaInstance>> {
-        let mut ret = Vec::new();
-        for selection in self.selection.iter() {
-            for element in self
-                .expand_selection(ObjectType::Bound(0, 0), selection)
-                .iter()
-            {
-                match element {
-                    SceneElement::DesignElement(d_id, id) => {
-                        if let Some(instance) = self.designs[*d_id as usize].make_instance(
-                            *id,
-                            SELECTED_COLOR,
-                            SELECT_SCALE_FACTOR,
-                        ) {
-                            ret.push(instance)
-                        }
-                    }
-                    SceneElement::PhantomElement(phantom_element) => {
-                        if let Some(instance) = self
-                            .designs
-                            .get(phantom_element.design_id as usize)
-                            .and_then(|d| {
-                                d.make_instance_phantom(
-                                    phantom_element,
-                                    SELECTED_COLOR,
-                                    SELECT_SCALE_FACTOR,
-                                )
-                            })
-                        {
-                            ret.push(instance);
-                        }
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        }
+        let mut ret =
+            self.highlight_instances(&self.selection, ObjectType::Bound(0, 0), SELECTED_COLOR);
+        ret.extend(self.highlight_instances(
+            &self.secondary_selection,
+            ObjectType::Bound(0, 0),
+            SECONDARY_SELECTION_COLOR,
+        ));
         Rc::new(ret)
     }
 
     /// Return the instances of candidate spheres
     pub fn get_candidate_spheres(&self) -> Rc<Vec<RawDnaInstance>> {
-        let mut ret = Vec::new();
-        for candidate in self.candidates.iter() {
-            for element in self
-                .expand_selection(ObjectType::Nucleotide(0), candidate)
-                .iter()
-            {
-                match element {
-                    SceneElement::DesignElement(d_id, id) => {
-                        if let Some(instance) = self.designs[*d_id as usize].make_instance(
-                            *id,
-                            CANDIDATE_COLOR,
-                            SELECT_SCALE_FACTOR,
-                        ) {
-                            ret.push(instance)
-                        }
-                    }
-                    SceneElement::PhantomElement(phantom_element) => {
-                        if let Some(instance) = self
-                            .designs
-                            .get(phantom_element.design_id as usize)
-                            .and_then(|d| {
-                                d.make_instance_phantom(
-                                    phantom_element,
-                                    CANDIDATE_COLOR,
-                                    SELECT_SCALE_FACTOR,
-                                )
-                            })
-                        {
-                            ret.push(instance);
-                        }
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        }
-        Rc::new(ret)
+        Rc::new(self.highlight_instances(
+            &self.candidates,
+            ObjectType::Nucleotide(0),
+            CANDIDATE_COLOR,
+        ))
     }
 
     /// Return the instances of candidate tubes
     pub fn get_candidate_tubes(&self) -> Rc<Vec<RawDnaInstance>> {
-        let mut ret = Vec::new();
-        for candidate in self.candidates.iter() {
-            for element in self
-                .expand_selection(ObjectType::Bound(0, 0), candidate)
-                .iter()
-            {
-                match element {
-                    SceneElement::DesignElement(d_id, id) => {
-                        if let Some(instance) = self.designs[*d_id as usize].make_instance(
-                            *id,
-                            CANDIDATE_COLOR,
-                            SELECT_SCALE_FACTOR,
-                        ) {
-                            ret.push(instance)
-                        }
-                    }
-                    SceneElement::PhantomElement(phantom_element) => {
-                        if let Some(instance) = self
-                            .designs
-                            .get(phantom_element.design_id as usize)
-                            .and_then(|d| {
-                                d.make_instance_phantom(
-                                    phantom_element,
-                                    CANDIDATE_COLOR,
-                                    SELECT_SCALE_FACTOR,
-                                )
-                            })
-                        {
-                            ret.push(instance);
-                        }
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        }
-        Rc::new(ret)
+        Rc::new(self.highlight_instances(
+            &self.candidates,
+            ObjectType::Bound(0, 0),
+            CANDIDATE_COLOR,
+        ))
     }
 
     /// Return the identifier of the group of the selected element
@@ -626,6 +563,73 @@ impl Data {
         Some(selection).filter(|s| *s != Selection::Nothing)
     }
 
+    /// Return the number of grids of design `d_id`.
+    pub fn get_nb_grids(&self, d_id: usize) -> usize {
+        self.designs
+            .get(d_id)
+            .map(|d| d.get_grid().len())
+            .unwrap_or(0)
+    }
+
+    /// Return the axis of the helix that would be built at position (`x`, `y`) of grid `g_id` of
+    /// design `d_id`.
+    pub fn get_grid_helix_axis(
+        &self,
+        d_id: u32,
+        g_id: usize,
+        x: isize,
+        y: isize,
+    ) -> Option<crate::design::Axis> {
+        self.designs
+            .get(d_id as usize)
+            .and_then(|d| d.get_grid_helix_axis(g_id, x, y))
+    }
+
+    /// Return the nucleotides whose projection on the screen lies in the rectangle with opposite
+    /// corners `corner1` and `corner2`, given in normalized screen coordinates.
+    pub fn nucls_in_rectangle(
+        &self,
+        corner1: (f32, f32),
+        corner2: (f32, f32),
+    ) -> Vec<SceneElement> {
+        let camera = self.view.borrow().get_camera();
+        let projection = self.view.borrow().get_projection();
+        let mut ret = Vec::new();
+        for (d_id, design) in self.designs.iter().enumerate() {
+            let nucls = design.nucls_in_rectangle(corner1, corner2, |point| {
+                super::maths_3d::project_point(point, camera.clone(), projection.clone())
+            });
+            ret.extend(
+                nucls
+                    .into_iter()
+                    .map(|e_id| SceneElement::DesignElement(d_id as u32, e_id)),
+            );
+        }
+        ret
+    }
+
+    /// Select all the elements of `elements`. If `adding` is true, they are added to the current
+    /// selection, otherwise they replace it. Return the new selection.
+    pub fn select_elements(&mut self, elements: Vec<SceneElement>, adding: bool) -> Vec<Selection> {
+        if !adding {
+            self.selection.clear();
+            self.selected_element = None;
+        }
+        self.sub_selection_mode = SelectionMode::Nucleotide;
+        for element in elements.iter() {
+            let selection = self.element_to_selection(element, self.selection_mode);
+            if selection != Selection::Nothing && !self.selection.contains(&selection) {
+                self.selection.push(selection);
+            }
+        }
+        if let Some(element) = elements.last() {
+            self.selected_element = Some(*element);
+        }
+        self.update_selected_position();
+        self.selection_update = true;
+        self.selection.clone()
+    }
+
     pub fn add_to_selection(&mut self, element: Option<SceneElement>) -> Option<Vec<Selection>> {
         if let Some(SceneElement::WidgetElement(_)) = element {
             return None;
@@ -904,6 +908,16 @@ impl Data {
         self.candidates = future_candidates;
     }
 
+    pub fn notify_secondary_selection(&mut self, selection: Vec<Selection>) {
+        let future_selection = selection
+            .iter()
+            .filter(|s| s.get_design().is_some())
+            .cloned()
+            .collect();
+        self.selection_update |= self.secondary_selection != future_selection;
+        self.secondary_selection = future_selection;
+    }
+
     pub fn notify_selection(&mut self, selection: Vec<Selection>) {
         let future_selection = selection
             .iter()
@@ -1027,6 +1041,46 @@ impl Data {
         self.instance_update = true;
     }
 
+    /// This function must be called when some strands of a design have been modified without
+    /// modifying the identifiers of their elements
+    pub fn notify_strands_update(&mut self, design_id: u32, strands: HashSet<usize>) {
+        self.strands_update
+            .entry(design_id)
+            .or_insert_with(HashSet::new)
+            .extend(strands);
+    }
+
+    /// Notify the view that the instances of some strands have been modified. Only these instances
+    /// are sent to the view, together with the letters and the 3' ends, whose positions may have
+    /// changed.
+    fn update_strands_instances(&mut self) {
+        let mut spheres = Vec::new();
+        let mut tubes = Vec::new();
+        let mut letters = Vec::new();
+        let mut cones = Vec::new();
+        for design in self.designs.iter() {
+            if let Some(strands) = self.strands_update.get(&design.get_id()) {
+                let (design_spheres, design_tubes) = design.get_updated_raw_instances(strands);
+                spheres.extend(design_spheres);
+                tubes.extend(design_tubes);
+            }
+            letters = design.get_letter_instances();
+            cones.extend(design.get_all_prime3_cone());
+            cones.extend(design.get_force_arrows());
+        }
+        self.strands_update.clear();
+        self.view.borrow_mut().update(ViewUpdate::Letter(letters));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::Prime3Cone, Rc::new(cones)));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDnaPartial(Mesh::Tube, Rc::new(tubes)));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDnaPartial(Mesh::Sphere, Rc::new(spheres)));
+    }
+
     /// Notify the view that the set of instances have been modified.
     fn update_instances(&mut self) {
         let mut spheres = Vec::with_capacity(self.get_number_spheres());
@@ -1039,13 +1093,13 @@ impl Data {
         let mut letters = Vec::new();
         let mut grids = Vec::new();
         let mut cones = Vec::new();
-        for design in self.designs.iter() {
-            for sphere in design.get_spheres_raw().iter() {
-                spheres.push(*sphere);
-            }
-            for tube in design.get_tubes_raw().iter() {
-                tubes.push(*tube);
-            }
+        let base_colors = self.view.borrow().get_base_colors();
+        for design in self.designs.iter_mut() {
+            design.set_base_colors(base_colors);
+            let (design_spheres, design_tubes) =
+                design.get_raw_instances_indexed(spheres.len(), tubes.len());
+            spheres.extend(design_spheres);
+            tubes.extend(design_tubes);
             letters = design.get_letter_instances();
             for grid in design.get_grid().iter().filter(|g| g.visible) {
                 grids.push(grid.clone());
@@ -1066,6 +1120,7 @@ impl Data {
             for cone in design.get_all_prime3_cone() {
                 cones.push(cone);
             }
+            cones.extend(design.get_force_arrows());
         }
         self.update_free_xover();
         self.view
@@ -1151,6 +1206,13 @@ impl Data {
         for design in self.designs.iter() {
             matrices.push(design.get_model_matrix());
         }
+        let fog_follow_nucl = self.view.borrow().get_fog_follow_nucl();
+        if let Some((design_id, nucl)) = fog_follow_nucl {
+            let position = self.get_nucl_position(nucl, design_id);
+            self.view
+                .borrow_mut()
+                .update(ViewUpdate::FogFollowPosition(position));
+        }
         self.view
             .borrow_mut()
             .update(ViewUpdate::ModelMatrices(matrices));
@@ -1341,6 +1403,11 @@ impl Data {
         }
     }
 
+    /// Return the origin and direction of an helix axis in the world coordinates
+    pub fn get_helix_axis(&self, h_id: usize, design_id: usize) -> Option<(Vec3, Vec3)> {
+        self.designs.get(design_id)?.get_helix_axis(h_id)
+    }
+
     pub fn get_nucl_position(&self, nucl: Nucl, design_id: usize) -> Option<Vec3> {
         let design = self.designs.get(design_id)?;
         design.get_nucl_position(nucl)
@@ -22,7 +22,7 @@ use super::super::view::{
 use super::super::GridInstance;
 use super::{LetterInstance, SceneElement, StrandBuilder};
 use crate::consts::*;
-use crate::design::{Design, Nucl, ObjectType, Referential};
+use crate::design::{Axis, Design, Nucl, ObjectType, Referential};
 use crate::utils;
 use crate::utils::instance::Instance;
 use std::collections::{HashMap, HashSet};
@@ -35,6 +35,14 @@ pub struct Design3D {
     design: Arc<RwLock<Design>>,
     id: u32,
     symbol_map: HashMap<char, usize>,
+    /// Maps the identifier of the nucleotides to the index of their instance in the spheres
+    /// buffer
+    sphere_indices: HashMap<u32, usize>,
+    /// Maps the identifier of the bounds to the index of their instance in the tubes buffer
+    tube_indices: HashMap<u32, usize>,
+    /// If some, the colors of the A, T, G and C bases used to draw the nucleotides instead of
+    /// the color of their strand
+    base_colors: Option<[u32; 4]>,
 }
 
 impl Design3D {
@@ -48,9 +56,34 @@ impl Design3D {
             design,
             id,
             symbol_map,
+            sphere_indices: HashMap::new(),
+            tube_indices: HashMap::new(),
+            base_colors: None,
         }
     }
 
+    /// Set the colors of the A, T, G and C bases used to draw the nucleotides. If `None`, the
+    /// nucleotides are drawn with the color of their strand.
+    pub fn set_base_colors(&mut self, base_colors: Option<[u32; 4]>) {
+        self.base_colors = base_colors;
+    }
+
+    /// Return the color of the base of nucleotide `id` in the `base_colors` scheme
+    fn get_base_color(&self, id: u32, base_colors: [u32; 4]) -> u32 {
+        let symbol = self.design.read().unwrap().get_symbol(id);
+        match symbol.map(|c| c.to_ascii_uppercase()) {
+            Some('A') => base_colors[0],
+            Some('T') => base_colors[1],
+            Some('G') => base_colors[2],
+            Some('C') => base_colors[3],
+            _ => UNASSIGNED_BASE_COLOR,
+        }
+    }
+
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
     /*
     /// Convert a list of ids into a list of instances
     pub fn id_to_instances(&self, ids: Vec<u32>) -> Vec<Instance> {
@@ -82,6 +115,68 @@ impl Design3D {
     }
     */
 
+    /// Convert a list of ids into a list of instances, and return the index of each instance
+    /// shifted by `offset`.
+    fn id_to_indexed_raw_instances(
+        &self,
+        ids: Vec<u32>,
+        offset: usize,
+    ) -> (Vec<RawDnaInstance>, HashMap<u32, usize>) {
+        let mut ret = Vec::new();
+        let mut indices = HashMap::new();
+        for id in ids.iter() {
+            if let Some(instance) = self.make_raw_instance(*id) {
+                indices.insert(*id, offset + ret.len());
+                ret.push(instance)
+            }
+        }
+        (ret, indices)
+    }
+
+    /// Return the lists of raw sphere and tube instances to be displayed to represent the design.
+    ///
+    /// The position of each instance in the instances buffers, assuming that the returned
+    /// instances start at index `sphere_offset` and `tube_offset` respectively, is stored so that
+    /// the instances can later be updated individually.
+    pub fn get_raw_instances_indexed(
+        &mut self,
+        sphere_offset: usize,
+        tube_offset: usize,
+    ) -> (Vec<RawDnaInstance>, Vec<RawDnaInstance>) {
+        let nucl_ids = self.design.read().unwrap().get_all_visible_nucl_ids();
+        let bound_ids = self.design.read().unwrap().get_all_visible_bound_ids();
+        let (spheres, sphere_indices) = self.id_to_indexed_raw_instances(nucl_ids, sphere_offset);
+        let (tubes, tube_indices) = self.id_to_indexed_raw_instances(bound_ids, tube_offset);
+        self.sphere_indices = sphere_indices;
+        self.tube_indices = tube_indices;
+        (spheres, tubes)
+    }
+
+    /// Return the new sphere and tube instances of the elements of the strands in `strands`,
+    /// together with their index in the instances buffers.
+    pub fn get_updated_raw_instances(
+        &self,
+        strands: &HashSet<usize>,
+    ) -> (Vec<(usize, RawDnaInstance)>, Vec<(usize, RawDnaInstance)>) {
+        let mut spheres = Vec::new();
+        let mut tubes = Vec::new();
+        for s_id in strands.iter() {
+            let elements = self.design.read().unwrap().get_strand_elements(*s_id);
+            for id in elements {
+                if let Some(idx) = self.sphere_indices.get(&id) {
+                    if let Some(instance) = self.make_raw_instance(id) {
+                        spheres.push((*idx, instance));
+                    }
+                } else if let Some(idx) = self.tube_indices.get(&id) {
+                    if let Some(instance) = self.make_raw_instance(id) {
+                        tubes.push((*idx, instance));
+                    }
+                }
+            }
+        }
+        (spheres, tubes)
+    }
+
     /// Return the list of raw sphere instances to be displayed to represent the design
     pub fn get_spheres_raw(&self) -> Rc<Vec<RawDnaInstance>> {
         let ids = self.design.read().unwrap().get_all_visible_nucl_ids();
@@ -194,6 +289,24 @@ impl Design3D {
         Some(instanciable)
     }
 
+    /// Return the raw instances that are drawn to represent a nucleotide: its sphere followed by
+    /// the tubes that link it to its neighbours.
+    pub fn instances_for_nucl(&self, nucl: Nucl) -> Vec<RawDnaInstance> {
+        let mut ret = Vec::new();
+        if let Some(id) = self.get_identifier_nucl(&nucl) {
+            ret.extend(self.make_raw_instance(id));
+            let bound_ids = self.design.read().unwrap().get_all_bound_ids();
+            for bound_id in bound_ids {
+                if let Some(ObjectType::Bound(id1, id2)) = self.get_object_type(bound_id) {
+                    if id1 == id || id2 == id {
+                        ret.extend(self.make_raw_instance(bound_id));
+                    }
+                }
+            }
+        }
+        ret
+    }
+
     /// Convert return an instance representing the object with identifier `id`
     pub fn make_raw_instance(&self, id: u32) -> Option<RawDnaInstance> {
         let kind = self.get_object_type(id)?;
@@ -209,7 +322,11 @@ impl Design3D {
             }
             ObjectType::Nucleotide(id) => {
                 let position = self.get_design_element_position(id, referential)?;
-                let color = self.get_color(id)?;
+                let color = if let Some(base_colors) = self.base_colors {
+                    self.get_base_color(id, base_colors)
+                } else {
+                    self.get_color(id)?
+                };
                 let color = Instance::color_from_u32(color);
                 let id = id | self.id << 24;
                 let small = self.design.read().unwrap().has_small_spheres_nucl_id(id);
@@ -586,6 +703,62 @@ impl Design3D {
         [min_x, max_x, min_y, max_y, min_z, max_z]
     }
 
+    /// Return the minimum and maximum corners of the axis aligned box containing all the
+    /// nucleotides of the design, in the world coordinates. Return `None` if the design has no
+    /// nucleotide.
+    pub fn bounding_box(&self) -> Option<(Vec3, Vec3)> {
+        let design = self.design.read().unwrap();
+        let mut positions = design
+            .get_all_nucl_ids()
+            .into_iter()
+            .filter_map(|id| design.get_element_position(id, Referential::World));
+        let first = positions.next()?;
+        Some(positions.fold((first, first), |(min, max), pos| {
+            (min.min_by_component(pos), max.max_by_component(pos))
+        }))
+    }
+
+    /// Return the axis of the helix that would be built at position (`x`, `y`) of grid `g_id`. The
+    /// direction of the axis is the vector between two consecutive nucleotides.
+    pub fn get_grid_helix_axis(&self, g_id: usize, x: isize, y: isize) -> Option<Axis> {
+        let design = self.design.read().unwrap();
+        let origin = design.get_grid_latice_position(g_id, x, y)?;
+        let orientation = design.get_grid_basis(g_id)?;
+        let z_step = design.get_dna_parameters().z_step;
+        Some(Axis {
+            origin,
+            direction: orientation * (z_step * Vec3::unit_x()),
+        })
+    }
+
+    /// Return the identifiers of the visible nucleotides whose projection on the screen lies in
+    /// the rectangle with opposite corners `corner1` and `corner2`.
+    ///
+    /// `project` maps a point in the world coordinates to its position on the screen, expressed
+    /// in the same coordinates as the corners.
+    pub fn nucls_in_rectangle<F: Fn(Vec3) -> Option<(f32, f32)>>(
+        &self,
+        corner1: (f32, f32),
+        corner2: (f32, f32),
+        project: F,
+    ) -> Vec<u32> {
+        let (min_x, max_x) = (corner1.0.min(corner2.0), corner1.0.max(corner2.0));
+        let (min_y, max_y) = (corner1.1.min(corner2.1), corner1.1.max(corner2.1));
+        let design = self.design.read().unwrap();
+        design
+            .get_all_visible_nucl_ids()
+            .into_iter()
+            .filter(|id| {
+                design
+                    .get_element_position(*id, Referential::World)
+                    .and_then(&project)
+                    .map_or(false, |(x, y)| {
+                        min_x <= x && x <= max_x && min_y <= y && y <= max_y
+                    })
+            })
+            .collect()
+    }
+
     fn get_all_grid_corners(&self) -> Vec<Vec3> {
         let mut ret = Vec::new();
         for grid in self.get_grid().iter() {
@@ -683,6 +856,15 @@ impl Design3D {
             .collect()
     }
 
+    /// Return the origin and direction of an helix axis in the world coordinates
+    pub fn get_helix_axis(&self, h_id: usize) -> Option<(Vec3, Vec3)> {
+        self.design
+            .read()
+            .unwrap()
+            .get_helix_axis(h_id, Referential::World)
+            .map(|axis| (axis.origin, axis.direction))
+    }
+
     pub fn get_helix_basis(&self, h_id: u32) -> Option<Rotor3> {
         self.design.read().unwrap().get_helix_basis(h_id)
     }
@@ -768,6 +950,10 @@ impl Design3D {
             .unwrap_or(Vec::new())
     }
 
+    pub fn get_design(&self) -> Arc<RwLock<Design>> {
+        self.design.clone()
+    }
+
     pub fn get_helix_grid(&self, g_id: usize, x: isize, y: isize) -> Option<u32> {
         self.design.read().unwrap().get_helix_grid(g_id, x, y)
     }
@@ -847,6 +1033,29 @@ impl Design3D {
         prime5_1.and(prime5_2).is_some()
     }
 
+    /// Return the arrows representing the forces applied on the elements of the running
+    /// simulation
+    pub fn get_force_arrows(&self) -> Vec<RawDnaInstance> {
+        self.design
+            .read()
+            .unwrap()
+            .get_simulation_forces()
+            .into_iter()
+            .filter(|(_, force)| force.mag() > 1e-5)
+            .map(|(position, force)| {
+                ConeInstance {
+                    position,
+                    length: FORCE_ARROW_SCALE * force.mag(),
+                    rotor: rotor_from_x_axis(force.normalized()),
+                    color: Instance::color_from_u32(FORCE_ARROW_COLOR),
+                    id: 0,
+                    radius: SPHERE_RADIUS,
+                }
+                .to_raw_instance()
+            })
+            .collect()
+    }
+
     pub fn get_all_prime3_cone(&self) -> Vec<RawDnaInstance> {
         let cones = self.design.read().unwrap().get_prime3_set();
         let mut ret = Vec::with_capacity(cones.len());
@@ -883,6 +1092,16 @@ fn create_dna_bound(
     }
 }
 
+/// Return the rotation that maps the x axis on the unit vector `direction`.
+fn rotor_from_x_axis(direction: Vec3) -> Rotor3 {
+    if direction.x < -0.9999 {
+        // The rotation between two opposite vectors is not well defined
+        Rotor3::from_rotation_xy(std::f32::consts::PI)
+    } else {
+        Rotor3::from_rotation_between(Vec3::unit_x(), direction)
+    }
+}
+
 fn create_prime3_cone(source: Vec3, dest: Vec3, color: u32) -> RawDnaInstance {
     let color = Instance::color_from_u32(color);
     let rotor = Rotor3::from_rotation_between(Vec3::unit_x(), (dest - source).normalized());
@@ -898,3 +1117,146 @@ fn create_prime3_cone(source: Vec3, dest: Vec3, color: u32) -> RawDnaInstance {
     }
     .to_raw_instance()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::fixtures::design_two_strands;
+    use crate::design::DesignNotificationContent;
+
+    #[test]
+    fn color_change_only_updates_strand_instances() {
+        let design = Arc::new(RwLock::new(design_two_strands()));
+        let mut design3d = Design3D::new(design.clone());
+        design.read().unwrap().data_was_updated();
+        let (spheres, tubes) = design3d.get_raw_instances_indexed(0, 0);
+
+        design.write().unwrap().change_strand_color(1, 0xFF00FF00);
+        let strands = match design.read().unwrap().data_was_updated().map(|n| n.content) {
+            Some(DesignNotificationContent::StrandsChanged(strands)) => strands,
+            _ => panic!("expected a localized update"),
+        };
+        assert_eq!(strands, vec![1].into_iter().collect());
+
+        let (updated_spheres, updated_tubes) = design3d.get_updated_raw_instances(&strands);
+        let strand_elements = design3d.get_strand_elements(1);
+        let expected_spheres: HashSet<usize> = strand_elements
+            .iter()
+            .filter_map(|e| design3d.sphere_indices.get(e).cloned())
+            .collect();
+        let expected_tubes: HashSet<usize> = strand_elements
+            .iter()
+            .filter_map(|e| design3d.tube_indices.get(e).cloned())
+            .collect();
+        assert!(!expected_spheres.is_empty());
+        assert!(expected_spheres.len() < spheres.len());
+        assert!(expected_tubes.len() < tubes.len());
+        assert_eq!(
+            updated_spheres
+                .iter()
+                .map(|(i, _)| *i)
+                .collect::<HashSet<_>>(),
+            expected_spheres
+        );
+        assert_eq!(
+            updated_tubes
+                .iter()
+                .map(|(i, _)| *i)
+                .collect::<HashSet<_>>(),
+            expected_tubes
+        );
+    }
+
+    #[test]
+    fn nucl_instances_are_at_nucl_position() {
+        let design = Arc::new(RwLock::new(design_two_strands()));
+        let design3d = Design3D::new(design.clone());
+        design.read().unwrap().data_was_updated();
+        let nucl = Nucl::new(1, 4, true);
+        let instances = design3d.instances_for_nucl(nucl);
+        // One sphere and the two tubes linking the nucleotide to its neighbours
+        assert_eq!(instances.len(), 3);
+        let expected = design
+            .read()
+            .unwrap()
+            .get_helix_nucl(nucl, Referential::Model, false)
+            .unwrap();
+        let translation = instances[0].model.cols[3].xyz();
+        assert!((translation - expected).mag() < 1e-5);
+    }
+
+    #[test]
+    fn bounding_box_contains_helix_ends() {
+        let design = Arc::new(RwLock::new(design_two_strands()));
+        let design3d = Design3D::new(design.clone());
+        design.read().unwrap().data_was_updated();
+        let (min, max) = design3d.bounding_box().unwrap();
+        for position in [0, 9].iter() {
+            let nucl = Nucl::new(1, *position, true);
+            let pos = design
+                .read()
+                .unwrap()
+                .get_helix_nucl(nucl, Referential::World, false)
+                .unwrap();
+            assert!(min.x <= pos.x && pos.x <= max.x);
+            assert!(min.y <= pos.y && pos.y <= max.y);
+            assert!(min.z <= pos.z && pos.z <= max.z);
+        }
+        assert!(max.x - min.x > 0.);
+    }
+
+    #[test]
+    fn nucleotides_colored_by_base() {
+        let design = Arc::new(RwLock::new(design_two_strands()));
+        design
+            .write()
+            .unwrap()
+            .change_strand_sequence(0, "AAAAAAAAAA".to_string());
+        design.read().unwrap().data_was_updated();
+        let mut design3d = Design3D::new(design.clone());
+        design3d.set_base_colors(Some(DEFAULT_BASE_COLORS));
+
+        let assigned = design3d.instances_for_nucl(Nucl::new(1, 4, true));
+        assert_eq!(
+            assigned[0].color,
+            Instance::color_from_u32(DEFAULT_BASE_COLORS[0])
+        );
+        let unassigned = design3d.instances_for_nucl(Nucl::new(2, 4, false));
+        assert_eq!(
+            unassigned[0].color,
+            Instance::color_from_u32(UNASSIGNED_BASE_COLOR)
+        );
+    }
+
+    #[test]
+    fn nucleotides_in_rectangle_are_the_projected_ones() {
+        let design = Arc::new(RwLock::new(design_two_strands()));
+        let design3d = Design3D::new(design.clone());
+        design.read().unwrap().data_was_updated();
+        let nb_nucls = design.read().unwrap().get_all_nucl_ids().len();
+        // Project the nucleotides on the plane z = 0, scaled down to fit in the unit square.
+        let (min, max) = design3d.bounding_box().unwrap();
+        let size = (max - min).mag();
+        let project = |p: Vec3| Some(((p.x - min.x) / size, (p.y - min.y) / size));
+
+        let all = design3d.nucls_in_rectangle((0., 0.), (1., 1.), project);
+        assert_eq!(all.len(), nb_nucls);
+        // Only the nucleotides at the beginning of the helices
+        let half_x = (max.x - min.x) / (2. * size);
+        let first_half = design3d.nucls_in_rectangle((half_x, 1.), (0., 0.), project);
+        assert!(!first_half.is_empty());
+        assert!(first_half.len() < nb_nucls);
+        assert!(design3d
+            .nucls_in_rectangle((2., 2.), (3., 3.), project)
+            .is_empty());
+    }
+
+    #[test]
+    fn arrow_can_point_backward() {
+        let rotor = rotor_from_x_axis(-Vec3::unit_x());
+        assert!((Vec3::unit_x().rotated_by(rotor) + Vec3::unit_x()).mag() < 1e-5);
+        let direction = Vec3::new(1., 2., -3.).normalized();
+        let rotor = rotor_from_x_axis(direction);
+        assert!((Vec3::unit_x().rotated_by(rotor) - direction).mag() < 1e-5);
+    }
+}
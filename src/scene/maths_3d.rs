@@ -16,9 +16,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use super::{
-    camera::{CameraPtr, ProjectionPtr},
+    camera::{CameraPtr, ProjectionKind, ProjectionPtr},
     Vec3,
 };
+use crate::design::Axis;
 
 /// Use to compute the shortes line between two lines in 3D.
 /// Let P1, P2, P3, P4 be 4 points.
@@ -50,8 +51,7 @@ pub fn unproject_point_on_line(
     x_ndc: f32,
     y_ndc: f32,
 ) -> Option<Vec3> {
-    let p1 = camera.borrow().position;
-    let p2 = ndc_to_world(x_ndc, y_ndc, camera, projection);
+    let (p1, p2) = ndc_to_ray(x_ndc, y_ndc, camera, projection);
 
     let p3 = objective_origin;
     let p4 = objective_origin + objective_direction;
@@ -65,6 +65,29 @@ pub fn unproject_point_on_line(
     }
 }
 
+/// Return the integer `n` such that `axis.origin + n * axis.direction` is the point of `axis`
+/// that is the closest to the ray going from the camera through the point (`mouse_x`, `mouse_y`)
+/// of the screen.
+pub fn project_cursor_on_axis(
+    axis: &Axis,
+    camera: CameraPtr,
+    projection: ProjectionPtr,
+    mouse_x: f64,
+    mouse_y: f64,
+) -> Option<isize> {
+    let p1 = unproject_point_on_line(
+        axis.origin,
+        axis.direction,
+        camera,
+        projection,
+        mouse_x as f32,
+        mouse_y as f32,
+    )?;
+
+    let sign = (p1 - axis.origin).dot(axis.direction).signum();
+    Some(((p1 - axis.origin).mag() * sign / axis.direction.mag()).round() as isize)
+}
+
 /// Shoot a ray from the camera and compute its intersection with the plane P: (p- p0).dot(n) - 0
 /// if the intersection if the point p, the return value is the coordinates of the point p.
 /// If the line and the plane are parallel, None is returned
@@ -76,8 +99,7 @@ pub fn unproject_point_on_plane(
     x_ndc: f32,
     y_ndc: f32,
 ) -> Option<Vec3> {
-    let p1 = camera.borrow().position;
-    let p2 = ndc_to_world(x_ndc, y_ndc, camera, projection);
+    let (p1, p2) = ndc_to_ray(x_ndc, y_ndc, camera, projection);
 
     let dir = p2 - p1;
 
@@ -90,20 +112,33 @@ pub fn unproject_point_on_plane(
     }
 }
 
-/// Convert a point on the screen into a point in the world. Usefull for casting rays
-fn ndc_to_world(x_ndc: f32, y_ndc: f32, camera: CameraPtr, projection: ProjectionPtr) -> Vec3 {
+/// Return two points of the ray that goes through a point on the screen. The first one is in the
+/// plane of the camera and the second one is one unit in front of it.
+fn ndc_to_ray(
+    x_ndc: f32,
+    y_ndc: f32,
+    camera: CameraPtr,
+    projection: ProjectionPtr,
+) -> (Vec3, Vec3) {
     let x_screen = 2. * x_ndc - 1.;
     let y_screen = 1. - 2. * y_ndc;
 
-    let p1 = camera.borrow().position;
-    let p2 = {
-        let correction = (projection.borrow().get_fovy() / 2.).tan();
-        let right = camera.borrow().right_vec() * correction;
-        let up = camera.borrow().up_vec() * correction;
-        let direction = camera.borrow().direction();
-        p1 + right * x_screen * projection.borrow().get_ratio() + up * y_screen + direction
-    };
-    p2
+    let camera = camera.borrow();
+    let projection = projection.borrow();
+    let correction = (projection.get_fovy() / 2.).tan();
+    let right = camera.right_vec() * correction;
+    let up = camera.up_vec() * correction;
+    let offset = right * x_screen * projection.get_ratio() + up * y_screen;
+    match projection.get_kind() {
+        ProjectionKind::Perspective => (
+            camera.position,
+            camera.position + offset + camera.direction(),
+        ),
+        ProjectionKind::Orthographic { distance } => {
+            let origin = camera.position + offset * distance;
+            (origin, origin + camera.direction())
+        }
+    }
 }
 
 pub fn cast_ray(
@@ -112,8 +147,34 @@ pub fn cast_ray(
     camera: CameraPtr,
     projection: ProjectionPtr,
 ) -> (Vec3, Vec3) {
-    let target = ndc_to_world(x_ndc, y_ndc, camera.clone(), projection);
-    (camera.borrow().position, target - camera.borrow().position)
+    let (origin, target) = ndc_to_ray(x_ndc, y_ndc, camera, projection);
+    (origin, target - origin)
+}
+
+/// Return the normalized screen coordinates of the projection of `point`, with (0, 0) being the
+/// top left corner of the screen and (1, 1) the bottom right one. This is the inverse of
+/// `cast_ray`. Return `None` if the point is behind the camera.
+pub fn project_point(
+    point: Vec3,
+    camera: CameraPtr,
+    projection: ProjectionPtr,
+) -> Option<(f32, f32)> {
+    let camera = camera.borrow();
+    let projection = projection.borrow();
+    let relative = camera
+        .get_basis()
+        .convert_point_to_self(point - camera.position);
+    let correction = (projection.get_fovy() / 2.).tan();
+    let distance = match projection.get_kind() {
+        ProjectionKind::Perspective => -relative.z,
+        ProjectionKind::Orthographic { distance } => distance,
+    };
+    if distance <= 0. || relative.z >= 0. {
+        return None;
+    }
+    let x_screen = relative.x / (distance * correction * projection.get_ratio());
+    let y_screen = relative.y / (distance * correction);
+    Some(((x_screen + 1.) / 2., (1. - y_screen) / 2.))
 }
 
 pub struct UnalignedBoundaries {
@@ -189,6 +250,20 @@ impl UnalignedBoundaries {
         }
     }
 
+    /// Return the length of the boundaries along each vector of the basis
+    pub fn lengths(&self) -> Option<Vec3> {
+        let ret = Vec3::new(
+            self.max_x - self.min_x,
+            self.max_y - self.min_y,
+            self.max_z - self.min_z,
+        );
+        if ret.x.is_finite() && ret.y.is_finite() && ret.z.is_finite() {
+            Some(ret)
+        } else {
+            None
+        }
+    }
+
     fn bounding_sphere_radius(&self) -> Option<f32> {
         let lenght_x = self.max_x - self.min_x;
         let length_y = self.max_y - self.min_y;
@@ -211,3 +286,47 @@ impl UnalignedBoundaries {
         Some(middle + x_back.max(10.) * self.basis.unit_z)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::camera::{Camera, Projection};
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use ultraviolet::Rotor3;
+
+    #[test]
+    fn center_ray_points_forward() {
+        let camera = Rc::new(RefCell::new(Camera::new(
+            Vec3::new(1., -2., 3.),
+            Rotor3::from_euler_angles(0.3, -0.5, 1.1),
+        )));
+        let projection = Rc::new(RefCell::new(Projection::new(800, 600, 0.8, 0.1, 100.)));
+        let (origin, direction) = cast_ray(0.5, 0.5, camera.clone(), projection.clone());
+        assert_eq!(origin, camera.borrow().position);
+        let cos = direction
+            .normalized()
+            .dot(camera.borrow().direction().normalized());
+        assert!((cos - 1.).abs() < 1e-5);
+
+        let (_, off_center) = cast_ray(0.9, 0.5, camera.clone(), projection);
+        assert!(off_center.normalized().dot(camera.borrow().right_vec()) > 0.);
+    }
+
+    #[test]
+    fn projected_point_is_on_cast_ray() {
+        let camera = Rc::new(RefCell::new(Camera::new(
+            Vec3::new(1., -2., 3.),
+            Rotor3::from_euler_angles(0.3, -0.5, 1.1),
+        )));
+        let projection = Rc::new(RefCell::new(Projection::new(800, 600, 0.8, 0.1, 100.)));
+        let (origin, direction) = cast_ray(0.2, 0.7, camera.clone(), projection.clone());
+        let point = origin + 12. * direction;
+        let (x, y) = project_point(point, camera.clone(), projection.clone()).unwrap();
+        assert!((x - 0.2).abs() < 1e-4);
+        assert!((y - 0.7).abs() < 1e-4);
+
+        let behind = origin - 3. * direction;
+        assert!(project_point(behind, camera, projection).is_none());
+    }
+}
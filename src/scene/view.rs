@@ -20,13 +20,15 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use super::{camera, ActionMode};
 use crate::consts::*;
-use crate::design::Axis;
-use crate::utils::{bindgroup_manager, texture};
+use crate::design::{Axis, Nucl};
+use crate::utils::{bindgroup_manager, read_texture_rgba, texture};
 use crate::{DrawArea, PhySize};
-use camera::{Camera, CameraPtr, Projection, ProjectionPtr};
+use camera::{Axis6, Camera, CameraPtr, Projection, ProjectionKind, ProjectionPtr};
 use iced_wgpu::wgpu;
+use iced_winit::winit::dpi::PhysicalPosition;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 use texture::Texture;
 use ultraviolet::{Mat4, Rotor3, Vec3};
 use wgpu::{Device, Queue};
@@ -35,10 +37,13 @@ use wgpu::{Device, Queue};
 mod uniforms;
 pub use uniforms::FogParameters;
 use uniforms::Uniforms;
+mod camera_flight;
 mod direction_cube;
 mod dna_obj;
 /// This modules defines a trait for drawing widget made of several meshes.
 mod drawable;
+mod fog_transition;
+mod frame_timer;
 mod grid;
 mod grid_disc;
 /// A HandleDrawer draws the widget for translating objects
@@ -47,13 +52,17 @@ mod instances_drawer;
 mod letter;
 /// A RotationWidget draws the widget for rotating objects
 mod rotation_widget;
+mod selection_pulse;
 
 use super::maths_3d;
 use crate::text::Letter;
 use bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
+use camera_flight::CameraFlight;
 use direction_cube::*;
 pub use dna_obj::{ConeInstance, DnaObject, RawDnaInstance, SphereInstance, TubeInstance};
 use drawable::{Drawable, Drawer, Vertex};
+use fog_transition::FogTransition;
+use frame_timer::FrameTimer;
 pub use grid::{GridInstance, GridIntersection, GridTypeDescr};
 use grid::{GridManager, GridTextures};
 pub use grid_disc::GridDisc;
@@ -65,9 +74,13 @@ pub use letter::LetterInstance;
 use maths_3d::unproject_point_on_line;
 use rotation_widget::RotationWidget;
 pub use rotation_widget::{RotationMode, RotationWidgetDescriptor, RotationWidgetOrientation};
+use selection_pulse::SelectionPulse;
 //use plane_drawer::PlaneDrawer;
 //pub use plane_drawer::Plane;
 
+/// The minimal distance used to derive the orthographic projection from the perspective one
+const MIN_ORTHOGRAPHIC_DISTANCE: f32 = 1.;
+
 static MODEL_BG_ENTRY: &'static [wgpu::BindGroupLayoutEntry] = &[wgpu::BindGroupLayoutEntry {
     binding: 0,
     visibility: wgpu::ShaderStage::from_bits_truncate(wgpu::ShaderStage::VERTEX.bits()),
@@ -102,16 +115,22 @@ pub struct View {
     letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
     helix_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
     device: Rc<Device>,
+    queue: Rc<Queue>,
     /// A bind group associated to the uniform buffer containing the view and projection matrices.
     //TODO this is currently only passed to the widgets, it could be passed to the mesh pipeline as
     //well.
     viewer: UniformBindGroup,
+    /// The direction cube is always drawn with a perspective projection, so it gets its own view
+    /// and projection matrices.
+    direction_cube_viewer: UniformBindGroup,
     models: DynamicBindGroup,
     redraw_twice: bool,
     need_redraw: bool,
     need_redraw_fake: bool,
     draw_letter: bool,
     msaa_texture: Option<wgpu::TextureView>,
+    /// The number of samples used for multisample anti-aliasing
+    sample_count: u32,
     grid_manager: GridManager,
     disc_drawer: InstanceDrawer<GridDisc>,
     dna_drawers: DnaDrawers,
@@ -120,6 +139,30 @@ pub struct View {
     fog_parameters: FogParameters,
     rendering_mode: RenderingMode,
     background3d: Background3D,
+    /// The colors of the A, T, G and C bases in `RenderingMode::ByBase`
+    base_color_scheme: [u32; 4],
+    /// If some, the animation applied to the selected elements
+    selection_pulse: Option<SelectionPulse>,
+    /// If some, the transition towards new fog parameters
+    fog_transition: Option<FogTransition>,
+    /// If some, the movement of the camera towards a target position and orientation
+    camera_flight: Option<CameraFlight>,
+    /// If some, the distance along the camera's direction under which the DNA is not drawn
+    clip_distance: Option<f32>,
+    /// The factors applied to the radius of the spheres and of the tubes representing the DNA
+    dna_radii: (f32, f32),
+    /// The selected spheres and tubes, before the animation is applied to them
+    selected_instances: (Rc<Vec<RawDnaInstance>>, Rc<Vec<RawDnaInstance>>),
+    /// The model matrices of the designs
+    model_matrices: Vec<Mat4>,
+    /// Measures the time between the frames drawn on screen
+    frame_timer: FrameTimer,
+    /// The factor applied to the size of the handles and of the rotation widget
+    widget_scale: f32,
+    /// If some, the nucleotide that the fog center follows, together with its design
+    fog_follow_nucl: Option<(usize, Nucl)>,
+    /// The position of the followed nucleotide in the coordinates of its design
+    fog_follow_position: Option<Vec3>,
 }
 
 impl View {
@@ -146,6 +189,11 @@ impl View {
             queue.clone(),
             &Uniforms::from_view_proj(camera.clone(), projection.clone()),
         );
+        let direction_cube_viewer = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            &Uniforms::from_view_proj(camera.clone(), projection.clone()),
+        );
         let model_bg_desc = wgpu::BindGroupLayoutDescriptor {
             entries: MODEL_BG_ENTRY,
             label: None,
@@ -265,7 +313,9 @@ impl View {
             fake_depth_texture,
             new_size: None,
             device: device.clone(),
+            queue: queue.clone(),
             viewer,
+            direction_cube_viewer,
             models,
             handle_drawers: HandlesDrawer::new(device.clone()),
             rotation_widget: RotationWidget::new(device),
@@ -276,6 +326,7 @@ impl View {
             need_redraw_fake: true,
             draw_letter: false,
             msaa_texture,
+            sample_count: SAMPLE_COUNT,
             grid_manager,
             disc_drawer,
             dna_drawers,
@@ -284,6 +335,18 @@ impl View {
             fog_parameters: FogParameters::new(),
             rendering_mode: Default::default(),
             background3d: Default::default(),
+            base_color_scheme: DEFAULT_BASE_COLORS,
+            selection_pulse: None,
+            fog_transition: None,
+            clip_distance: None,
+            dna_radii: (1., 1.),
+            selected_instances: Default::default(),
+            model_matrices: Vec::new(),
+            frame_timer: Default::default(),
+            widget_scale: 1.,
+            fog_follow_nucl: None,
+            fog_follow_position: None,
+            camera_flight: None,
         }
     }
 
@@ -297,11 +360,18 @@ impl View {
                 self.need_redraw_fake = true;
             }
             ViewUpdate::Camera => {
-                self.viewer.update(&Uniforms::from_view_proj_fog(
-                    self.camera.clone(),
-                    self.projection.clone(),
-                    &self.fog_parameters,
-                ));
+                // The area shown by the orthographic projection follows the zoom of the camera
+                let kind = self.projection.borrow().get_kind();
+                if let ProjectionKind::Orthographic { .. } = kind {
+                    let distance = self.orthographic_distance();
+                    self.projection
+                        .borrow_mut()
+                        .set_kind(ProjectionKind::Orthographic { distance });
+                }
+                self.viewer.update(&self.uniforms());
+                let perspective = Rc::new(RefCell::new(self.projection.borrow().perspective()));
+                self.direction_cube_viewer
+                    .update(&Uniforms::from_view_proj(self.camera.clone(), perspective));
                 self.handle_drawers
                     .update_camera(self.camera.clone(), self.projection.clone());
                 self.need_redraw_fake = true;
@@ -310,14 +380,8 @@ impl View {
                     .new_instances(vec![DirectionCube::new(dist)]);
             }
             ViewUpdate::Fog(fog) => {
-                let fog_center = self.fog_parameters.alt_fog_center.clone();
-                self.fog_parameters = fog;
-                self.fog_parameters.alt_fog_center = fog_center;
-                self.viewer.update(&Uniforms::from_view_proj_fog(
-                    self.camera.clone(),
-                    self.projection.clone(),
-                    &self.fog_parameters,
-                ));
+                self.fog_transition = None;
+                self.set_fog(fog);
             }
             ViewUpdate::Handles(descr) => {
                 self.handle_drawers.update_decriptor(
@@ -336,9 +400,11 @@ impl View {
                 );
                 self.need_redraw_fake = true;
             }
-            ViewUpdate::ModelMatrices(ref matrices) => {
-                self.models.update(matrices.clone().as_slice());
+            ViewUpdate::ModelMatrices(matrices) => {
+                self.models.update(matrices.as_slice());
+                self.model_matrices = matrices;
                 self.need_redraw_fake = true;
+                self.update_fog_follow();
             }
             ViewUpdate::Letter(letter) => {
                 for (i, instance) in letter.into_iter().enumerate() {
@@ -352,17 +418,31 @@ impl View {
             }
             ViewUpdate::Grids(grid) => self.grid_manager.new_instances(grid),
             ViewUpdate::GridDiscs(instances) => self.disc_drawer.new_instances(instances),
-            ViewUpdate::RawDna(mesh, instances) => {
+            ViewUpdate::RawDna(mesh, raw_instances) => {
+                let radius_scale = mesh.radius_scale(self.dna_radii);
+                let instances = if let Some(scale) = radius_scale {
+                    Rc::new(scale_instances(raw_instances.as_ref(), scale))
+                } else {
+                    raw_instances.clone()
+                };
+                match mesh {
+                    Mesh::SelectedSphere => self.selected_instances.0 = instances.clone(),
+                    Mesh::SelectedTube => self.selected_instances.1 = instances.clone(),
+                    _ => (),
+                }
                 self.dna_drawers
                     .get_mut(mesh)
                     .new_instances_raw(instances.as_ref());
                 if let Some(mesh) = mesh.to_fake() {
-                    let mut instances = instances.as_ref().clone();
+                    let mut instances = raw_instances.as_ref().clone();
                     for i in instances.iter_mut() {
                         if i.scale.z < 0.99 {
                             i.scale *= 2.5;
                         }
                     }
+                    if let Some(scale) = radius_scale {
+                        instances = scale_instances(&instances, scale);
+                    }
                     self.need_redraw_fake = true;
                     self.dna_drawers
                         .get_mut(mesh)
@@ -374,14 +454,51 @@ impl View {
                         .new_instances_raw(instances.as_ref());
                 }
             }
+            ViewUpdate::RawDnaPartial(mesh, raw_instances) => {
+                let radius_scale = mesh.radius_scale(self.dna_radii);
+                let mut instances = raw_instances.as_ref().clone();
+                if let Some(scale) = radius_scale {
+                    for (_, i) in instances.iter_mut() {
+                        i.scale *= scale;
+                    }
+                }
+                self.dna_drawers
+                    .get_mut(mesh)
+                    .update_instances_raw(instances.as_slice());
+                if let Some(mesh) = mesh.to_fake() {
+                    let mut instances = raw_instances.as_ref().clone();
+                    for (_, i) in instances.iter_mut() {
+                        if i.scale.z < 0.99 {
+                            i.scale *= 2.5;
+                        }
+                        if let Some(scale) = radius_scale {
+                            i.scale *= scale;
+                        }
+                    }
+                    self.need_redraw_fake = true;
+                    self.dna_drawers
+                        .get_mut(mesh)
+                        .update_instances_raw(instances.as_slice());
+                }
+                if let Some(mesh) = mesh.to_outline() {
+                    self.dna_drawers
+                        .get_mut(mesh)
+                        .update_instances_raw(instances.as_slice());
+                }
+            }
             ViewUpdate::FogCenter(center) => {
                 self.fog_parameters.alt_fog_center = center;
-                self.viewer.update(&Uniforms::from_view_proj_fog(
-                    self.camera.clone(),
-                    self.projection.clone(),
-                    &self.fog_parameters,
-                ));
+                self.viewer.update(&self.uniforms());
             }
+            ViewUpdate::FogFollowPosition(position) => {
+                self.fog_follow_position = position;
+                self.update_fog_follow();
+            }
+            ViewUpdate::ProjectionKind(kind) => {
+                self.projection.borrow_mut().set_kind(kind);
+                self.update(ViewUpdate::Camera);
+            }
+            ViewUpdate::ClipPlane(clip_distance) => self.set_clip_distance(clip_distance),
         }
     }
 
@@ -390,7 +507,229 @@ impl View {
     }
 
     pub fn need_redraw(&self) -> bool {
-        self.need_redraw | self.redraw_twice
+        self.need_redraw
+            | self.redraw_twice
+            | self.selection_pulse.is_some()
+            | self.fog_transition.is_some()
+            | self.camera_flight.is_some()
+    }
+
+    fn set_fog(&mut self, fog: FogParameters) {
+        let fog_center = self.fog_parameters.alt_fog_center.clone();
+        self.fog_parameters = fog;
+        self.fog_parameters.alt_fog_center = fog_center;
+        self.viewer.update(&self.uniforms());
+    }
+
+    /// Make the fog center follow a nucleotide of a design. The position of the nucleotide must
+    /// then be given with `ViewUpdate::FogFollowPosition`.
+    ///
+    /// If `target` is `None`, the fog is centered on the camera.
+    pub fn set_fog_follow_nucl(&mut self, target: Option<(usize, Nucl)>) {
+        self.fog_follow_nucl = target;
+        self.fog_follow_position = None;
+        if target.is_none() {
+            self.update(ViewUpdate::FogCenter(None));
+        }
+    }
+
+    pub fn get_fog_follow_nucl(&self) -> Option<(usize, Nucl)> {
+        self.fog_follow_nucl
+    }
+
+    /// Move the fog center to the world position of the followed nucleotide, if there is one.
+    fn update_fog_follow(&mut self) {
+        if let Some((design_id, _)) = self.fog_follow_nucl {
+            if let Some(position) = self.fog_follow_position {
+                let center = self
+                    .model_matrices
+                    .get(design_id)
+                    .map(|m| m.transform_point3(position))
+                    .unwrap_or(position);
+                self.fog_parameters.alt_fog_center = Some(center);
+                self.viewer.update(&self.uniforms());
+                self.need_redraw = true;
+            }
+        }
+    }
+
+    /// The uniforms of the main viewer, given the current camera, fog and clipping plane.
+    fn uniforms(&self) -> Uniforms {
+        Uniforms::from_view_proj_fog(
+            self.camera.clone(),
+            self.projection.clone(),
+            &self.fog_parameters,
+        )
+        .with_clip_distance(self.clip_distance)
+    }
+
+    /// Set the distance, along the direction of the camera, of a plane in front of which the
+    /// DNA is not drawn. If `clip_distance` is `None`, nothing is clipped.
+    pub fn set_clip_distance(&mut self, clip_distance: Option<f32>) {
+        self.clip_distance = clip_distance;
+        self.viewer.update(&self.uniforms());
+        self.need_redraw = true;
+    }
+
+    /// Change the fog parameters progressively, so that they reach `target` after `duration`.
+    pub fn animate_fog_to(&mut self, target: FogParameters, duration: Duration) {
+        self.fog_transition = Some(FogTransition::new(
+            self.fog_parameters.clone(),
+            target,
+            duration,
+        ));
+        self.need_redraw = true;
+    }
+
+    fn animate_fog(&mut self) {
+        if let Some(transition) = self.fog_transition.as_ref() {
+            let elapsed = transition.elapsed();
+            let fog = transition.parameters_at(elapsed);
+            if transition.is_finished(elapsed) {
+                self.fog_transition = None;
+            }
+            self.set_fog(fog);
+        }
+    }
+
+    /// Move the camera progressively, so that it reaches `position` and `orientation` after
+    /// `duration`.
+    pub fn fly_to(&mut self, position: Vec3, orientation: Rotor3, duration: Duration) {
+        let from = {
+            let camera = self.camera.borrow();
+            (camera.position, camera.rotor)
+        };
+        self.camera_flight = Some(CameraFlight::new(from, (position, orientation), duration));
+        self.need_redraw = true;
+    }
+
+    fn animate_camera(&mut self) {
+        if let Some(flight) = self.camera_flight.as_ref() {
+            let elapsed = flight.elapsed();
+            let (position, rotor) = flight.pose_at(elapsed);
+            if flight.is_finished(elapsed) {
+                self.camera_flight = None;
+            }
+            {
+                let mut camera = self.camera.borrow_mut();
+                camera.position = position;
+                camera.rotor = rotor;
+            }
+            self.update(ViewUpdate::Camera);
+        }
+    }
+
+    /// The time elapsed between the last two frames drawn on screen
+    pub fn last_frame_time(&self) -> Duration {
+        self.frame_timer.last_frame_time()
+    }
+
+    /// The average time between two frames drawn on screen, over the last frames
+    pub fn average_frame_time(&self) -> Duration {
+        self.frame_timer.average_frame_time()
+    }
+
+    /// Set the number of samples used for multisample anti-aliasing. The depth and msaa
+    /// textures, and the pipelines drawing on them, are re-created accordingly.
+    ///
+    /// Return false, and leave the view unchanged, if `sample_count` is not supported by the
+    /// device.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> bool {
+        if !Texture::SUPPORTED_SAMPLE_COUNTS.contains(&sample_count) {
+            return false;
+        }
+        if sample_count == self.sample_count {
+            return true;
+        }
+        self.sample_count = sample_count;
+        let area_size = PhySize::new(
+            self.depth_texture.size.width,
+            self.depth_texture.size.height,
+        );
+        self.create_multisampled_textures(area_size);
+        for drawer in self
+            .letter_drawer
+            .iter_mut()
+            .chain(self.helix_letter_drawer.iter_mut())
+        {
+            drawer.set_sample_count(sample_count);
+        }
+        self.grid_manager.set_sample_count(sample_count);
+        self.disc_drawer.set_sample_count(sample_count);
+        self.dna_drawers.set_sample_count(sample_count);
+        self.direction_cube.set_sample_count(sample_count);
+        self.skybox_cube.set_sample_count(sample_count);
+        self.handle_drawers.set_sample_count(sample_count);
+        self.rotation_widget.set_sample_count(sample_count);
+        self.need_redraw = true;
+        true
+    }
+
+    pub fn get_sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Re-create the textures whose number of samples is `self.sample_count`.
+    fn create_multisampled_textures(&mut self, area_size: PhySize) {
+        self.depth_texture =
+            Texture::create_depth_texture(self.device.as_ref(), &area_size, self.sample_count);
+        self.msaa_texture = if self.sample_count > 1 {
+            Some(crate::utils::texture::Texture::create_msaa_texture(
+                self.device.as_ref(),
+                &area_size,
+                self.sample_count,
+                wgpu::TextureFormat::Bgra8UnormSrgb,
+            ))
+        } else {
+            None
+        };
+    }
+
+    /// Set the factor applied to the size of the handles and of the rotation widget.
+    pub fn set_widget_scale(&mut self, scale: f32) {
+        self.widget_scale = scale;
+        self.handle_drawers
+            .set_scale(scale, self.camera.clone(), self.projection.clone());
+        self.rotation_widget
+            .set_scale(scale, self.camera.clone(), self.projection.clone());
+        self.need_redraw = true;
+        self.need_redraw_fake = true;
+    }
+
+    pub fn get_widget_scale(&self) -> f32 {
+        self.widget_scale
+    }
+
+    /// Turn on or off the pulse animation of the selected elements. `frequency` is the number of
+    /// pulses per second.
+    pub fn set_selection_pulse(&mut self, pulse: bool, frequency: f32) {
+        if pulse {
+            self.selection_pulse = Some(SelectionPulse::new(frequency));
+        } else {
+            self.selection_pulse = None;
+            self.dna_drawers
+                .get_mut(Mesh::SelectedSphere)
+                .new_instances_raw(self.selected_instances.0.as_ref());
+            self.dna_drawers
+                .get_mut(Mesh::SelectedTube)
+                .new_instances_raw(self.selected_instances.1.as_ref());
+        }
+        self.need_redraw = true;
+    }
+
+    /// Scale the selected elements according to the current state of the pulse animation
+    fn animate_selection(&mut self) {
+        if let Some(pulse) = self.selection_pulse.as_ref() {
+            let time = pulse.elapsed();
+            let spheres = pulse.pulsed_instances(self.selected_instances.0.as_ref(), time, true);
+            let tubes = pulse.pulsed_instances(self.selected_instances.1.as_ref(), time, false);
+            self.dna_drawers
+                .get_mut(Mesh::SelectedSphere)
+                .new_instances_raw(&spheres);
+            self.dna_drawers
+                .get_mut(Mesh::SelectedTube)
+                .new_instances_raw(&tubes);
+        }
     }
 
     /// Draw the scene
@@ -403,20 +742,15 @@ impl View {
         action_mode: ActionMode,
     ) {
         let fake_color = draw_type.is_fake();
+        if !fake_color {
+            self.frame_timer.tick(std::time::Instant::now());
+            self.animate_selection();
+            self.animate_fog();
+            self.animate_camera();
+        }
         if let Some(size) = self.new_size.take() {
-            self.depth_texture =
-                Texture::create_depth_texture(self.device.as_ref(), &area.size, SAMPLE_COUNT);
             self.fake_depth_texture = Texture::create_depth_texture(self.device.as_ref(), &size, 1);
-            self.msaa_texture = if SAMPLE_COUNT > 1 {
-                Some(crate::utils::texture::Texture::create_msaa_texture(
-                    self.device.clone().as_ref(),
-                    &area.size,
-                    SAMPLE_COUNT,
-                    wgpu::TextureFormat::Bgra8UnormSrgb,
-                ))
-            } else {
-                None
-            };
+            self.create_multisampled_textures(area.size);
         }
         let clear_color = if fake_color || self.background3d == Background3D::White {
             wgpu::Color {
@@ -638,7 +972,7 @@ impl View {
             );
             self.direction_cube.draw(
                 &mut render_pass,
-                viewer_bind_group,
+                self.direction_cube_viewer.get_bindgroup(),
                 self.models.get_bindgroup(),
             )
         } else if draw_type == DrawType::Grid {
@@ -689,6 +1023,141 @@ impl View {
         }
     }
 
+    /// Render the scene in an offscreen texture of size `width` x `height` and return the RGBA
+    /// values of its pixels, row by row.
+    ///
+    /// The aspect ratio of the projection is set to the one of the image during the rendering and
+    /// restored afterward. Return an error if the rendered texture could not be read.
+    pub fn render_to_image(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, wgpu::BufferAsyncError> {
+        let size = PhySize::new(width, height);
+        let saved_projection = self.projection.borrow().clone();
+        self.projection.borrow_mut().resize(width, height);
+        self.update(ViewUpdate::Camera);
+
+        let depth_texture =
+            Texture::create_depth_texture(self.device.as_ref(), &size, self.sample_count);
+        let msaa_texture = if self.sample_count > 1 {
+            Some(crate::utils::texture::Texture::create_msaa_texture(
+                self.device.as_ref(),
+                &size,
+                self.sample_count,
+                wgpu::TextureFormat::Bgra8UnormSrgb,
+            ))
+        } else {
+            None
+        };
+        let saved_depth_texture = std::mem::replace(&mut self.depth_texture, depth_texture);
+        let saved_msaa_texture = std::mem::replace(&mut self.msaa_texture, msaa_texture);
+        // A pending resize of the drawing area must be applied to the next regular frame.
+        let new_size = self.new_size.take();
+
+        let extent = wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+            label: Some("screenshot texture"),
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let area = DrawArea {
+            position: PhysicalPosition::new(0, 0),
+            size,
+        };
+        self.draw(
+            &mut encoder,
+            &texture_view,
+            DrawType::Scene,
+            area,
+            ActionMode::Normal,
+        );
+
+        let pixels = read_texture_rgba(&self.device, &self.queue, encoder, &texture, extent);
+
+        self.depth_texture = saved_depth_texture;
+        self.msaa_texture = saved_msaa_texture;
+        self.new_size = new_size;
+        *self.projection.borrow_mut() = saved_projection;
+        self.update(ViewUpdate::Camera);
+        pixels
+    }
+
+    /// Switch between a perspective and an orthographic projection. The orthographic projection
+    /// shows the area that the perspective projection shows at the distance between the camera
+    /// and the origin of the world.
+    pub fn set_orthographic(&mut self, orthographic: bool) {
+        let kind = if orthographic {
+            ProjectionKind::Orthographic {
+                distance: self.orthographic_distance(),
+            }
+        } else {
+            ProjectionKind::Perspective
+        };
+        self.update(ViewUpdate::ProjectionKind(kind));
+    }
+
+    /// The distance at which the orthographic projection shows the same area as the
+    /// perspective projection: the distance between the camera and the origin of the world.
+    fn orthographic_distance(&self) -> f32 {
+        self.camera
+            .borrow()
+            .position
+            .mag()
+            .max(MIN_ORTHOGRAPHIC_DISTANCE)
+    }
+
+    /// Move the camera, without changing its orientation, so that the box whose extreme corners
+    /// are `min` and `max` fills the view.
+    pub fn frame_bounding_box(&mut self, min: Vec3, max: Vec3) {
+        let basis = self.camera.borrow().get_basis();
+        let mut boundaries = maths_3d::UnalignedBoundaries::from_basis(basis);
+        for i in 0..8 {
+            boundaries.add_point(Vec3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            ));
+        }
+        let fovy = self.projection.borrow().get_fovy();
+        let ratio = self.projection.borrow().get_ratio();
+        if let Some(position) = boundaries.fit_point(fovy, ratio) {
+            self.camera.borrow_mut().position = position;
+            self.update(ViewUpdate::Camera);
+        }
+    }
+
+    /// Set the distances of the near and far clipping planes of the projection.
+    ///
+    /// `near` is clamped to a small positive value, and `far` to be larger than `near`.
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.projection.borrow_mut().set_clip_planes(near, far);
+        self.update(ViewUpdate::Camera);
+    }
+
+    /// The distances of the near and far clipping planes of the projection
+    pub fn get_clip_planes(&self) -> (f32, f32) {
+        self.projection.borrow().get_clip_planes()
+    }
+
+    /// Set the factors by which the radius of the spheres and tubes representing the DNA are
+    /// multiplied. The new radii are used for the instances received after this call.
+    pub fn set_dna_radii(&mut self, sphere_scale: f32, tube_scale: f32) {
+        self.dna_radii = (sphere_scale, tube_scale);
+    }
+
     /// Get a pointer to the camera
     pub fn get_camera(&self) -> CameraPtr {
         self.camera.clone()
@@ -699,6 +1168,31 @@ impl View {
         self.projection.clone()
     }
 
+    /// Make the camera look along an axis, centered on `origin`. The distance between the camera
+    /// and `origin` is preserved.
+    pub fn look_along_helix(&mut self, origin: Vec3, direction: Vec3) {
+        let distance = (self.camera.borrow().position - origin).mag();
+        self.camera
+            .borrow_mut()
+            .look_along(origin, direction, distance);
+        self.update(ViewUpdate::Camera);
+    }
+
+    /// Make the camera look straight down `axis`, so that the direction cube shows the
+    /// corresponding face. The camera keeps its distance to `pivot`.
+    pub fn snap_camera_to(&mut self, axis: Axis6, pivot: Option<Vec3>) {
+        self.camera.borrow_mut().snap_to(axis, pivot);
+        self.update(ViewUpdate::Camera);
+    }
+
+    /// Remove the roll of the camera so that vertical features appear vertical. Nothing is done if
+    /// the camera looks vertically.
+    pub fn level_camera(&mut self) {
+        if self.camera.borrow_mut().level() {
+            self.update(ViewUpdate::Camera);
+        }
+    }
+
     pub fn set_draw_letter(&mut self, value: bool) {
         self.draw_letter = value;
     }
@@ -779,17 +1273,22 @@ impl View {
         mouse_x: f64,
         mouse_y: f64,
     ) -> Option<isize> {
-        let p1 = unproject_point_on_line(
-            axis.origin,
-            axis.direction,
+        maths_3d::project_cursor_on_axis(
+            axis,
             self.camera.clone(),
             self.projection.clone(),
-            mouse_x as f32,
-            mouse_y as f32,
-        )?;
+            mouse_x,
+            mouse_y,
+        )
+    }
 
-        let sign = (p1 - axis.origin).dot(axis.direction).signum();
-        Some(((p1 - axis.origin).mag() * sign / axis.direction.mag()).round() as isize)
+    /// Return the origin and the normalized direction of the ray going from the camera through
+    /// the point (`x_ndc`, `y_ndc`) of the screen, in world coordinates. The center of the screen
+    /// is (0.5, 0.5).
+    pub fn screen_ray(&self, x_ndc: f32, y_ndc: f32) -> (Vec3, Vec3) {
+        let (origin, direction) =
+            maths_3d::cast_ray(x_ndc, y_ndc, self.camera.clone(), self.projection.clone());
+        (origin, direction.normalized())
     }
 
     pub fn grid_intersection(&self, x_ndc: f32, y_ndc: f32) -> Option<GridIntersection> {
@@ -797,6 +1296,22 @@ impl View {
         self.grid_manager.intersect(ray.0, ray.1)
     }
 
+    /// Return the grid cell under the cursor if it is empty, or the nearest empty cell of the
+    /// same grid otherwise.
+    pub fn grid_intersection_free(
+        &self,
+        x_ndc: f32,
+        y_ndc: f32,
+        design: &crate::design::Design,
+    ) -> Option<GridIntersection> {
+        let intersection = self.grid_intersection(x_ndc, y_ndc)?;
+        let g_id = intersection.grid_id;
+        intersection.nearest_free_cell(
+            |x, y| design.get_helix_grid(g_id, x, y).is_none(),
+            MAX_FREE_CELL_DISTANCE,
+        )
+    }
+
     pub fn set_candidate_grid(&mut self, grids: Vec<(usize, usize)>) {
         self.grid_manager.set_candidate_grid(grids)
     }
@@ -810,6 +1325,21 @@ impl View {
         self.need_redraw = true;
     }
 
+    pub fn set_base_color_scheme(&mut self, scheme: [u32; 4]) {
+        self.base_color_scheme = scheme;
+        self.need_redraw = true;
+    }
+
+    /// Return the colors that must be used for the bases of the nucleotides, if the nucleotides
+    /// must be colored by base.
+    pub fn get_base_colors(&self) -> Option<[u32; 4]> {
+        if self.rendering_mode == RenderingMode::ByBase {
+            Some(self.base_color_scheme)
+        } else {
+            None
+        }
+    }
+
     pub fn background3d(&mut self, bg: Background3D) {
         self.background3d = bg;
         self.need_redraw = true;
@@ -833,8 +1363,18 @@ pub enum ViewUpdate {
     Grids(Rc<Vec<GridInstance>>),
     GridDiscs(Vec<GridDisc>),
     RawDna(Mesh, Rc<Vec<RawDnaInstance>>),
+    /// Some instances of a mesh have been modified. Each instance comes with its index in the
+    /// instances buffer of the mesh.
+    RawDnaPartial(Mesh, Rc<Vec<(usize, RawDnaInstance)>>),
     Fog(FogParameters),
     FogCenter(Option<Vec3>),
+    /// The position, in the coordinates of its design, of the nucleotide followed by the fog
+    /// center
+    FogFollowPosition(Option<Vec3>),
+    /// The kind of projection has been modified
+    ProjectionKind(ProjectionKind),
+    /// The distance of the near clipping plane has been modified
+    ClipPlane(Option<f32>),
 }
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
@@ -875,6 +1415,29 @@ impl Mesh {
         }
     }
 
+    /// If the instances of `self` represent nucleotides or bounds, the factor by which their
+    /// scale must be multiplied given the scales `(sphere_scale, tube_scale)` of the radii.
+    fn radius_scale(&self, (sphere_scale, tube_scale): (f32, f32)) -> Option<Vec3> {
+        match self {
+            Self::Sphere
+            | Self::OutlineSphere
+            | Self::CandidateSphere
+            | Self::SelectedSphere
+            | Self::PhantomSphere
+            | Self::SuggestionSphere
+            | Self::PastedSphere => Some(Vec3::broadcast(sphere_scale)),
+            // The first coordinate of the scale of a tube is its length
+            Self::Tube
+            | Self::OutlineTube
+            | Self::CandidateTube
+            | Self::SelectedTube
+            | Self::PhantomTube
+            | Self::SuggestionTube
+            | Self::PastedTube => Some(Vec3::new(1., tube_scale, tube_scale)),
+            _ => None,
+        }
+    }
+
     fn to_outline(&self) -> Option<Self> {
         match self {
             Self::Sphere => Some(Self::OutlineSphere),
@@ -885,6 +1448,16 @@ impl Mesh {
     }
 }
 
+fn scale_instances(instances: &[RawDnaInstance], scale: Vec3) -> Vec<RawDnaInstance> {
+    instances
+        .iter()
+        .map(|i| RawDnaInstance {
+            scale: i.scale * scale,
+            ..*i
+        })
+        .collect()
+}
+
 struct DnaDrawers {
     sphere: InstanceDrawer<SphereInstance>,
     tube: InstanceDrawer<TubeInstance>,
@@ -971,6 +1544,13 @@ impl DnaDrawers {
         ret
     }
 
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        // In cartoon mode, all the drawers of real textures are returned
+        for drawer in self.reals(RenderingMode::Cartoon) {
+            drawer.set_sample_count(sample_count);
+        }
+    }
+
     pub fn fakes(&mut self) -> Vec<&mut dyn RawDrawer<RawInstance = RawDnaInstance>> {
         vec![&mut self.fake_sphere, &mut self.fake_tube]
     }
@@ -1204,3 +1784,124 @@ impl DrawType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::gpu;
+
+    #[test]
+    #[ignore = "requires a GPU"]
+    fn rendered_image_has_requested_size() {
+        let (device, queue) = gpu();
+        let size = PhySize::new(800, 600);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut view = View::new(size, size, device.clone(), queue.clone(), &mut encoder);
+        queue.submit(Some(encoder.finish()));
+
+        let ratio = view.get_projection().borrow().get_ratio();
+        let image = view.render_to_image(256, 256).unwrap();
+        assert_eq!(image.len(), 256 * 256 * 4);
+        assert_eq!(view.get_projection().borrow().get_ratio(), ratio);
+    }
+
+    #[test]
+    #[ignore = "requires a GPU"]
+    fn msaa_texture_is_dropped_with_single_sample() {
+        let (device, queue) = gpu();
+        let size = PhySize::new(800, 600);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut view = View::new(size, size, device.clone(), queue.clone(), &mut encoder);
+        queue.submit(Some(encoder.finish()));
+
+        assert!(view.set_sample_count(4));
+        assert!(view.msaa_texture.is_some());
+        assert!(!view.set_sample_count(3));
+        assert_eq!(view.get_sample_count(), 4);
+        assert!(view.set_sample_count(1));
+        assert!(view.msaa_texture.is_none());
+        assert_eq!(view.depth_texture.size.width, 800);
+    }
+
+    #[test]
+    fn dna_instances_are_scaled() {
+        let sphere = RawDnaInstance {
+            model: Mat4::identity(),
+            color: ultraviolet::Vec4::one(),
+            scale: Vec3::one(),
+            id: 0,
+        };
+        let tube = RawDnaInstance {
+            scale: Vec3::new(2., 0.3, 0.3),
+            ..sphere
+        };
+        let radii = (1.5, 2.);
+
+        let scale = Mesh::Sphere.radius_scale(radii).unwrap();
+        let spheres = scale_instances(&[sphere], scale);
+        assert_eq!(spheres[0].scale, Vec3::broadcast(1.5));
+
+        let scale = Mesh::SelectedTube.radius_scale(radii).unwrap();
+        let tubes = scale_instances(&[tube], scale);
+        assert_eq!(tubes[0].scale, Vec3::new(2., 0.6, 0.6));
+
+        assert!(Mesh::PivotSphere.radius_scale(radii).is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a GPU"]
+    fn fog_center_follows_nucleotide() {
+        let (device, queue) = gpu();
+        let size = PhySize::new(800, 600);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut view = View::new(size, size, device.clone(), queue.clone(), &mut encoder);
+        queue.submit(Some(encoder.finish()));
+
+        let position = Vec3::new(1., 2., 3.);
+        view.set_fog_follow_nucl(Some((0, Nucl::new(1, 4, true))));
+        view.update(ViewUpdate::FogFollowPosition(Some(position)));
+        view.update(ViewUpdate::ModelMatrices(vec![Mat4::identity()]));
+        assert_eq!(view.fog_parameters.alt_fog_center, Some(position));
+
+        let translation = Vec3::new(10., 0., -5.);
+        view.update(ViewUpdate::ModelMatrices(vec![Mat4::from_translation(
+            translation,
+        )]));
+        assert_eq!(
+            view.fog_parameters.alt_fog_center,
+            Some(position + translation)
+        );
+
+        view.set_fog_follow_nucl(None);
+        assert_eq!(view.fog_parameters.alt_fog_center, None);
+    }
+
+    #[test]
+    fn clip_distance_is_written_in_uniforms() {
+        let camera = Rc::new(RefCell::new(Camera::new(
+            (0.0, 5.0, 10.0),
+            Rotor3::identity(),
+        )));
+        let projection = Rc::new(RefCell::new(Projection::new(
+            800,
+            600,
+            70f32.to_radians(),
+            0.1,
+            1000.0,
+        )));
+        let uniforms = Uniforms::from_view_proj_fog(camera, projection, &FogParameters::new())
+            .with_clip_distance(Some(12.5));
+        let bytes = bytemuck::bytes_of(&uniforms);
+        // Offsets of u_clip_distance and u_make_clip in the uniform block of the shaders
+        let clip_distance = f32::from_ne_bytes([bytes[172], bytes[173], bytes[174], bytes[175]]);
+        let make_clip = u32::from_ne_bytes([bytes[176], bytes[177], bytes[178], bytes[179]]);
+        assert_eq!(clip_distance, 12.5);
+        assert_eq!(make_clip, 1);
+
+        let uniforms = uniforms.with_clip_distance(None);
+        assert_eq!(uniforms.make_clip, 0);
+    }
+}
@@ -0,0 +1,125 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A smooth movement of the camera towards a target position and orientation.
+
+use std::time::{Duration, Instant};
+use ultraviolet::{Bivec3, Rotor3, Vec3};
+
+pub struct CameraFlight {
+    start: Instant,
+    duration: Duration,
+    from: (Vec3, Rotor3),
+    to: (Vec3, Rotor3),
+}
+
+impl CameraFlight {
+    pub fn new(from: (Vec3, Rotor3), to: (Vec3, Rotor3), duration: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            duration,
+            from,
+            to,
+        }
+    }
+
+    /// The time elapsed since the begining of the flight
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        elapsed >= self.duration
+    }
+
+    /// The position and orientation of the camera `elapsed` after the begining of the flight.
+    /// The position is interpolated linearly and the orientation is interpolated spherically.
+    pub fn pose_at(&self, elapsed: Duration) -> (Vec3, Rotor3) {
+        let t = if self.duration.as_secs_f32() > 0. {
+            (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.)
+        } else {
+            1.
+        };
+        let position = self.from.0 + t * (self.to.0 - self.from.0);
+        (position, slerp(self.from.1, self.to.1, t))
+    }
+}
+
+/// Spherical interpolation between two rotors, along the shortest path.
+fn slerp(from: Rotor3, to: Rotor3, t: f32) -> Rotor3 {
+    let a = [from.s, from.bv.xy, from.bv.xz, from.bv.yz];
+    let mut b = [to.s, to.bv.xy, to.bv.xz, to.bv.yz];
+    let mut dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    // `to` and `-to` represent the same rotation, take the closest one.
+    if dot < 0. {
+        for x in b.iter_mut() {
+            *x = -*x;
+        }
+        dot = -dot;
+    }
+    let (wa, wb) = if dot > 0.9995 {
+        // The rotors are almost equal, a linear interpolation is precise enough
+        (1. - t, t)
+    } else {
+        let theta = dot.acos();
+        let sin_theta = theta.sin();
+        (
+            ((1. - t) * theta).sin() / sin_theta,
+            (t * theta).sin() / sin_theta,
+        )
+    };
+    let c: Vec<f32> = a
+        .iter()
+        .zip(b.iter())
+        .map(|(x, y)| wa * x + wb * y)
+        .collect();
+    let norm = c.iter().map(|x| x * x).sum::<f32>().sqrt();
+    Rotor3::new(
+        c[0] / norm,
+        Bivec3::new(c[1] / norm, c[2] / norm, c[3] / norm),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_converges_to_target() {
+        let from = (Vec3::zero(), Rotor3::identity());
+        let to = (
+            Vec3::new(10., -5., 3.),
+            Rotor3::from_euler_angles(0.4, -1.1, 2.),
+        );
+        let flight = CameraFlight::new(from, to, Duration::from_millis(500));
+        let frame = Duration::from_millis(16);
+        let mut last_distance = std::f32::INFINITY;
+        let mut elapsed = Duration::from_millis(0);
+        while !flight.is_finished(elapsed) {
+            let (position, _) = flight.pose_at(elapsed);
+            let distance = (position - to.0).mag();
+            assert!(distance < last_distance);
+            last_distance = distance;
+            elapsed += frame;
+        }
+        let (position, rotor) = flight.pose_at(elapsed);
+        assert!((position - to.0).mag() < 1e-5);
+        let direction = rotor * Vec3::unit_z();
+        let target_direction = to.1 * Vec3::unit_z();
+        assert!((direction - target_direction).mag() < 1e-4);
+    }
+}
@@ -16,6 +16,8 @@ layout(set=0, binding=0) uniform Uniform {
     uint u_make_fog;
     uint u_fog_from_cam;
     vec3 u_fog_center;
+    float u_clip_distance;
+    uint u_make_clip;
 };
 
 const float HALF_LIFE = 10.;
@@ -27,6 +29,10 @@ const vec3 SAND_COLOR = vec3(0.368, 0.360, 0.219);
 const vec3 HORIZON = vec3(0.917, 0.917, 0.917);
 
 void main() {
+    if (u_make_clip > 0 && -(u_view * vec4(v_position, 1.)).z < u_clip_distance) {
+        discard;
+    }
+
     vec3 normal = normalize(v_normal);
     vec3 light_position = abs(v_color.w - 1.) < 1e-3 ? u_camera_position : vec3(0., 0., 1000.);
     vec3 light_dir = normalize(light_position - v_position);
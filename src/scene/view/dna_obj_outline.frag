@@ -17,10 +17,16 @@ layout(set=0, binding=0) uniform Uniform {
     uint u_make_fog;
     uint u_fog_from_cam;
     vec3 u_fog_center;
+    float u_clip_distance;
+    uint u_make_clip;
 };
 
 
 void main() {
+    if (u_make_clip > 0 && -(u_view * vec4(v_position, 1.)).z < u_clip_distance) {
+        discard;
+    }
+
     float visibility;
     if (u_make_fog > 0) {
         float dist;
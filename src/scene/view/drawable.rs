@@ -67,6 +67,8 @@ pub struct Drawer<D: Drawable> {
     fake_vertex_buffer: Option<wgpu::Buffer>,
     index_buffer: wgpu::Buffer,
     primitive_topology: wgpu::PrimitiveTopology,
+    /// The number of samples of the textures on which the object is drawn
+    sample_count: u32,
 }
 
 impl<D: Drawable> Drawer<D> {
@@ -86,6 +88,16 @@ impl<D: Drawable> Drawer<D> {
             pipeline: None,
             pipeline_fake: None,
             primitive_topology: D::primitive_topology(),
+            sample_count: SAMPLE_COUNT,
+        }
+    }
+
+    /// Set the number of samples of the textures on which the object is drawn. The pipeline
+    /// will be re-created on the next draw.
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.pipeline = None;
         }
     }
 
@@ -185,7 +197,7 @@ impl<D: Drawable> Drawer<D> {
             wgpu::BlendState::REPLACE
         };
 
-        let sample_count = if !fake { SAMPLE_COUNT } else { 1 };
+        let sample_count = if !fake { self.sample_count } else { 1 };
 
         let targets = &[wgpu::ColorTargetState {
             format,
@@ -0,0 +1,95 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A smooth transition between two sets of fog parameters.
+
+use super::FogParameters;
+use std::time::{Duration, Instant};
+
+pub struct FogTransition {
+    start: Instant,
+    duration: Duration,
+    from: FogParameters,
+    to: FogParameters,
+}
+
+impl FogTransition {
+    pub fn new(from: FogParameters, to: FogParameters, duration: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            duration,
+            from,
+            to,
+        }
+    }
+
+    /// The time elapsed since the begining of the transition
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        elapsed >= self.duration
+    }
+
+    /// The fog parameters `elapsed` after the begining of the transition. The radius and the
+    /// length of the fog are interpolated linearly, the other parameters are the ones of the
+    /// target.
+    pub fn parameters_at(&self, elapsed: Duration) -> FogParameters {
+        let t = if self.duration.as_secs_f32() > 0. {
+            (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.)
+        } else {
+            1.
+        };
+        FogParameters {
+            radius: self.from.radius + t * (self.to.radius - self.from.radius),
+            length: self.from.length + t * (self.to.length - self.from.length),
+            ..self.to.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fog_approaches_target_monotonically() {
+        let from = FogParameters::new();
+        let to = FogParameters {
+            radius: 50.,
+            length: 2.,
+            active: true,
+            ..FogParameters::new()
+        };
+        let transition = FogTransition::new(from, to, Duration::from_millis(500));
+        let frame = Duration::from_millis(16);
+        let mut last_distance = std::f32::INFINITY;
+        let mut elapsed = Duration::from_millis(0);
+        while !transition.is_finished(elapsed) {
+            let fog = transition.parameters_at(elapsed);
+            let distance = (fog.radius - 50.).abs() + (fog.length - 2.).abs();
+            assert!(distance < last_distance);
+            assert!(fog.active);
+            last_distance = distance;
+            elapsed += frame;
+        }
+        let fog = transition.parameters_at(elapsed);
+        assert_eq!(fog.radius, 50.);
+        assert_eq!(fog.length, 2.);
+    }
+}
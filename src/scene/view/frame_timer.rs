@@ -0,0 +1,91 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Measure the time elapsed between the frames drawn by the view.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The number of frame times over which the average is computed
+const NB_SAMPLES: usize = 60;
+
+#[derive(Default)]
+pub struct FrameTimer {
+    last_frame: Option<Instant>,
+    /// The most recent frame times, the last one being at the back
+    samples: VecDeque<Duration>,
+}
+
+impl FrameTimer {
+    /// Record that a frame is drawn at instant `now`.
+    pub fn tick(&mut self, now: Instant) {
+        if let Some(last_frame) = self.last_frame {
+            self.push_sample(now.saturating_duration_since(last_frame));
+        }
+        self.last_frame = Some(now);
+    }
+
+    fn push_sample(&mut self, frame_time: Duration) {
+        if self.samples.len() == NB_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(frame_time);
+    }
+
+    /// The time elapsed between the last two frames
+    pub fn last_frame_time(&self) -> Duration {
+        self.samples.back().cloned().unwrap_or_default()
+    }
+
+    /// The average time between two frames over the last `NB_SAMPLES` frames
+    pub fn average_frame_time(&self) -> Duration {
+        if self.samples.is_empty() {
+            Duration::default()
+        } else {
+            self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_is_computed_over_samples() {
+        let mut timer = FrameTimer::default();
+        let start = Instant::now();
+        timer.tick(start);
+        assert_eq!(timer.last_frame_time(), Duration::default());
+        timer.tick(start + Duration::from_millis(10));
+        timer.tick(start + Duration::from_millis(40));
+        assert_eq!(timer.last_frame_time(), Duration::from_millis(30));
+        assert_eq!(timer.average_frame_time(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn old_samples_are_forgotten() {
+        let mut timer = FrameTimer::default();
+        let start = Instant::now();
+        timer.tick(start);
+        timer.tick(start + Duration::from_secs(10));
+        for i in 1..=NB_SAMPLES as u64 {
+            timer.tick(start + Duration::from_secs(10) + Duration::from_millis(i));
+        }
+        assert_eq!(timer.average_frame_time(), Duration::from_millis(1));
+    }
+}
@@ -253,6 +253,10 @@ impl GridManager {
         }
     }
 
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        self.drawer.set_sample_count(sample_count);
+    }
+
     /// Draw the instances of the mesh on the render pass
     pub fn draw<'a>(
         &'a mut self,
@@ -322,6 +326,31 @@ pub struct GridIntersection {
     pub y: isize,
 }
 
+impl GridIntersection {
+    /// Return the intersection moved to the nearest cell `(x, y)` for which `is_free(x, y)` is
+    /// true, looking at most `max_distance` cells away in each direction.
+    pub fn nearest_free_cell<F: Fn(isize, isize) -> bool>(
+        &self,
+        is_free: F,
+        max_distance: isize,
+    ) -> Option<Self> {
+        let mut best: Option<(isize, isize, isize)> = None;
+        for dx in -max_distance..=max_distance {
+            for dy in -max_distance..=max_distance {
+                let dist = dx * dx + dy * dy;
+                if best.map(|b| dist < b.0).unwrap_or(true) && is_free(self.x + dx, self.y + dy) {
+                    best = Some((dist, dx, dy));
+                }
+            }
+        }
+        best.map(|(_, dx, dy)| Self {
+            x: self.x + dx,
+            y: self.y + dy,
+            ..self.clone()
+        })
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Debug, Copy)]
 pub struct GridVertex {
@@ -475,3 +504,29 @@ impl Instanciable for GridInstance {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occupied_cell_snaps_to_free_neighbour() {
+        let intersection = GridIntersection {
+            depth: 1.,
+            design_id: 0,
+            grid_id: 0,
+            x: 2,
+            y: 3,
+        };
+        let occupied = [(2, 3), (1, 3), (2, 2), (2, 4)];
+        let free = intersection
+            .nearest_free_cell(|x, y| !occupied.contains(&(x, y)), 5)
+            .unwrap();
+        assert_eq!((free.x, free.y), (3, 3));
+        assert_eq!(free.grid_id, 0);
+
+        let same = intersection.nearest_free_cell(|_, _| true, 5).unwrap();
+        assert_eq!((same.x, same.y), (2, 3));
+        assert!(intersection.nearest_free_cell(|_, _| false, 5).is_none());
+    }
+}
@@ -27,6 +27,8 @@ pub struct HandlesDescriptor {
     pub origin: Vec3,
     pub orientation: HandleOrientation,
     pub size: f32,
+    /// A factor applied to `size`, that allows the user to make the widgets bigger
+    pub scale: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -38,7 +40,7 @@ impl HandlesDescriptor {
     pub fn make_handles(&self, camera: CameraPtr, projection: ProjectionPtr) -> [Handle; 3] {
         let dist = (camera.borrow().position - self.origin).mag();
         let (right, up, dir) = self.make_axis();
-        let length = self.size * dist * (projection.borrow().get_fovy() / 2.).tan();
+        let length = self.scale * self.size * projection.borrow().half_height_at(dist);
         [
             Handle::new(self.origin, right, up, 0xFF0000, RIGHT_HANDLE_ID, length),
             Handle::new(self.origin, up, right, 0xFF00, UP_HANDLE_ID, length),
@@ -102,6 +104,13 @@ impl HandlesDrawer {
         }
     }
 
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        for drawer in self.drawers.iter_mut() {
+            drawer.set_sample_count(sample_count);
+        }
+        self.big_handle_drawer.set_sample_count(sample_count);
+    }
+
     pub fn draw<'a>(
         &'a mut self,
         render_pass: &mut wgpu::RenderPass<'a>,
@@ -135,6 +144,15 @@ impl HandlesDrawer {
         self.update_camera(camera, projection);
     }
 
+    /// Scale the handles by a factor `scale`
+    pub fn set_scale(&mut self, scale: f32, camera: CameraPtr, projection: ProjectionPtr) {
+        if let Some(descriptor) = self.descriptor.as_mut() {
+            descriptor.scale = scale;
+        }
+        self.update_camera(camera, projection);
+        self.select_handle(self.selected);
+    }
+
     pub fn update_camera(&mut self, camera: CameraPtr, projection: ProjectionPtr) {
         self.handles = self
             .descriptor
@@ -296,3 +314,49 @@ impl Drawable for Handle {
         wgpu::PrimitiveTopology::TriangleList
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::camera::{Camera, Projection};
+    use super::*;
+    use std::cell::RefCell;
+
+    /// The distance between the origin of the handle and its tip
+    fn tip_length(handle: &Handle, fake: bool) -> f32 {
+        handle
+            .vertices(fake)
+            .iter()
+            .map(|v| (v.position - handle.origin).dot(handle.direction))
+            .fold(0., f32::max)
+    }
+
+    #[test]
+    fn scale_multiplies_handle_length() {
+        let camera = Rc::new(RefCell::new(Camera::new(
+            (0.0, 5.0, 10.0),
+            Rotor3::identity(),
+        )));
+        let projection = Rc::new(RefCell::new(Projection::new(
+            800,
+            600,
+            70f32.to_radians(),
+            0.1,
+            1000.0,
+        )));
+        let mut descriptor = HandlesDescriptor {
+            origin: Vec3::zero(),
+            orientation: HandleOrientation::Rotor(Rotor3::identity()),
+            size: 0.25,
+            scale: 1.,
+        };
+        let handles = descriptor.make_handles(camera.clone(), projection.clone());
+        descriptor.scale = 2.;
+        let scaled_handles = descriptor.make_handles(camera, projection);
+        for (h, scaled) in handles.iter().zip(scaled_handles.iter()) {
+            for fake in [false, true].iter() {
+                let ratio = tip_length(scaled, *fake) / tip_length(h, *fake);
+                assert!((ratio - 2.).abs() < 1e-4);
+            }
+        }
+    }
+}
@@ -213,6 +213,12 @@ pub struct InstanceDrawer<D: Instanciable + ?Sized> {
     nb_indices: u32,
     ressource: D::Ressource,
     device: Rc<Device>,
+    /// The layouts and options needed to re-create the pipeline
+    viewer_desc: BindGroupLayoutDescriptor<'static>,
+    models_desc: BindGroupLayoutDescriptor<'static>,
+    fake: bool,
+    wireframe: bool,
+    outliner: bool,
 }
 
 impl<D: Instanciable> InstanceDrawer<D> {
@@ -296,41 +302,14 @@ impl<D: Instanciable> InstanceDrawer<D> {
             wgpu::BufferUsage::VERTEX,
         );
 
-        let vertex_module = if fake {
-            D::fake_vertex_module(&device).unwrap_or_else(|| D::vertex_module(&device))
-        } else if outliner {
-            D::outline_vertex_module(&device).unwrap_or_else(|| D::vertex_module(&device))
-        } else {
-            D::vertex_module(&device)
-        };
-
-        let fragment_module = if fake {
-            D::fake_fragment_module(&device).unwrap_or_else(|| D::fragment_module(&device))
-        } else if outliner {
-            D::outline_fragment_module(&device).unwrap_or_else(|| D::fragment_module(&device))
-        } else {
-            D::fragment_module(&device)
-        };
-
-        let primitive_topology = if wireframe {
-            match D::primitive_topology() {
-                PrimitiveTopology::TriangleList => PrimitiveTopology::LineList,
-                PrimitiveTopology::TriangleStrip => PrimitiveTopology::LineStrip,
-                pt => pt,
-            }
-        } else {
-            D::primitive_topology()
-        };
-
-        let pipeline = Self::create_pipeline(
+        let pipeline = Self::build_pipeline(
             &device,
             viewer_desc,
             models_desc,
-            vertex_module,
-            fragment_module,
-            primitive_topology,
             fake,
+            wireframe,
             outliner,
+            SAMPLE_COUNT,
         );
         let instances = DynamicBindGroup::new(device.clone(), queue);
 
@@ -361,9 +340,62 @@ impl<D: Instanciable> InstanceDrawer<D> {
             additional_bind_group,
             ressource,
             device,
+            viewer_desc: viewer_desc.clone(),
+            models_desc: models_desc.clone(),
+            fake,
+            wireframe,
+            outliner,
         }
     }
 
+    fn build_pipeline(
+        device: &Device,
+        viewer_desc: &BindGroupLayoutDescriptor<'static>,
+        models_desc: &BindGroupLayoutDescriptor<'static>,
+        fake: bool,
+        wireframe: bool,
+        outliner: bool,
+        sample_count: u32,
+    ) -> RenderPipeline {
+        let vertex_module = if fake {
+            D::fake_vertex_module(device).unwrap_or_else(|| D::vertex_module(device))
+        } else if outliner {
+            D::outline_vertex_module(device).unwrap_or_else(|| D::vertex_module(device))
+        } else {
+            D::vertex_module(device)
+        };
+
+        let fragment_module = if fake {
+            D::fake_fragment_module(device).unwrap_or_else(|| D::fragment_module(device))
+        } else if outliner {
+            D::outline_fragment_module(device).unwrap_or_else(|| D::fragment_module(device))
+        } else {
+            D::fragment_module(device)
+        };
+
+        let primitive_topology = if wireframe {
+            match D::primitive_topology() {
+                PrimitiveTopology::TriangleList => PrimitiveTopology::LineList,
+                PrimitiveTopology::TriangleStrip => PrimitiveTopology::LineStrip,
+                pt => pt,
+            }
+        } else {
+            D::primitive_topology()
+        };
+
+        Self::create_pipeline(
+            device,
+            viewer_desc,
+            models_desc,
+            vertex_module,
+            fragment_module,
+            primitive_topology,
+            fake,
+            outliner,
+            sample_count,
+        )
+    }
+
     pub fn new_instances(&mut self, instances: Vec<D>) {
         let raw_instances: Vec<D::RawInstance> =
             instances.iter().map(|d| d.to_raw_instance()).collect();
@@ -395,6 +427,7 @@ impl<D: Instanciable> InstanceDrawer<D> {
         primitive_topology: PrimitiveTopology,
         fake: bool,
         outliner: bool,
+        sample_count: u32,
     ) -> RenderPipeline {
         let viewer_bind_group_layout =
             device.create_bind_group_layout(&viewer_bind_group_layout_desc);
@@ -443,7 +476,7 @@ impl<D: Instanciable> InstanceDrawer<D> {
             wgpu::BlendState::REPLACE
         };
 
-        let sample_count = if fake { 1 } else { SAMPLE_COUNT };
+        let sample_count = if fake { 1 } else { sample_count };
 
         let instance_bind_group_layout_desc = BindGroupLayoutDescriptor {
             label: None,
@@ -553,6 +586,14 @@ pub trait RawDrawer {
     );
 
     fn new_instances_raw(&mut self, instances_raw: &Vec<Self::RawInstance>);
+
+    /// Overwrite some of the instances. Each instance comes with its index in the instances
+    /// buffer.
+    fn update_instances_raw(&mut self, instances_raw: &[(usize, Self::RawInstance)]);
+
+    /// Re-create the pipeline so that it renders on textures with `sample_count` samples.
+    /// Drawers of fake textures always use a single sample and are not affected.
+    fn set_sample_count(&mut self, sample_count: u32);
 }
 
 impl<D: Instanciable> RawDrawer for InstanceDrawer<D> {
@@ -563,6 +604,30 @@ impl<D: Instanciable> RawDrawer for InstanceDrawer<D> {
         self.instances.update(instances_raw.as_slice());
     }
 
+    fn update_instances_raw(&mut self, instances_raw: &[(usize, D::RawInstance)]) {
+        let instance_size = std::mem::size_of::<D::RawInstance>();
+        for (idx, instance) in instances_raw.iter() {
+            if *idx < self.nb_instances as usize {
+                self.instances
+                    .update_offset(idx * instance_size, bytemuck::bytes_of(instance));
+            }
+        }
+    }
+
+    fn set_sample_count(&mut self, sample_count: u32) {
+        if !self.fake {
+            self.pipeline = Self::build_pipeline(
+                &self.device,
+                &self.viewer_desc,
+                &self.models_desc,
+                self.fake,
+                self.wireframe,
+                self.outliner,
+                sample_count,
+            );
+        }
+    }
+
     fn draw<'a>(
         &'a mut self,
         render_pass: &mut RenderPass<'a>,
@@ -73,6 +73,14 @@ impl RotationWidget {
         }
     }
 
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        self.sphere_drawer.set_sample_count(sample_count);
+        for drawer in self.circle_drawers.iter_mut() {
+            drawer.set_sample_count(sample_count);
+        }
+        self.big_circle_drawer.set_sample_count(sample_count);
+    }
+
     pub fn update_decriptor(
         &mut self,
         descriptor: Option<RotationWidgetDescriptor>,
@@ -105,6 +113,15 @@ impl RotationWidget {
         self.big_circle_drawer.new_object(self.big_circle);
     }
 
+    /// Scale the widget by a factor `scale`
+    pub fn set_scale(&mut self, scale: f32, camera: CameraPtr, projection: ProjectionPtr) {
+        if let Some(descriptor) = self.descriptor.as_mut() {
+            descriptor.scale = scale;
+        }
+        self.update_camera(camera, projection);
+        self.select_circle(self.selected);
+    }
+
     pub fn update_camera(&mut self, camera: CameraPtr, projection: ProjectionPtr) {
         self.sphere = self
             .descriptor
@@ -219,6 +236,8 @@ pub struct RotationWidgetDescriptor {
     pub origin: Vec3,
     pub orientation: RotationWidgetOrientation,
     pub size: f32,
+    /// A factor applied to `size`, that allows the user to make the widgets bigger
+    pub scale: f32,
     pub only_right: bool,
 }
 
@@ -231,7 +250,7 @@ impl RotationWidgetDescriptor {
     fn make_circles(&self, camera: CameraPtr, projection: ProjectionPtr) -> [Circle; 3] {
         let dist = (camera.borrow().position - self.origin).mag();
         let (right, up, dir) = self.make_axis();
-        let length = self.size * dist * (projection.borrow().get_fovy() / 2.).tan() * 1.1;
+        let length = self.scale * self.size * projection.borrow().half_height_at(dist) * 1.1;
         let filter = if self.only_right { 0f32 } else { 1f32 };
         [
             Circle::new(self.origin, length, up, dir, 0xFF_00_00, RIGHT_CIRCLE_ID),
@@ -256,7 +275,7 @@ impl RotationWidgetDescriptor {
 
     fn make_sphere(&self, camera: CameraPtr, projection: ProjectionPtr) -> Sphere {
         let dist = (camera.borrow().position - self.origin).mag();
-        let length = self.size * dist * (projection.borrow().get_fovy() / 2.).tan();
+        let length = self.scale * self.size * projection.borrow().half_height_at(dist);
         Sphere::new(self.origin, length, 0xA0_54_54_44, SPHERE_WIDGET_ID)
     }
 
@@ -0,0 +1,113 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! An animation that makes the selected elements grow and shrink periodically.
+
+use super::RawDnaInstance;
+use std::time::Instant;
+
+/// The maximal relative variation of the size of the selected elements
+const PULSE_AMPLITUDE: f32 = 0.25;
+
+pub struct SelectionPulse {
+    /// The number of pulses per second
+    pub frequency: f32,
+    start: Instant,
+}
+
+impl SelectionPulse {
+    pub fn new(frequency: f32) -> Self {
+        Self {
+            frequency,
+            start: Instant::now(),
+        }
+    }
+
+    /// The number of seconds elapsed since the begining of the animation
+    pub fn elapsed(&self) -> f32 {
+        self.start.elapsed().as_secs_f32()
+    }
+
+    /// The factor by which the selected elements are scaled `time` seconds after the begining of
+    /// the animation. It goes from 1 to `1 + PULSE_AMPLITUDE` and back once per period.
+    pub fn scale_factor(&self, time: f32) -> f32 {
+        let phase = 2. * std::f32::consts::PI * self.frequency * time;
+        1. + PULSE_AMPLITUDE * 0.5 * (1. - phase.cos())
+    }
+
+    /// Return the instances scaled for the frame drawn `time` seconds after the begining of the
+    /// animation. If `spheres` is false, the instances are tubes and only their radius is scaled.
+    pub fn pulsed_instances(
+        &self,
+        instances: &[RawDnaInstance],
+        time: f32,
+        spheres: bool,
+    ) -> Vec<RawDnaInstance> {
+        let factor = self.scale_factor(time);
+        instances
+            .iter()
+            .map(|instance| {
+                let mut instance = *instance;
+                if spheres {
+                    instance.scale *= factor;
+                } else {
+                    instance.scale.y *= factor;
+                    instance.scale.z *= factor;
+                }
+                instance
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Instanciable, SphereInstance};
+    use super::*;
+    use ultraviolet::{Vec3, Vec4};
+
+    #[test]
+    fn scale_varies_between_ticks() {
+        let pulse = SelectionPulse::new(1.);
+        let sphere = SphereInstance {
+            position: Vec3::zero(),
+            color: Vec4::one(),
+            id: 0,
+            radius: 1.,
+        }
+        .to_raw_instance();
+        let tick = 1. / 60.;
+        let first = pulse.pulsed_instances(&[sphere], 0.1, true)[0];
+        let second = pulse.pulsed_instances(&[sphere], 0.1 + tick, true)[0];
+        assert!((first.scale - second.scale).mag() > 1e-3);
+        assert!(first.scale.x >= sphere.scale.x);
+    }
+
+    #[test]
+    fn one_pulse_per_period() {
+        let frequency = 2.;
+        let pulse = SelectionPulse::new(frequency);
+        let period = 1. / frequency;
+        assert!((pulse.scale_factor(0.) - 1.).abs() < 1e-5);
+        assert!((pulse.scale_factor(period / 2.) - (1. + PULSE_AMPLITUDE)).abs() < 1e-5);
+        assert!((pulse.scale_factor(period) - 1.).abs() < 1e-5);
+        let time = 0.1;
+        assert!((pulse.scale_factor(time) - pulse.scale_factor(time + period)).abs() < 1e-5);
+        // Half a period later, the elements are not at the same point of the pulse
+        assert!((pulse.scale_factor(time) - pulse.scale_factor(time + period / 2.)).abs() > 1e-2);
+    }
+}
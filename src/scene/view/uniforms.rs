@@ -31,6 +31,9 @@ pub struct Uniforms {
     pub make_fog: u32,
     pub fog_from_camera: u32,
     pub fog_alt_center: Vec3,
+    /// Distance along the camera's direction under which fragments are discarded
+    pub clip_distance: f32,
+    pub make_clip: u32,
 }
 
 unsafe impl bytemuck::Pod for Uniforms {}
@@ -47,6 +50,8 @@ impl Uniforms {
             make_fog: false as u32,
             fog_from_camera: false as u32,
             fog_alt_center: Vec3::zero(),
+            clip_distance: 0.,
+            make_clip: false as u32,
         }
     }
 
@@ -68,11 +73,20 @@ impl Uniforms {
             make_fog: make_fog as u32,
             fog_from_camera: fog.from_camera as u32,
             fog_alt_center: fog.alt_fog_center.unwrap_or(Vec3::zero()),
+            clip_distance: 0.,
+            make_clip: false as u32,
         }
     }
+
+    /// Set the near clipping plane. If `clip_distance` is `None`, no clipping occurs.
+    pub fn with_clip_distance(mut self, clip_distance: Option<f32>) -> Self {
+        self.make_clip = clip_distance.is_some() as u32;
+        self.clip_distance = clip_distance.unwrap_or(0.);
+        self
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FogParameters {
     pub radius: f32,
     pub length: f32,
@@ -67,6 +67,90 @@ impl BufferDimensions {
     }
 }
 
+/// Copy `texture` to a staging buffer with `encoder`, submit `encoder` and return the RGBA values
+/// of the pixels of `texture`, row by row.
+///
+/// `texture` must have size `extent`, a BGRA format and the `COPY_SRC` usage.
+pub fn read_texture_rgba(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    mut encoder: wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    extent: wgpu::Extent3d,
+) -> Result<Vec<u8>, wgpu::BufferAsyncError> {
+    let buffer_dimensions = BufferDimensions::new(extent.width as usize, extent.height as usize);
+    let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        size: (buffer_dimensions.padded_bytes_per_row * buffer_dimensions.height) as u64,
+        usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+        label: Some("texture staging buffer"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::TextureCopyView {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x: 0, y: 0, z: 0 },
+        },
+        wgpu::BufferCopyView {
+            buffer: &staging_buffer,
+            layout: wgpu::TextureDataLayout {
+                offset: 0,
+                bytes_per_row: buffer_dimensions.padded_bytes_per_row as u32,
+                rows_per_image: 0,
+            },
+        },
+        extent,
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = staging_buffer.slice(..);
+    let buffer_future = buffer_slice.map_async(wgpu::MapMode::Read);
+    device.poll(wgpu::Maintain::Wait);
+    futures::executor::block_on(buffer_future)?;
+    let pixels_slice = buffer_slice.get_mapped_range();
+    let mut pixels =
+        Vec::with_capacity(buffer_dimensions.unpadded_bytes_per_row * buffer_dimensions.height);
+    for chunck in pixels_slice.chunks(buffer_dimensions.padded_bytes_per_row) {
+        // The texture is in BGRA format
+        for bgra in chunck[..buffer_dimensions.unpadded_bytes_per_row].chunks(4) {
+            pixels.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+        }
+    }
+    drop(pixels_slice);
+    staging_buffer.unmap();
+    Ok(pixels)
+}
+
+/// Return a device and a queue for the tests that render with a GPU.
+///
+/// These tests are ignored by default and must be run with `cargo test -- --ignored` on a machine
+/// that has a GPU.
+#[cfg(test)]
+pub fn gpu() -> (std::rc::Rc<wgpu::Device>, std::rc::Rc<wgpu::Queue>) {
+    futures::executor::block_on(async {
+        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::LowPower,
+                compatible_surface: None,
+            })
+            .await
+            .expect("No GPU available");
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: wgpu::Features::empty(),
+                    limits: wgpu::Limits::default(),
+                    label: None,
+                },
+                None,
+            )
+            .await
+            .expect("Could not get a device");
+        (std::rc::Rc::new(device), std::rc::Rc::new(queue))
+    })
+}
+
 pub fn phantom_helix_encoder_nucl(
     design_id: u32,
     helix_id: u32,
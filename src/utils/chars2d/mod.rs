@@ -50,6 +50,8 @@ pub struct CharDrawer {
     /// The pipeline created by `self`
     pipeline: Option<RenderPipeline>,
     letter: Rc<Letter>,
+    /// The number of samples of the textures on which the characters are drawn
+    sample_count: u32,
 }
 
 impl CharDrawer {
@@ -75,12 +77,21 @@ impl CharDrawer {
             pipeline: None,
             instances_bg,
             letter: char_texture.clone(),
+            sample_count: SAMPLE_COUNT,
         };
         let pipeline = ret.create_pipeline(globals_layout);
         ret.pipeline = Some(pipeline);
         ret
     }
 
+    /// Re-create the pipeline so that it draws on textures with `sample_count` samples.
+    pub fn set_sample_count(&mut self, globals_layout: &BindGroupLayout, sample_count: u32) {
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.pipeline = Some(self.create_pipeline(globals_layout));
+        }
+    }
+
     pub fn draw<'a>(&'a mut self, render_pass: &mut RenderPass<'a>) {
         self.update_instances();
         render_pass.set_pipeline(self.pipeline.as_ref().unwrap());
@@ -106,6 +117,11 @@ impl CharDrawer {
         self.letter.advance
     }
 
+    /// The advancement used for characters that have no drawer
+    pub fn fallback_advance() -> f32 {
+        FALLBACK_CHAR_ADVANCE
+    }
+
     /// Create a render pipepline. This function is meant to be called once, before drawing for the
     /// first time.
     fn create_pipeline(&self, globals_layout: &BindGroupLayout) -> RenderPipeline {
@@ -178,7 +194,7 @@ impl CharDrawer {
                     clamp_depth: Default::default(),
                 }),
                 multisample: wgpu::MultisampleState {
-                    count: SAMPLE_COUNT,
+                    count: self.sample_count,
                     mask: !0,
                     alpha_to_coverage_enabled: false,
                 },
@@ -187,20 +203,43 @@ impl CharDrawer {
     }
 }
 
+/// Return the abscissa of the start of each character of `string`, followed by the abscissa of
+/// its end. Characters that have no drawer advance by `CharDrawer::fallback_advance()`.
 pub fn char_positions(string: String, drawers: &HashMap<char, CharDrawer>) -> Vec<f32> {
     let mut ret = vec![0f32];
     let mut x = 0f32;
     for c in string.chars() {
-        x += drawers.get(&c).unwrap().advancement_x();
+        x += drawers
+            .get(&c)
+            .map(|d| d.advancement_x())
+            .unwrap_or_else(CharDrawer::fallback_advance);
         ret.push(x);
     }
     ret
 }
 
+/// Return the height of the highest character of `string`. Characters that have no drawer are
+/// ignored.
 pub fn height(string: String, drawers: &HashMap<char, CharDrawer>) -> f32 {
     let mut ret = 0f32;
     for c in string.chars() {
-        ret = ret.max(drawers.get(&c).unwrap().letter.height)
+        if let Some(drawer) = drawers.get(&c) {
+            ret = ret.max(drawer.letter.height)
+        }
     }
     ret
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_chars_use_fallback_advance() {
+        let drawers = HashMap::new();
+        let positions = char_positions("a*T".to_string(), &drawers);
+        let advance = CharDrawer::fallback_advance();
+        assert_eq!(positions, vec![0., advance, 2. * advance, 3. * advance]);
+        assert_eq!(height("a*T".to_string(), &drawers), 0.);
+    }
+}
@@ -53,6 +53,10 @@ impl CircleInstance {
         self.color = color
     }
 
+    pub fn color(&self) -> u32 {
+        self.color
+    }
+
     #[allow(dead_code)]
     pub fn angle(self, angle: f32) -> Self {
         Self { angle, ..self }
@@ -89,8 +93,12 @@ pub struct CircleDrawer {
     instances_bg: DynamicBindGroup,
     /// The pipeline created by `self`
     pipeline: Option<RenderPipeline>,
+    circle_kind: CircleKind,
+    /// The number of samples of the textures on which the circles are drawn
+    sample_count: u32,
 }
 
+#[derive(Clone, Copy)]
 pub enum CircleKind {
     FullCircle,
     RotationWidget,
@@ -111,12 +119,22 @@ impl CircleDrawer {
             number_instances: 0,
             pipeline: None,
             instances_bg,
+            circle_kind,
+            sample_count: SAMPLE_COUNT,
         };
-        let pipeline = ret.create_pipeline(globals_layout, circle_kind);
+        let pipeline = ret.create_pipeline(globals_layout);
         ret.pipeline = Some(pipeline);
         ret
     }
 
+    /// Re-create the pipeline so that it draws on textures with `sample_count` samples.
+    pub fn set_sample_count(&mut self, globals_layout: &BindGroupLayout, sample_count: u32) {
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.pipeline = Some(self.create_pipeline(globals_layout));
+        }
+    }
+
     pub fn draw<'a>(&'a mut self, render_pass: &mut RenderPass<'a>) {
         self.update_instances();
         render_pass.set_pipeline(self.pipeline.as_ref().unwrap());
@@ -138,16 +156,12 @@ impl CircleDrawer {
 
     /// Create a render pipepline. This function is meant to be called once, before drawing for the
     /// first time.
-    fn create_pipeline(
-        &self,
-        globals_layout: &BindGroupLayout,
-        circle_kind: CircleKind,
-    ) -> RenderPipeline {
+    fn create_pipeline(&self, globals_layout: &BindGroupLayout) -> RenderPipeline {
         let vertex_module = self
             .device
             .create_shader_module(&include_spirv!("circle.vert.spv"));
 
-        let fragment_module = match circle_kind {
+        let fragment_module = match self.circle_kind {
             CircleKind::FullCircle => self
                 .device
                 .create_shader_module(&include_spirv!("circle.frag.spv")),
@@ -215,7 +229,7 @@ impl CircleDrawer {
                     clamp_depth: false,
                 }),
                 multisample: wgpu::MultisampleState {
-                    count: SAMPLE_COUNT,
+                    count: self.sample_count,
                     mask: !0,
                     alpha_to_coverage_enabled: false,
                 },
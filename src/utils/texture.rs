@@ -36,6 +36,11 @@ pub struct SampledTexture {
 impl Texture {
     pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float; // 1.
 
+    /// The sample counts that can be used for the multisampled textures. The version of wgpu that
+    /// we use does not let us query the sample counts supported by the adapter, so these are the
+    /// ones that every adapter must support for the color and depth formats that we use.
+    pub const SUPPORTED_SAMPLE_COUNTS: [u32; 2] = [1, 4];
+
     pub fn create_depth_texture(device: &wgpu::Device, size: &PhySize, sample_count: u32) -> Self {
         let size = wgpu::Extent3d {
             width: size.width,